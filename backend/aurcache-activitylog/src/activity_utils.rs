@@ -1,15 +1,23 @@
 use crate::activity_serializer::ActivitySerializer;
+use crate::build_cancel_activity::BuildCancelActivity;
+use crate::build_delete_activity::BuildDeleteActivity;
+use crate::build_finish_activity::BuildFinishActivity;
+use crate::build_retry_activity::BuildRetryActivity;
+use crate::build_start_activity::BuildStartActivity;
 use crate::package_add_activity::PackageAddActivity;
 use crate::package_delete_activity::PackageDeleteActivity;
+use crate::package_patch_activity::PackagePatchActivity;
 use crate::package_update_activity::PackageUpdateActivity;
+use crate::repo_repair_activity::RepoRepairActivity;
+use crate::setting_update_activity::SettingUpdateActivity;
 use anyhow::anyhow;
 use aurcache_db::activities;
 use aurcache_db::activities::ActivityType;
 use aurcache_db::prelude::Activities;
 use sea_orm::ActiveValue::Set;
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, EntityTrait, FromQueryResult, Order, QueryOrder,
-    QuerySelect,
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, FromQueryResult,
+    Order, QueryFilter, QueryOrder, QuerySelect,
 };
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -22,6 +30,77 @@ pub struct Activity {
     pub user: Option<String>,
 }
 
+/// Structured audit view of a single activity entry.
+#[derive(Deserialize, ToSchema, Serialize)]
+pub struct AuditEntry {
+    pub id: i32,
+    pub typ: String,
+    pub timestamp: i64,
+    pub user: Option<String>,
+    pub ip: Option<String>,
+    pub pkg_id: Option<i32>,
+    pub text: String,
+    /// Raw activity payload, including old and new values where applicable
+    #[schema(value_type = Object)]
+    pub data: serde_json::Value,
+}
+
+/// Who triggered an activity, from where, and which package it concerns.
+#[derive(Debug, Clone, Default)]
+pub struct ActivityMeta {
+    pub user: Option<String>,
+    pub ip: Option<String>,
+    pub pkg_id: Option<i32>,
+}
+
+impl ActivityMeta {
+    /// Activity triggered by AURCache itself (e.g. scheduled jobs)
+    #[must_use]
+    pub fn server(pkg_id: Option<i32>) -> Self {
+        Self {
+            user: Some("Server".to_string()),
+            ip: None,
+            pkg_id,
+        }
+    }
+}
+
+/// Filter for querying the activity log. Empty fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct ActivityFilter {
+    pub user: Option<String>,
+    pub types: Vec<ActivityType>,
+    pub pkg_id: Option<i32>,
+    /// unix timestamp (inclusive)
+    pub from: Option<i64>,
+    /// unix timestamp (inclusive)
+    pub to: Option<i64>,
+    pub limit: Option<u64>,
+    pub page: Option<u64>,
+}
+
+impl ActivityFilter {
+    fn condition(&self) -> Condition {
+        let mut condition = Condition::all();
+        if let Some(user) = &self.user {
+            condition = condition.add(activities::Column::User.eq(user.clone()));
+        }
+        if !self.types.is_empty() {
+            condition = condition.add(activities::Column::Typ.is_in(self.types.clone()));
+        }
+        if let Some(pkg_id) = self.pkg_id {
+            condition = condition.add(activities::Column::PkgId.eq(pkg_id));
+        }
+        if let Some(from) = self.from {
+            condition = condition.add(activities::Column::Timestamp.gte(from));
+        }
+        if let Some(to) = self.to {
+            condition = condition.add(activities::Column::Timestamp.lte(to));
+        }
+        condition
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ActivityLog {
     db: DatabaseConnection,
@@ -37,7 +116,7 @@ impl ActivityLog {
         &self,
         activity: T,
         activity_type: ActivityType,
-        user: Option<String>,
+        meta: ActivityMeta,
     ) -> anyhow::Result<()> {
         let activity = serde_json::to_string(&activity)?;
         let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
@@ -45,7 +124,9 @@ impl ActivityLog {
         activities::ActiveModel {
             timestamp: Set(timestamp),
             data: Set(activity),
-            user: Set(user),
+            user: Set(meta.user),
+            ip: Set(meta.ip),
+            pkg_id: Set(meta.pkg_id),
             typ: Set(activity_type),
             ..std::default::Default::default()
         }
@@ -55,15 +136,9 @@ impl ActivityLog {
         Ok(())
     }
 
-    pub async fn list(&self, limit: Option<u64>) -> anyhow::Result<Vec<Activity>> {
+    pub async fn list(&self, filter: &ActivityFilter) -> anyhow::Result<Vec<Activity>> {
         // List activities from database
-        let activities = Activities::find()
-            .order_by(activities::Column::Timestamp, Order::Desc)
-            .limit(limit)
-            .into_model::<activities::Model>()
-            .all(&self.db)
-            .await
-            .map_err(|e| anyhow!(e.to_string()))?;
+        let activities = self.find(filter).await?;
 
         let t: Vec<Activity> = activities
             .iter()
@@ -82,6 +157,59 @@ impl ActivityLog {
         Ok(t)
     }
 
+    /// List audit entries matching the filter, newest first
+    pub async fn audit(&self, filter: &ActivityFilter) -> anyhow::Result<Vec<AuditEntry>> {
+        let activities = self.find(filter).await?;
+
+        Ok(activities
+            .into_iter()
+            .map(|x| {
+                let text = self
+                    .deserialize_type(x.typ, &x.data)
+                    .map(|v| v.format())
+                    .unwrap_or_default();
+                AuditEntry {
+                    id: x.id,
+                    typ: x.typ.as_str().to_string(),
+                    timestamp: x.timestamp,
+                    user: x.user,
+                    ip: x.ip,
+                    pkg_id: x.pkg_id,
+                    text,
+                    data: serde_json::from_str(&x.data).unwrap_or(serde_json::Value::Null),
+                }
+            })
+            .collect())
+    }
+
+    /// Export audit entries matching the filter as JSON Lines (one entry per line)
+    pub async fn export_jsonl(&self, filter: &ActivityFilter) -> anyhow::Result<String> {
+        let mut out = String::new();
+        for entry in self.audit(filter).await? {
+            out.push_str(&serde_json::to_string(&entry)?);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// Fetch the raw activity rows matching the filter, newest first
+    async fn find(&self, filter: &ActivityFilter) -> anyhow::Result<Vec<activities::Model>> {
+        Activities::find()
+            .filter(filter.condition())
+            .order_by(activities::Column::Timestamp, Order::Desc)
+            .order_by(activities::Column::Id, Order::Desc)
+            .limit(filter.limit)
+            .offset(
+                filter
+                    .page
+                    .zip(filter.limit)
+                    .map(|(page, limit)| page * limit),
+            )
+            .all(&self.db)
+            .await
+            .map_err(|e| anyhow!(e.to_string()))
+    }
+
     fn deserialize_type(
         &self,
         activity_type: ActivityType,
@@ -97,8 +225,30 @@ impl ActivityLog {
             ActivityType::UpdatePackage => Ok(Box::from(serde_json::from_str::<
                 PackageUpdateActivity,
             >(data)?)),
-            ActivityType::StartBuild => todo!("StartBuild"),
-            ActivityType::FinishBuild => todo!("FinishBuild"),
+            ActivityType::StartBuild => {
+                Ok(Box::from(serde_json::from_str::<BuildStartActivity>(data)?))
+            }
+            ActivityType::FinishBuild => Ok(Box::from(
+                serde_json::from_str::<BuildFinishActivity>(data)?,
+            )),
+            ActivityType::PatchPackage => Ok(Box::from(serde_json::from_str::<
+                PackagePatchActivity,
+            >(data)?)),
+            ActivityType::PatchSetting | ActivityType::ResetSetting => Ok(Box::from(
+                serde_json::from_str::<SettingUpdateActivity>(data)?,
+            )),
+            ActivityType::CancelBuild => Ok(Box::from(
+                serde_json::from_str::<BuildCancelActivity>(data)?,
+            )),
+            ActivityType::RetryBuild => {
+                Ok(Box::from(serde_json::from_str::<BuildRetryActivity>(data)?))
+            }
+            ActivityType::RepairRepo => {
+                Ok(Box::from(serde_json::from_str::<RepoRepairActivity>(data)?))
+            }
+            ActivityType::DeleteBuild => Ok(Box::from(
+                serde_json::from_str::<BuildDeleteActivity>(data)?,
+            )),
         }
     }
 }
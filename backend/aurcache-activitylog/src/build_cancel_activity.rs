@@ -0,0 +1,18 @@
+use crate::activity_serializer::ActivitySerializer;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BuildCancelActivity {
+    pub package: String,
+    pub build_id: i32,
+    pub old_status: Option<i32>,
+}
+
+impl ActivitySerializer for BuildCancelActivity {
+    fn format(&self) -> String {
+        format!(
+            "canceled build #{} of package {}",
+            self.build_id, self.package
+        )
+    }
+}
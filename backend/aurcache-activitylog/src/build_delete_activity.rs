@@ -0,0 +1,20 @@
+use crate::activity_serializer::ActivitySerializer;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BuildDeleteActivity {
+    pub package: String,
+    pub build_id: i32,
+    pub version: String,
+    pub platform: String,
+    pub status: Option<i32>,
+}
+
+impl ActivitySerializer for BuildDeleteActivity {
+    fn format(&self) -> String {
+        format!(
+            "deleted build #{} of package {} ({}, {})",
+            self.build_id, self.package, self.version, self.platform
+        )
+    }
+}
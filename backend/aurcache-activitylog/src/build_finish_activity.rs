@@ -0,0 +1,28 @@
+use crate::activity_serializer::ActivitySerializer;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BuildFinishActivity {
+    pub package: String,
+    pub build_id: i32,
+    pub version: String,
+    pub platform: String,
+    pub success: bool,
+    /// build duration in seconds
+    pub duration: Option<i64>,
+    pub error: Option<String>,
+}
+
+impl ActivitySerializer for BuildFinishActivity {
+    fn format(&self) -> String {
+        let outcome = if self.success { "finished" } else { "failed" };
+        let duration = self
+            .duration
+            .map(|d| format!(" after {d}s"))
+            .unwrap_or_default();
+        format!(
+            "{} build #{} of package {} ({}, {}){}",
+            outcome, self.build_id, self.package, self.version, self.platform, duration
+        )
+    }
+}
@@ -0,0 +1,18 @@
+use crate::activity_serializer::ActivitySerializer;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BuildRetryActivity {
+    pub package: String,
+    pub build_id: i32,
+    pub new_build_id: i32,
+}
+
+impl ActivitySerializer for BuildRetryActivity {
+    fn format(&self) -> String {
+        format!(
+            "retried build #{} of package {} as build #{}",
+            self.build_id, self.package, self.new_build_id
+        )
+    }
+}
@@ -0,0 +1,19 @@
+use crate::activity_serializer::ActivitySerializer;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BuildStartActivity {
+    pub package: String,
+    pub build_id: i32,
+    pub version: String,
+    pub platform: String,
+}
+
+impl ActivitySerializer for BuildStartActivity {
+    fn format(&self) -> String {
+        format!(
+            "started build #{} of package {} ({}, {})",
+            self.build_id, self.package, self.version, self.platform
+        )
+    }
+}
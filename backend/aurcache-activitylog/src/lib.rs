@@ -1,5 +1,13 @@
 pub mod activity_serializer;
 pub mod activity_utils;
+pub mod build_cancel_activity;
+pub mod build_delete_activity;
+pub mod build_finish_activity;
+pub mod build_retry_activity;
+pub mod build_start_activity;
 pub mod package_add_activity;
 pub mod package_delete_activity;
+pub mod package_patch_activity;
 pub mod package_update_activity;
+pub mod repo_repair_activity;
+pub mod setting_update_activity;
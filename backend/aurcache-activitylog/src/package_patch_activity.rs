@@ -0,0 +1,40 @@
+use crate::activity_serializer::ActivitySerializer;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PackagePatchActivity {
+    pub package: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+impl PackagePatchActivity {
+    /// Snapshot old and new values, dropping all fields left unset (`null`) by the patch.
+    pub fn new<T: Serialize>(package: String, old: &T, new: &T) -> anyhow::Result<Self> {
+        Ok(Self {
+            package,
+            old: strip_nulls(serde_json::to_value(old)?),
+            new: strip_nulls(serde_json::to_value(new)?),
+        })
+    }
+}
+
+fn strip_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(mut map) => {
+            map.retain(|_, v| !v.is_null());
+            Value::Object(map)
+        }
+        v => v,
+    }
+}
+
+impl ActivitySerializer for PackagePatchActivity {
+    fn format(&self) -> String {
+        format!(
+            "changed package {}: {} -> {}",
+            self.package, self.old, self.new
+        )
+    }
+}
@@ -0,0 +1,23 @@
+use crate::activity_serializer::ActivitySerializer;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RepoRepairActivity {
+    pub orphan_archives: bool,
+    pub missing_files: bool,
+    pub repo_database: bool,
+}
+
+impl ActivitySerializer for RepoRepairActivity {
+    fn format(&self) -> String {
+        let fixes: Vec<&str> = [
+            (self.orphan_archives, "orphan archives"),
+            (self.missing_files, "missing files"),
+            (self.repo_database, "repo database"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, name)| enabled.then_some(name))
+        .collect();
+        format!("repaired repo ({})", fixes.join(", "))
+    }
+}
@@ -0,0 +1,25 @@
+use crate::activity_serializer::ActivitySerializer;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SettingUpdateActivity {
+    pub key: String,
+    pub pkg_id: Option<i32>,
+    pub old: Option<String>,
+    /// `None` if the setting was reset to its default
+    pub new: Option<String>,
+}
+
+impl ActivitySerializer for SettingUpdateActivity {
+    fn format(&self) -> String {
+        let scope = match self.pkg_id {
+            None => "global setting".to_string(),
+            Some(pkg_id) => format!("setting of package #{pkg_id}"),
+        };
+        let old = self.old.as_deref().unwrap_or_default();
+        match &self.new {
+            None => format!("reset {scope} {} (was '{old}')", self.key),
+            Some(new) => format!("changed {scope} {} from '{old}' to '{new}'", self.key),
+        }
+    }
+}
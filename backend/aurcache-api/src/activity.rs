@@ -1,25 +1,101 @@
+use crate::models::activity::ActivityQuery;
 use crate::models::authenticated::Authenticated;
-use aurcache_activitylog::activity_utils::{Activity, ActivityLog};
-use rocket::response::status::NotFound;
+use aurcache_activitylog::activity_utils::{Activity, ActivityLog, AuditEntry};
+use rocket::http::{ContentType, Status};
+use rocket::response::status::Custom;
 use rocket::serde::json::Json;
 use rocket::{State, get};
 use utoipa::OpenApi;
 
 #[derive(OpenApi)]
-#[openapi(paths(activity))]
+#[openapi(paths(activity, audit, audit_export))]
 pub struct ActivityApi;
 
 #[utoipa::path(
     responses(
             (status = 200, description = "Get last n Activity entries", body = [Vec<Activity>]),
+            (status = 400, description = "Invalid filter"),
+    ),
+    params(
+            ("typ" = Option<Vec<String>>, Query, description = "Only entries of these types (e.g. finish_build)"),
+            ("pkgid" = Option<i32>, Query, description = "Only entries concerning this package"),
+            ("limit" = Option<u64>, Query, description = "Limit of entries to fetch"),
+            ("page" = Option<u64>, Query, description = "Page to fetch"),
     )
 )]
-#[get("/activity?<limit>")]
+#[get("/activity?<query..>")]
 pub async fn activity(
     _a: Authenticated,
     al: &State<ActivityLog>,
-    limit: Option<u64>,
-) -> Result<Json<Vec<Activity>>, NotFound<String>> {
-    let activities = al.list(limit).await;
-    Ok(Json(activities.map_err(|e| NotFound(e.to_string()))?))
+    query: ActivityQuery,
+) -> Result<Json<Vec<Activity>>, Custom<String>> {
+    let filter = query
+        .into_filter()
+        .map_err(|e| Custom(Status::BadRequest, e.to_string()))?;
+
+    al.list(&filter)
+        .await
+        .map(Json)
+        .map_err(|e| Custom(Status::NotFound, e.to_string()))
+}
+
+#[utoipa::path(
+    responses(
+            (status = 200, description = "Get filtered audit log entries", body = [Vec<AuditEntry>]),
+            (status = 400, description = "Invalid filter"),
+    ),
+    params(
+            ("user" = Option<String>, Query, description = "Only entries of this user"),
+            ("typ" = Option<Vec<String>>, Query, description = "Only entries of these types (e.g. patch_package)"),
+            ("pkgid" = Option<i32>, Query, description = "Only entries concerning this package"),
+            ("from" = Option<i64>, Query, description = "Start of date range (unix timestamp)"),
+            ("to" = Option<i64>, Query, description = "End of date range (unix timestamp)"),
+            ("limit" = Option<u64>, Query, description = "Limit of entries to fetch"),
+            ("page" = Option<u64>, Query, description = "Page to fetch"),
+    )
+)]
+#[get("/audit?<query..>")]
+pub async fn audit(
+    _a: Authenticated,
+    al: &State<ActivityLog>,
+    query: ActivityQuery,
+) -> Result<Json<Vec<AuditEntry>>, Custom<String>> {
+    let filter = query
+        .into_filter()
+        .map_err(|e| Custom(Status::BadRequest, e.to_string()))?;
+
+    al.audit(&filter)
+        .await
+        .map(Json)
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))
+}
+
+#[utoipa::path(
+    responses(
+            (status = 200, description = "Export filtered audit log entries as JSON Lines", content_type = "application/jsonl"),
+            (status = 400, description = "Invalid filter"),
+    ),
+    params(
+            ("user" = Option<String>, Query, description = "Only entries of this user"),
+            ("typ" = Option<Vec<String>>, Query, description = "Only entries of these types (e.g. patch_package)"),
+            ("pkgid" = Option<i32>, Query, description = "Only entries concerning this package"),
+            ("from" = Option<i64>, Query, description = "Start of date range (unix timestamp)"),
+            ("to" = Option<i64>, Query, description = "End of date range (unix timestamp)"),
+    )
+)]
+#[get("/audit/export?<query..>")]
+pub async fn audit_export(
+    _a: Authenticated,
+    al: &State<ActivityLog>,
+    query: ActivityQuery,
+) -> Result<(ContentType, String), Custom<String>> {
+    let filter = query
+        .into_filter()
+        .map_err(|e| Custom(Status::BadRequest, e.to_string()))?;
+
+    let jsonl = al
+        .export_jsonl(&filter)
+        .await
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+    Ok((ContentType::new("application", "jsonl"), jsonl))
 }
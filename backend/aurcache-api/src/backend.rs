@@ -1,11 +1,21 @@
-use crate::activity::activity;
+use crate::activity::{activity, audit, audit_export};
 use crate::aur::search;
-use crate::build::{build_output, cancel_build, delete_build, get_build, list_builds, rery_build};
+use crate::build::{
+    build_output, cancel_build, delete_build, get_build, list_builds, package_resource_trend,
+    rery_build,
+};
+use crate::bulk::{
+    packages_bulk_add, packages_bulk_delete, packages_bulk_patch, packages_bulk_update,
+};
 use crate::health::health;
+use crate::manifest::{manifest_apply, manifest_export};
+use crate::metrics::metrics;
+use crate::mirrors::{mirrors, rank_mirrors};
 use crate::package::{
     get_package, package_add_endpoint, package_del, package_list, package_update_endpoint,
     package_update_entity_endpoint,
 };
+use crate::repo::{repo_check, repo_files_search, repo_package_files, repo_repair};
 use crate::settings::{setting_get, setting_patch, setting_reset, settings};
 use crate::stats::{dashboard_graph_data, stats, user_info};
 use rocket::{Route, routes};
@@ -29,11 +39,27 @@ pub fn build_api() -> Vec<Route> {
         rery_build,
         package_update_endpoint,
         cancel_build,
+        package_resource_trend,
         health,
+        metrics,
+        mirrors,
+        rank_mirrors,
         activity,
+        audit,
+        audit_export,
+        repo_check,
+        repo_repair,
+        repo_files_search,
+        repo_package_files,
         settings,
         setting_get,
         setting_patch,
-        setting_reset
+        setting_reset,
+        manifest_apply,
+        manifest_export,
+        packages_bulk_add,
+        packages_bulk_update,
+        packages_bulk_delete,
+        packages_bulk_patch
     ]
 }
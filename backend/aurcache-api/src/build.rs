@@ -4,8 +4,15 @@ use rocket::serde::json::Json;
 use rocket::{State, delete, get, post};
 
 use crate::models::authenticated::Authenticated;
-use crate::models::builds::ListBuildsModel;
-use aurcache_db::prelude::Builds;
+use crate::models::builds::{
+    BuildDetailsModel, BuildResourceUsageModel, ListBuildsModel, ResourceTrendModel,
+};
+use aurcache_activitylog::activity_utils::ActivityLog;
+use aurcache_activitylog::build_cancel_activity::BuildCancelActivity;
+use aurcache_activitylog::build_delete_activity::BuildDeleteActivity;
+use aurcache_activitylog::build_retry_activity::BuildRetryActivity;
+use aurcache_db::activities::ActivityType;
+use aurcache_db::prelude::{Builds, Packages};
 use aurcache_db::{builds, packages};
 use aurcache_types::builder::{Action, BuildStates};
 use aurcache_utils::package::update::update_platform;
@@ -23,10 +30,22 @@ use utoipa::OpenApi;
     get_build,
     delete_build,
     cancel_build,
-    rery_build
+    rery_build,
+    package_resource_trend
 ))]
 pub struct BuildApi;
 
+/// container resource usage columns of a build
+const RESOURCE_COLUMNS: [builds::Column; 7] = [
+    builds::Column::PeakMemory,
+    builds::Column::CpuTime,
+    builds::Column::WallTime,
+    builds::Column::NetRx,
+    builds::Column::NetTx,
+    builds::Column::BlkRead,
+    builds::Column::BlkWrite,
+];
+
 #[utoipa::path(
     responses(
             (status = 200, description = "get build output of specified build"),
@@ -94,6 +113,7 @@ pub async fn list_builds(
         .column(builds::Column::EndTime)
         .column(builds::Column::StartTime)
         .column(builds::Column::Platform)
+        .columns(RESOURCE_COLUMNS)
         .order_by(builds::Column::StartTime, Order::Desc)
         .limit(limit)
         .offset(page.zip(limit).map(|(page, limit)| page * limit));
@@ -113,7 +133,7 @@ pub async fn list_builds(
 
 #[utoipa::path(
     responses(
-            (status = 200, description = "Get build details"),
+            (status = 200, description = "Get build details", body = BuildDetailsModel),
     ),
     params(
             ("buildid", description = "Id of build")
@@ -124,7 +144,7 @@ pub async fn get_build(
     db: &State<DatabaseConnection>,
     buildid: i32,
     _a: Authenticated,
-) -> Result<Json<ListBuildsModel>, NotFound<String>> {
+) -> Result<Json<BuildDetailsModel>, NotFound<String>> {
     let db = db as &DatabaseConnection;
 
     let result = Builds::find()
@@ -139,13 +159,29 @@ pub async fn get_build(
         .column(builds::Column::EndTime)
         .column(builds::Column::StartTime)
         .column(builds::Column::Platform)
+        .columns(RESOURCE_COLUMNS)
         .into_model::<ListBuildsModel>()
         .one(db)
         .await
         .map_err(|e| NotFound(e.to_string()))?
         .ok_or(NotFound("no item with id found".to_string()))?;
 
-    Ok(Json(result))
+    let lint = Builds::find_by_id(buildid)
+        .select_only()
+        .column(builds::Column::Lint)
+        .into_tuple::<Option<String>>()
+        .one(db)
+        .await
+        .map_err(|e| NotFound(e.to_string()))?
+        .flatten()
+        .map(|lint| rocket::serde::json::from_str(&lint))
+        .transpose()
+        .map_err(|e| NotFound(e.to_string()))?;
+
+    Ok(Json(BuildDetailsModel {
+        build: result,
+        lint,
+    }))
 }
 
 #[utoipa::path(
@@ -160,7 +196,8 @@ pub async fn get_build(
 pub async fn delete_build(
     db: &State<DatabaseConnection>,
     buildid: i32,
-    _a: Authenticated,
+    a: Authenticated,
+    al: &State<ActivityLog>,
 ) -> Result<(), NotFound<String>> {
     let db = db as &DatabaseConnection;
 
@@ -169,12 +206,28 @@ pub async fn delete_build(
         .await
         .map_err(|e| NotFound(e.to_string()))?
         .ok_or(NotFound("Id not found".to_string()))?;
+    let activity = BuildDeleteActivity {
+        package: package_name(db, build.pkg_id).await,
+        build_id: build.id,
+        version: build.version.clone(),
+        platform: build.platform.clone(),
+        status: build.status,
+    };
+    let pkg_id = build.pkg_id;
 
     build
         .delete(db)
         .await
         .map_err(|e| NotFound(e.to_string()))?;
 
+    al.add(
+        activity,
+        ActivityType::DeleteBuild,
+        a.activity_meta(Some(pkg_id)),
+    )
+    .await
+    .map_err(|e| NotFound(e.to_string()))?;
+
     Ok(())
 }
 
@@ -188,14 +241,36 @@ pub async fn delete_build(
 )]
 #[post("/build/<buildid>/cancel")]
 pub async fn cancel_build(
+    db: &State<DatabaseConnection>,
     tx: &State<Sender<Action>>,
     buildid: i32,
-    _a: Authenticated,
+    a: Authenticated,
+    al: &State<ActivityLog>,
 ) -> Result<(), NotFound<String>> {
+    let db = db as &DatabaseConnection;
+
+    let build = Builds::find_by_id(buildid)
+        .one(db)
+        .await
+        .map_err(|e| NotFound(e.to_string()))?
+        .ok_or(NotFound("Build not found".to_string()))?;
+
     let _ = tx
         .send(Action::Cancel(buildid))
         .map_err(|e| NotFound(e.to_string()))?;
 
+    al.add(
+        BuildCancelActivity {
+            package: package_name(db, build.pkg_id).await,
+            build_id: build.id,
+            old_status: build.status,
+        },
+        ActivityType::CancelBuild,
+        a.activity_meta(Some(build.pkg_id)),
+    )
+    .await
+    .map_err(|e| NotFound(e.to_string()))?;
+
     Ok(())
 }
 
@@ -212,7 +287,8 @@ pub async fn rery_build(
     db: &State<DatabaseConnection>,
     tx: &State<Sender<Action>>,
     buildid: i32,
-    _a: Authenticated,
+    a: Authenticated,
+    al: &State<ActivityLog>,
 ) -> Result<Json<i32>, NotFound<String>> {
     let db = db as &DatabaseConnection;
 
@@ -242,9 +318,87 @@ pub async fn rery_build(
         .await
         .map_err(|e| NotFound(e.to_string()))?;
 
+    let pkg_name = package.name.clone();
     let new_buildid = update_platform(&platform, package, version, db, tx)
         .await
         .map_err(|e| NotFound(e.to_string()))?;
 
+    al.add(
+        BuildRetryActivity {
+            package: pkg_name,
+            build_id: buildid,
+            new_build_id: new_buildid,
+        },
+        ActivityType::RetryBuild,
+        a.activity_meta(Some(pkg_id)),
+    )
+    .await
+    .map_err(|e| NotFound(e.to_string()))?;
+
     Ok(Json(new_buildid))
 }
+
+#[utoipa::path(
+    responses(
+            (status = 200, description = "Resource usage of the latest builds of a package", body = ResourceTrendModel),
+    ),
+    params(
+            ("pkgid", description = "Id of Package"),
+            ("platform", description = "Only builds of this platform"),
+            ("limit", description = "Number of latest builds to consider (default 20)")
+    )
+)]
+#[get("/package/<pkgid>/resources?<platform>&<limit>")]
+pub async fn package_resource_trend(
+    db: &State<DatabaseConnection>,
+    pkgid: i32,
+    platform: Option<String>,
+    limit: Option<u64>,
+    _a: Authenticated,
+) -> Result<Json<ResourceTrendModel>, NotFound<String>> {
+    let db = db as &DatabaseConnection;
+
+    let mut query = Builds::find()
+        .select_only()
+        .column(builds::Column::Id)
+        .column(builds::Column::Version)
+        .column(builds::Column::Platform)
+        .column(builds::Column::Status)
+        .column(builds::Column::StartTime)
+        .columns(RESOURCE_COLUMNS)
+        .filter(builds::Column::PkgId.eq(pkgid))
+        .filter(builds::Column::WallTime.is_not_null())
+        .order_by(builds::Column::StartTime, Order::Desc)
+        .limit(limit.unwrap_or(20));
+    if let Some(platform) = platform {
+        query = query.filter(builds::Column::Platform.eq(platform));
+    }
+
+    let mut builds = query
+        .into_model::<BuildResourceUsageModel>()
+        .all(db)
+        .await
+        .map_err(|e| NotFound(e.to_string()))?;
+    builds.reverse();
+
+    let avg = |values: Vec<i64>| {
+        (!values.is_empty()).then(|| values.iter().sum::<i64>() / values.len() as i64)
+    };
+    Ok(Json(ResourceTrendModel {
+        max_peak_memory: builds.iter().filter_map(|b| b.peak_memory).max(),
+        avg_cpu_time: avg(builds.iter().filter_map(|b| b.cpu_time).collect()),
+        avg_wall_time: avg(builds.iter().filter_map(|b| b.wall_time).collect()),
+        builds,
+    }))
+}
+
+/// name of the package a build belongs to, empty if the package is gone already
+async fn package_name(db: &DatabaseConnection, pkg_id: i32) -> String {
+    Packages::find_by_id(pkg_id)
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+        .map(|p| p.name)
+        .unwrap_or_default()
+}
@@ -0,0 +1,282 @@
+use crate::models::authenticated::Authenticated;
+use crate::models::package::{
+    BulkAddPackages, BulkDeletePackages, BulkPatchPackages, BulkResultModel, BulkResultsModel,
+    BulkUpdatePackages, PackagePatchModel,
+};
+use aurcache_activitylog::activity_utils::ActivityLog;
+use aurcache_activitylog::package_add_activity::PackageAddActivity;
+use aurcache_activitylog::package_delete_activity::PackageDeleteActivity;
+use aurcache_activitylog::package_patch_activity::PackagePatchActivity;
+use aurcache_activitylog::package_update_activity::PackageUpdateActivity;
+use aurcache_db::activities::ActivityType;
+use aurcache_db::packages;
+use aurcache_types::builder::Action;
+use aurcache_utils::package::bulk::{package_add_many, package_update_many, select_packages};
+use aurcache_utils::package::delete::package_delete;
+use pacman_mirrors::platforms::Platform;
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use rocket::{State, patch, post};
+use sea_orm::ActiveValue::Set;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, NotSet};
+use std::str::FromStr;
+use tokio::sync::broadcast::Sender;
+use tracing::warn;
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    packages_bulk_add,
+    packages_bulk_update,
+    packages_bulk_delete,
+    packages_bulk_patch
+))]
+pub struct BulkApi;
+
+fn parse_platforms(
+    platforms: Option<&Vec<String>>,
+) -> Result<Option<Vec<Platform>>, Custom<String>> {
+    platforms
+        .map(|v| {
+            v.iter()
+                .map(|s| Platform::from_str(s))
+                .collect::<Result<Vec<Platform>, _>>()
+                .map_err(|e| Custom(Status::BadRequest, e.to_string()))
+        })
+        .transpose()
+}
+
+/// The package was changed already, a failed activity log entry must not hide its result
+fn warn_unlogged(package: &str, logged: anyhow::Result<()>) {
+    if let Err(e) = logged {
+        warn!("Failed to log the activity of package {package}: {e}");
+    }
+}
+
+fn result_model<T>(
+    pkg_id: Option<i32>,
+    name: String,
+    result: &anyhow::Result<T>,
+    builds: Vec<i32>,
+) -> BulkResultModel {
+    BulkResultModel {
+        id: pkg_id,
+        name,
+        builds,
+        error: result.as_ref().err().map(ToString::to_string),
+    }
+}
+
+/// Add many AUR packages at once, all names are validated with batched AUR requests.
+#[utoipa::path(
+    responses(
+            (status = 200, description = "Result per package name", body = BulkResultsModel),
+            (status = 400, description = "Invalid platform"),
+    )
+)]
+#[post("/packages/bulk/add", data = "<input>")]
+pub async fn packages_bulk_add(
+    db: &State<DatabaseConnection>,
+    input: Json<BulkAddPackages>,
+    tx: &State<Sender<Action>>,
+    a: Authenticated,
+    al: &State<ActivityLog>,
+) -> Result<Json<BulkResultsModel>, Custom<String>> {
+    let db = db as &DatabaseConnection;
+    let platforms = parse_platforms(input.platforms.as_ref())?;
+
+    let added = package_add_many(db, tx, &input.names, platforms, input.build_flags.clone())
+        .await
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+
+    let mut results = Vec::with_capacity(added.len());
+    for (name, result) in added {
+        if let Ok(pkg) = &result {
+            let logged = al
+                .add(
+                    PackageAddActivity {
+                        package: pkg.name.clone(),
+                    },
+                    ActivityType::AddPackage,
+                    a.activity_meta(Some(pkg.id)),
+                )
+                .await;
+            warn_unlogged(&pkg.name, logged);
+        }
+        let pkg_id = result.as_ref().ok().map(|p| p.id);
+        results.push(result_model(pkg_id, name, &result, vec![]));
+    }
+    Ok(Json(BulkResultsModel::new(results)))
+}
+
+/// Update or force-rebuild all packages matching the filter.
+/// Upstream versions of AUR packages are fetched with batched AUR requests.
+#[utoipa::path(
+    responses(
+            (status = 200, description = "Result per selected package", body = BulkResultsModel),
+            (status = 400, description = "Empty filter"),
+    )
+)]
+#[post("/packages/bulk/update", data = "<input>")]
+pub async fn packages_bulk_update(
+    db: &State<DatabaseConnection>,
+    input: Json<BulkUpdatePackages>,
+    tx: &State<Sender<Action>>,
+    a: Authenticated,
+    al: &State<ActivityLog>,
+) -> Result<Json<BulkResultsModel>, Custom<String>> {
+    let db = db as &DatabaseConnection;
+
+    let pkgs = select_packages(db, &input.filter)
+        .await
+        .map_err(|e| Custom(Status::BadRequest, e.to_string()))?;
+    let updated = package_update_many(db, tx, pkgs, input.force)
+        .await
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+
+    let mut results = Vec::with_capacity(updated.len());
+    for (pkg, result) in updated {
+        if result.is_ok() {
+            let logged = al
+                .add(
+                    PackageUpdateActivity {
+                        package: pkg.name.clone(),
+                        forced: input.force,
+                    },
+                    ActivityType::UpdatePackage,
+                    a.activity_meta(Some(pkg.id)),
+                )
+                .await;
+            warn_unlogged(&pkg.name, logged);
+        }
+        let builds = result.as_ref().cloned().unwrap_or_default();
+        results.push(result_model(Some(pkg.id), pkg.name, &result, builds));
+    }
+    Ok(Json(BulkResultsModel::new(results)))
+}
+
+/// Delete all packages matching the filter with their builds and files.
+#[utoipa::path(
+    responses(
+            (status = 200, description = "Result per selected package", body = BulkResultsModel),
+            (status = 400, description = "Empty filter"),
+    )
+)]
+#[post("/packages/bulk/delete", data = "<input>")]
+pub async fn packages_bulk_delete(
+    db: &State<DatabaseConnection>,
+    input: Json<BulkDeletePackages>,
+    a: Authenticated,
+    al: &State<ActivityLog>,
+) -> Result<Json<BulkResultsModel>, Custom<String>> {
+    let db = db as &DatabaseConnection;
+
+    let pkgs = select_packages(db, &input.filter)
+        .await
+        .map_err(|e| Custom(Status::BadRequest, e.to_string()))?;
+
+    let mut results = Vec::with_capacity(pkgs.len());
+    for pkg in pkgs {
+        let result = package_delete(db, pkg.id).await;
+        if result.is_ok() {
+            let logged = al
+                .add(
+                    PackageDeleteActivity {
+                        package: pkg.name.clone(),
+                    },
+                    ActivityType::RemovePackage,
+                    a.activity_meta(Some(pkg.id)),
+                )
+                .await;
+            warn_unlogged(&pkg.name, logged);
+        }
+        results.push(result_model(Some(pkg.id), pkg.name, &result, vec![]));
+    }
+    Ok(Json(BulkResultsModel::new(results)))
+}
+
+/// Set platforms and/or build flags of all packages matching the filter.
+/// The new values are used from the next build on.
+#[utoipa::path(
+    responses(
+            (status = 200, description = "Result per selected package", body = BulkResultsModel),
+            (status = 400, description = "Empty filter or invalid platform"),
+    )
+)]
+#[patch("/packages/bulk", data = "<input>")]
+pub async fn packages_bulk_patch(
+    db: &State<DatabaseConnection>,
+    input: Json<BulkPatchPackages>,
+    a: Authenticated,
+    al: &State<ActivityLog>,
+) -> Result<Json<BulkResultsModel>, Custom<String>> {
+    let db = db as &DatabaseConnection;
+
+    if input.platforms.is_none() && input.build_flags.is_none() {
+        return Err(Custom(
+            Status::BadRequest,
+            "Nothing to patch, set platforms or build_flags".to_string(),
+        ));
+    }
+    if input.platforms.as_ref().is_some_and(Vec::is_empty) {
+        return Err(Custom(
+            Status::BadRequest,
+            "At least one platform is required".to_string(),
+        ));
+    }
+    parse_platforms(input.platforms.as_ref())?;
+    let pkgs = select_packages(db, &input.filter)
+        .await
+        .map_err(|e| Custom(Status::BadRequest, e.to_string()))?;
+
+    let new = PackagePatchModel {
+        build_flags: input.build_flags.clone(),
+        platforms: input.platforms.clone(),
+        ..Default::default()
+    };
+
+    let mut results = Vec::with_capacity(pkgs.len());
+    for pkg in pkgs {
+        // snapshot of the values which get overwritten by this patch
+        let old = PackagePatchModel {
+            build_flags: new.build_flags.as_ref().map(|_| {
+                pkg.build_flags
+                    .split(';')
+                    .map(ToString::to_string)
+                    .collect()
+            }),
+            platforms: new
+                .platforms
+                .as_ref()
+                .map(|_| pkg.platforms.split(';').map(ToString::to_string).collect()),
+            ..Default::default()
+        };
+
+        let update_pkg = packages::ActiveModel {
+            id: Set(pkg.id),
+            build_flags: new.build_flags.clone().map_or(NotSet, |v| Set(v.join(";"))),
+            platforms: new.platforms.clone().map_or(NotSet, |v| Set(v.join(";"))),
+            ..Default::default()
+        };
+        let result = update_pkg.update(db).await;
+
+        if result.is_ok() {
+            let logged = match PackagePatchActivity::new(pkg.name.clone(), &old, &new) {
+                Ok(activity) => {
+                    al.add(
+                        activity,
+                        ActivityType::PatchPackage,
+                        a.activity_meta(Some(pkg.id)),
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            warn_unlogged(&pkg.name, logged);
+        }
+        let result = result.map_err(anyhow::Error::from);
+        results.push(result_model(Some(pkg.id), pkg.name, &result, vec![]));
+    }
+    Ok(Json(BulkResultsModel::new(results)))
+}
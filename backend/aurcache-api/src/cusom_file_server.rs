@@ -1,13 +1,20 @@
+use crate::repo_index::IndexRequest;
+use chrono::{DateTime, Utc};
 use rocket::fs::NamedFile;
 use rocket::http::uri::Segments;
-use rocket::http::{Header, Method, Status};
+use rocket::http::{ContentType, Header, Method, Status};
 use rocket::response::Responder;
 use rocket::route::{Handler, Outcome};
 use rocket::{Data, Request, Response, Route, async_trait, figment};
-use std::io::{Cursor, SeekFrom};
+use std::fs::Metadata;
+use std::io::{self, Cursor, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::fs::File;
-use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, ReadBuf, Take};
+use tracing::error;
 
 #[derive(Debug, Clone)]
 pub struct CustomFileServer {
@@ -43,62 +50,109 @@ impl CustomFileServer {
 impl From<CustomFileServer> for Vec<Route> {
     fn from(server: CustomFileServer) -> Self {
         let source = figment::Source::File(server.root.clone());
-        let mut route = Route::ranked(server.rank, Method::Get, "/<path..>", server);
-        route.name = Some(format!("FileServer: {source}").into());
-        vec![route]
+        // HEAD is routed explicitly, so range requests can tell it apart from GET
+        [Method::Get, Method::Head]
+            .into_iter()
+            .map(|method| {
+                let mut route = Route::ranked(server.rank, method, "/<path..>", server.clone());
+                route.name = Some(format!("FileServer: {source}").into());
+                route
+            })
+            .collect()
     }
 }
 
 #[async_trait]
 impl Handler for CustomFileServer {
     async fn handle<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r> {
+        // dotfiles like temporary archives are never served
         let relative_path = req
             .segments::<Segments<'_, rocket::http::uri::fmt::Path>>(0..)
             .ok()
-            .and_then(|segments| segments.to_path_buf(true).ok());
-
-        // Map uri to filepath
-        let file_path = match relative_path {
-            Some(p) => self.root.join(p),
-            None => return Outcome::forward(data, Status::NotFound),
+            .and_then(|segments| segments.to_path_buf(false).ok());
+        let Some(relative_path) = relative_path else {
+            return Outcome::forward(data, Status::NotFound);
         };
 
+        // generated html/json index of the repo and its platforms
+        if let Some(index) = IndexRequest::from_path(&self.root, &relative_path) {
+            if index.needs_redirect(req) {
+                let location = format!("{}/", req.uri().path());
+                return Outcome::Success(
+                    Response::build()
+                        .status(Status::PermanentRedirect)
+                        .raw_header("Location", location)
+                        .finalize(),
+                );
+            }
+            return match index.render(&self.root, req).await {
+                Ok((content_type, body)) => Outcome::Success(
+                    Response::build()
+                        .header(content_type)
+                        .sized_body(body.len(), Cursor::new(body))
+                        .finalize(),
+                ),
+                Err(e) => {
+                    error!("Failed to render repo index: {e}");
+                    Outcome::error(Status::InternalServerError)
+                }
+            };
+        }
+
+        // Map uri to filepath, the repo lock file is internal
+        let file_path = self.root.join(relative_path);
+        if file_path.is_dir() || file_path.extension().is_some_and(|e| e == "lck") {
+            return Outcome::forward(data, Status::NotFound);
+        }
+
         // open file
         let named_file = match NamedFile::open(&file_path).await {
             Ok(f) => f,
             Err(_) => return Outcome::forward(data, Status::NotFound),
         };
+        let Ok(metadata) = named_file.metadata().await else {
+            return Outcome::error(Status::InternalServerError);
+        };
+        let validators = Validators::new(&metadata);
+        let file_size = metadata.len();
 
-        let metadata = named_file.metadata().await.ok();
-        let file_size = metadata.as_ref().map_or(0, std::fs::Metadata::len);
-        let last_modified = metadata.and_then(|m| m.modified().ok()).map(|mtime| {
-            let datetime: chrono::DateTime<chrono::Utc> = mtime.into();
-            datetime.to_rfc2822()
-        });
-
-        let mut builder = match get_range_header_data(req, file_size, &file_path).await {
-            Some((partial_data, start, end)) => {
-                // Build a 206 Partial Content response.
-                let mut builder = Response::build();
-                builder
-                    .status(Status::PartialContent)
-                    .raw_header(
-                        "Content-Range",
-                        format!("bytes {}-{}/{}", start, end - 1, file_size),
-                    )
-                    .raw_header("Accept-Ranges", "bytes")
-                    .sized_body(partial_data.len(), Cursor::new(partial_data));
-                builder
+        let mut builder = if validators.not_modified(req) {
+            let mut builder = Response::build();
+            builder.status(Status::NotModified);
+            builder
+        } else {
+            match requested_ranges(req, &validators, file_size) {
+                Ranges::Full => match named_file.respond_to(req) {
+                    Ok(resp) => Response::build_from(resp),
+                    Err(_) => return Outcome::error(Status::InternalServerError),
+                },
+                Ranges::Unsatisfiable => {
+                    let mut builder = Response::build();
+                    builder
+                        .status(Status::RangeNotSatisfiable)
+                        .raw_header("Content-Range", format!("bytes */{file_size}"));
+                    builder
+                }
+                Ranges::Partial(ranges) => {
+                    let content_type = named_file
+                        .path()
+                        .extension()
+                        .and_then(|e| ContentType::from_extension(&e.to_string_lossy()))
+                        .unwrap_or(ContentType::Binary);
+                    match partial_response(named_file.path(), &ranges, file_size, content_type)
+                        .await
+                    {
+                        Ok(builder) => builder,
+                        Err(_) => return Outcome::error(Status::InternalServerError),
+                    }
+                }
             }
-            None => match named_file.respond_to(req) {
-                Ok(resp) => Response::build_from(resp),
-                Err(_) => return Outcome::error(Status::InternalServerError),
-            },
         };
 
         // Add Headers
-        if let Some(lm) = last_modified {
-            builder.header(Header::new("Last-Modified", lm));
+        builder.raw_header("ETag", validators.etag.clone());
+        if let Some(lm) = validators.last_modified {
+            builder.raw_header("Last-Modified", http_date(lm));
         }
         builder.header(Header::new("Accept-Ranges", "bytes"));
 
@@ -106,49 +160,372 @@ impl Handler for CustomFileServer {
     }
 }
 
-/// get range header and read bytes from file
-async fn get_range_header_data(
-    req: &Request<'_>,
-    file_size: u64,
-    file_path: &Path,
-) -> Option<(Vec<u8>, u64, u64)> {
-    let header = req.headers().get_one("Range")?;
-    let (start, end) = parse_range_header(header, file_size)?;
-    let data = read_file_range(file_path, start, end).await.ok()?;
-
-    Some((data, start, end))
-}
-
-/// Parser for Range header in the form "bytes=start-end".
-/// Returns a tuple (start, end) where `end` is exclusive.
-/// This version does not support multiple ranges.
-fn parse_range_header(header: &str, file_size: u64) -> Option<(u64, u64)> {
-    if !header.starts_with("bytes=") {
-        return None;
-    }
-    let range = &header[6..];
-    let parts: Vec<&str> = range.split('-').collect();
-    if parts.len() != 2 {
-        return None;
-    }
-    let start: u64 = parts[0].parse().ok()?;
-    // If the end is omitted, use the file size.
-    let end: u64 = if let Ok(e) = parts[1].parse::<u64>() {
-        e + 1 // HTTP ranges are inclusive; our reading will use an exclusive end.
-    } else {
-        file_size
+/// Maximum number of ranges served per request, larger range sets get the full file
+const MAX_RANGES: usize = 16;
+
+/// Validators of the served file for conditional requests
+struct Validators {
+    /// strong ETag derived from size and mtime
+    etag: String,
+    last_modified: Option<DateTime<Utc>>,
+}
+
+impl Validators {
+    fn new(metadata: &Metadata) -> Self {
+        let mtime = metadata.modified().ok();
+        let nanos = mtime
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos());
+        Validators {
+            etag: format!("\"{:x}-{nanos:x}\"", metadata.len()),
+            last_modified: mtime.map(DateTime::<Utc>::from),
+        }
+    }
+
+    fn etag_matches(&self, header: &str) -> bool {
+        header.split(',').map(str::trim).any(|tag| {
+            // If-None-Match uses the weak comparison
+            tag == "*" || tag.trim_start_matches("W/") == self.etag
+        })
+    }
+
+    /// Whether the file was modified after the HTTP date `header`
+    fn modified_since(&self, header: &str) -> bool {
+        let (Some(last_modified), Ok(since)) = (self.last_modified, parse_http_date(header)) else {
+            return true;
+        };
+        // HTTP dates have second precision
+        last_modified.timestamp() > since.timestamp()
+    }
+
+    /// If-None-Match takes precedence, If-Modified-Since is only checked without it
+    fn not_modified(&self, req: &Request<'_>) -> bool {
+        if let Some(header) = req.headers().get_one("If-None-Match") {
+            return self.etag_matches(header);
+        }
+        req.headers()
+            .get_one("If-Modified-Since")
+            .is_some_and(|header| !self.modified_since(header))
+    }
+
+    /// If-Range holds a strong ETag or a date, a stale one gets the full file
+    fn if_range_matches(&self, header: &str) -> bool {
+        if header.starts_with('"') {
+            header.trim() == self.etag
+        } else if header.starts_with("W/") {
+            false
+        } else {
+            !self.modified_since(header)
+        }
+    }
+}
+
+fn http_date(date: DateTime<Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn parse_http_date(date: &str) -> chrono::ParseResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(date.trim()).map(|d| d.with_timezone(&Utc))
+}
+
+/// Byte ranges to serve, `end` is exclusive
+#[derive(Debug, PartialEq, Eq)]
+enum Ranges {
+    Full,
+    Partial(Vec<(u64, u64)>),
+    Unsatisfiable,
+}
+
+/// Evaluate the Range header of a GET request
+fn requested_ranges(req: &Request<'_>, validators: &Validators, file_size: u64) -> Ranges {
+    if req.method() != Method::Get {
+        return Ranges::Full;
+    }
+    let Some(header) = req.headers().get_one("Range") else {
+        return Ranges::Full;
     };
-    if start >= end || end > file_size {
-        return None;
+    if let Some(if_range) = req.headers().get_one("If-Range")
+        && !validators.if_range_matches(if_range)
+    {
+        return Ranges::Full;
+    }
+    parse_range_header(header, file_size)
+}
+
+/// Parser for Range headers like `bytes=0-99,200-,-500`.
+///
+/// Syntax errors ignore the header and serve the full file, as RFC 9110 allows.
+fn parse_range_header(header: &str, file_size: u64) -> Ranges {
+    let Some(specs) = header.trim().strip_prefix("bytes=") else {
+        return Ranges::Full;
+    };
+
+    let mut ranges = vec![];
+    for spec in specs.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let Some((first, last)) = spec.split_once('-') else {
+            return Ranges::Full;
+        };
+        let range = match (first.trim(), last.trim()) {
+            // suffix range, the last n bytes
+            ("", suffix) => match suffix.parse::<u64>() {
+                Ok(0) => None,
+                Ok(n) => Some((file_size.saturating_sub(n), file_size)),
+                Err(_) => return Ranges::Full,
+            },
+            (first, last) => {
+                let Ok(start) = first.parse::<u64>() else {
+                    return Ranges::Full;
+                };
+                // HTTP ranges are inclusive; our reading will use an exclusive end.
+                let end = match last {
+                    "" => file_size,
+                    last => match last.parse::<u64>() {
+                        Ok(last) if last >= start => (last + 1).min(file_size),
+                        _ => return Ranges::Full,
+                    },
+                };
+                Some((start, end))
+            }
+        };
+        if let Some((start, end)) = range
+            && start < end
+        {
+            ranges.push((start, end));
+        }
+    }
+
+    match ranges.len() {
+        0 => Ranges::Unsatisfiable,
+        n if n > MAX_RANGES => Ranges::Full,
+        _ => Ranges::Partial(ranges),
+    }
+}
+
+/// Build a 206 Partial Content response, streaming the ranges of the file at `path`
+async fn partial_response<'r>(
+    path: &Path,
+    ranges: &[(u64, u64)],
+    file_size: u64,
+    content_type: ContentType,
+) -> io::Result<rocket::response::Builder<'r>> {
+    let mut builder = Response::build();
+    builder.status(Status::PartialContent);
+
+    if let [(start, end)] = ranges {
+        let body = FileRange::new(File::open(path).await?, *start, *end).await?;
+        builder
+            .header(content_type)
+            .raw_header(
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, end - 1, file_size),
+            )
+            .sized_body(usize::try_from(end - start).ok(), body);
+        return Ok(builder);
+    }
+
+    // multipart/byteranges, every part gets its own handle as cloned handles share the offset
+    let boundary = format!("{:016x}", rand_boundary());
+    let mut body: Box<dyn AsyncRead + Send + Unpin> = Box::new(tokio::io::empty());
+    for (start, end) in ranges {
+        let part_header = format!(
+            "\r\n--{boundary}\r\nContent-Type: {content_type}\r\nContent-Range: bytes {}-{}/{file_size}\r\n\r\n",
+            start,
+            end - 1
+        );
+        let part = FileRange::new(File::open(path).await?, *start, *end).await?;
+        body = Box::new(body.chain(Cursor::new(part_header)).chain(part));
+    }
+    body = Box::new(body.chain(Cursor::new(format!("\r\n--{boundary}--\r\n"))));
+
+    builder
+        .raw_header(
+            "Content-Type",
+            format!("multipart/byteranges; boundary={boundary}"),
+        )
+        .streamed_body(body);
+    Ok(builder)
+}
+
+/// Boundary for multipart responses, unique enough to not appear in package files
+fn rand_boundary() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    u64::try_from(nanos & u128::from(u64::MAX)).unwrap_or_default()
+}
+
+/// Bytes `start..end` of a file, read on demand instead of buffered in memory
+struct FileRange {
+    inner: Take<File>,
+    start: u64,
+    len: u64,
+}
+
+impl FileRange {
+    async fn new(mut file: File, start: u64, end: u64) -> io::Result<Self> {
+        file.seek(SeekFrom::Start(start)).await?;
+        Ok(FileRange {
+            inner: file.take(end - start),
+            start,
+            len: end - start,
+        })
+    }
+}
+
+impl AsyncRead for FileRange {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_read(cx, buf)
+    }
+}
+
+/// Seeks are relative to the range, rocket uses them to determine the body size
+impl AsyncSeek for FileRange {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let me = self.get_mut();
+        let offset = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => me.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => (me.len - me.inner.limit()).checked_add_signed(offset),
+        }
+        .filter(|offset| *offset <= me.len)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek outside of range"))?;
+
+        me.inner.set_limit(me.len - offset);
+        Pin::new(me.inner.get_mut()).start_seek(SeekFrom::Start(me.start + offset))
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let me = self.get_mut();
+        Pin::new(me.inner.get_mut())
+            .poll_complete(cx)
+            .map_ok(|position| position - me.start)
     }
-    Some((start, end))
 }
 
-/// Reads bytes from `start` up to (but not including) `end` from the file at `path`.
-async fn read_file_range(path: &Path, start: u64, end: u64) -> anyhow::Result<Vec<u8>> {
-    let mut file = File::open(path).await?;
-    file.seek(SeekFrom::Start(start)).await?;
-    let mut buffer = vec![0; usize::try_from(end - start)?];
-    file.read_exact(&mut buffer).await?;
-    Ok(buffer)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn validators() -> Validators {
+        Validators {
+            etag: "\"1f4-5f2b\"".to_string(),
+            last_modified: Some(Utc.with_ymd_and_hms(2026, 10, 19, 12, 0, 0).unwrap()),
+        }
+    }
+
+    #[test]
+    fn suffix_range() {
+        assert_eq!(
+            parse_range_header("bytes=-500", 1000),
+            Ranges::Partial(vec![(500, 1000)])
+        );
+        // longer than the file
+        assert_eq!(
+            parse_range_header("bytes=-500", 100),
+            Ranges::Partial(vec![(0, 100)])
+        );
+    }
+
+    #[test]
+    fn first_and_last_byte() {
+        assert_eq!(
+            parse_range_header("bytes=0-0,-1", 1000),
+            Ranges::Partial(vec![(0, 1), (999, 1000)])
+        );
+    }
+
+    #[test]
+    fn open_and_clamped_ranges() {
+        assert_eq!(
+            parse_range_header("bytes=100-", 1000),
+            Ranges::Partial(vec![(100, 1000)])
+        );
+        assert_eq!(
+            parse_range_header("bytes=900-1999", 1000),
+            Ranges::Partial(vec![(900, 1000)])
+        );
+    }
+
+    #[test]
+    fn start_past_eof_is_unsatisfiable() {
+        assert_eq!(
+            parse_range_header("bytes=1000-1999", 1000),
+            Ranges::Unsatisfiable
+        );
+        assert_eq!(
+            parse_range_header("bytes=5000-", 1000),
+            Ranges::Unsatisfiable
+        );
+        assert_eq!(parse_range_header("bytes=-0", 1000), Ranges::Unsatisfiable);
+        // satisfiable ranges are still served
+        assert_eq!(
+            parse_range_header("bytes=5000-,0-9", 1000),
+            Ranges::Partial(vec![(0, 10)])
+        );
+    }
+
+    #[test]
+    fn malformed_is_full() {
+        for header in [
+            "items=0-9",
+            "bytes=abc",
+            "bytes=a-9",
+            "bytes=0-b",
+            "bytes=9-0",
+            "bytes=-x",
+            "bytes=0-9,oops",
+        ] {
+            assert_eq!(parse_range_header(header, 1000), Ranges::Full, "{header}");
+        }
+    }
+
+    #[test]
+    fn too_many_ranges_is_full() {
+        let specs = |n: u64| {
+            (0..n)
+                .map(|i| format!("{}-{}", i * 10, i * 10 + 4))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        assert!(matches!(
+            parse_range_header(&format!("bytes={}", specs(MAX_RANGES as u64)), 1000),
+            Ranges::Partial(r) if r.len() == MAX_RANGES
+        ));
+        assert_eq!(
+            parse_range_header(&format!("bytes={}", specs(MAX_RANGES as u64 + 1)), 1000),
+            Ranges::Full
+        );
+    }
+
+    #[test]
+    fn if_none_match_uses_weak_comparison() {
+        let validators = validators();
+        assert!(validators.etag_matches("\"1f4-5f2b\""));
+        assert!(validators.etag_matches("W/\"1f4-5f2b\""));
+        assert!(validators.etag_matches("\"other\", W/\"1f4-5f2b\""));
+        assert!(validators.etag_matches("*"));
+        assert!(!validators.etag_matches("\"other\""));
+    }
+
+    #[test]
+    fn if_range_uses_strong_comparison() {
+        let validators = validators();
+        assert!(validators.if_range_matches("\"1f4-5f2b\""));
+        assert!(!validators.if_range_matches("W/\"1f4-5f2b\""));
+        assert!(!validators.if_range_matches("\"other\""));
+    }
+
+    #[test]
+    fn if_range_date() {
+        let validators = validators();
+        assert!(validators.if_range_matches("Mon, 19 Oct 2026 12:00:00 GMT"));
+        assert!(validators.if_range_matches("Mon, 19 Oct 2026 13:00:00 GMT"));
+        assert!(!validators.if_range_matches("Mon, 19 Oct 2026 11:59:59 GMT"));
+        assert!(!validators.if_range_matches("not a date"));
+    }
 }
@@ -1,3 +1,7 @@
+use crate::models::health::HealthModel;
+use crate::models::mirrors::MirrorHealthModel;
+use aurcache_utils::mirrors::MirrorHealthReport;
+use rocket::serde::json::Json;
 use rocket::{State, get};
 use sea_orm::DatabaseConnection;
 use utoipa::OpenApi;
@@ -8,13 +12,18 @@ pub struct HealthApi;
 
 #[utoipa::path(
     responses(
-            (status = 200, description = "Internal Healthcheck")
+            (status = 200, description = "Internal Healthcheck with the last mirror health probe", body = HealthModel)
     )
 )]
 #[get("/health")]
-pub async fn health(db: &State<DatabaseConnection>) -> Result<(), String> {
+pub async fn health(
+    db: &State<DatabaseConnection>,
+    mirror_health: &State<MirrorHealthReport>,
+) -> Result<Json<HealthModel>, String> {
     check_health(db).await.map_err(|e| format!("{e:?}"))?;
-    Ok(())
+    Ok(Json(HealthModel {
+        mirrors: mirror_health.get().map(MirrorHealthModel::from),
+    }))
 }
 
 async fn check_health(db: &DatabaseConnection) -> anyhow::Result<()> {
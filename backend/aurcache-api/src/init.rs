@@ -5,9 +5,11 @@ use crate::cusom_file_server::CustomFileServer;
 #[cfg(feature = "static")]
 use crate::embed::CustomHandler;
 use crate::models::authenticated::OauthEnabled;
-use crate::utils::config::oauth_config_from_env;
+use crate::utils::config::{api_tokens_from_env, oauth_config_from_env};
+use crate::utils::request_span::{RequestSpan, instrument_routes};
 use aurcache_activitylog::activity_utils::ActivityLog;
 use aurcache_types::builder::Action;
+use aurcache_utils::mirrors::MirrorHealthReport;
 use rocket::config::SecretKey;
 use rocket::fairing::AdHoc;
 use rocket::http::private::cookie::Key;
@@ -18,7 +20,9 @@ use std::env;
 use tokio::sync::broadcast::Sender;
 use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
-use utoipa::openapi::security::{AuthorizationCode, Flow, OAuth2, Scopes};
+use utoipa::openapi::security::{
+    AuthorizationCode, Flow, HttpAuthScheme, HttpBuilder, OAuth2, Scopes,
+};
 use utoipa::{Modify, OpenApi, openapi::security::SecurityScheme};
 use utoipa_redoc::{Redoc, Servable as _};
 use utoipa_scalar::{Scalar, Servable as _};
@@ -33,7 +37,11 @@ fn get_secret_key() -> SecretKey {
 }
 
 #[must_use]
-pub fn init_api(db: DatabaseConnection, tx: Sender<Action>) -> JoinHandle<()> {
+pub fn init_api(
+    db: DatabaseConnection,
+    tx: Sender<Action>,
+    mirror_health: MirrorHealthReport,
+) -> JoinHandle<()> {
     tokio::spawn(async {
         let config = Config {
             address: "0.0.0.0".parse().unwrap(),
@@ -49,20 +57,29 @@ pub fn init_api(db: DatabaseConnection, tx: Sender<Action>) -> JoinHandle<()> {
                 (path = "/api", api = crate::auth::AuthApi, tags = ["Auth"]),
                 (path = "/api", api = crate::build::BuildApi, tags = ["Build"]),
                 (path = "/api", api = crate::health::HealthApi, tags = ["Health"]),
+                (path = "/api", api = crate::metrics::MetricsApi, tags = ["Metrics"]),
+                (path = "/api", api = crate::mirrors::MirrorsApi, tags = ["Mirrors"]),
                 (path = "/api", api = crate::package::PackageApi, tags = ["Package"]),
+                (path = "/api", api = crate::bulk::BulkApi, tags = ["Package"]),
+                (path = "/api", api = crate::repo::RepoApi, tags = ["Repo"]),
                 (path = "/api", api = crate::stats::StatsApi, tags = ["Stats"]),
                 (path = "/api", api = crate::activity::ActivityApi, tags = ["Activity"]),
                 (path = "/api", api = crate::settings::SettingsApi, tags = ["Settings"]),
+                (path = "/api", api = crate::manifest::ManifestApi, tags = ["Manifest"]),
             ),
             tags(
                 (name = "AUR", description = "AUR management endpoints."),
                 (name = "Build", description = "Build management endpoints."),
                 (name = "Auth", description = "Authentication"),
                 (name = "Health", description = "Health endpoints"),
+                (name = "Metrics", description = "Prometheus metrics"),
+                (name = "Mirrors", description = "Pacman mirrorlist endpoints."),
                 (name = "Package", description = "Package management endpoints."),
+                (name = "Repo", description = "Repo maintenance endpoints."),
                 (name = "Stats", description = "Statistics endpoints."),
                 (name = "Activity", description = "Activity endpoints."),
                 (name = "Settings", description = "Settings endpoints."),
+                (name = "Manifest", description = "Declarative package and settings manifest."),
             ),
             modifiers(&SecurityAddon)
         )]
@@ -73,6 +90,10 @@ pub fn init_api(db: DatabaseConnection, tx: Sender<Action>) -> JoinHandle<()> {
         impl Modify for SecurityAddon {
             fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
                 let components = openapi.components.as_mut().unwrap(); // we can unwrap safely since there already is components registered.
+                components.add_security_scheme(
+                    "api_token",
+                    SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+                );
                 let oauth_config = oauth_config_from_env();
                 if let Ok(oauth_config) = oauth_config {
                     components.add_security_scheme(
@@ -93,15 +114,21 @@ pub fn init_api(db: DatabaseConnection, tx: Sender<Action>) -> JoinHandle<()> {
         let mut rock = rocket::custom(config)
             .manage(db.clone())
             .manage(tx)
+            .manage(mirror_health)
             .manage(OauthEnabled(oauth_config.is_ok()))
+            .manage(api_tokens_from_env())
             .manage(ActivityLog::new(db))
-            .mount("/api/", build_api())
+            .attach(RequestSpan)
+            .mount("/api/", instrument_routes(build_api()))
             .mount("/", Scalar::with_url("/docs", ApiDoc::openapi()))
             .mount("/", Redoc::with_url("/redoc", ApiDoc::openapi()));
 
         if let Ok(oauth_config) = oauth_config {
             rock = rock
-                .mount("/api/", routes![oauth_login, oauth_callback])
+                .mount(
+                    "/api/",
+                    instrument_routes(routes![oauth_login, oauth_callback]),
+                )
                 .attach(AdHoc::on_ignite("OAuth Config", |rocket| async {
                     rocket.attach(rocket_oauth2::OAuth2::<OauthUserInfo>::custom(
                         HyperRustlsAdapter::default(),
@@ -132,7 +159,8 @@ pub fn init_repo() -> JoinHandle<()> {
         };
 
         let launch_result = rocket::custom(config)
-            .mount("/", CustomFileServer::from("./repo"))
+            .attach(RequestSpan)
+            .mount("/", instrument_routes(CustomFileServer::from("./repo")))
             .launch()
             .await;
         match launch_result {
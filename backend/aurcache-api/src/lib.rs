@@ -3,13 +3,19 @@ mod aur;
 mod auth;
 pub mod backend;
 mod build;
+mod bulk;
 pub mod cusom_file_server;
 #[cfg(feature = "static")]
 pub mod embed;
 mod health;
 pub mod init;
-mod models;
+mod manifest;
+mod metrics;
+mod mirrors;
+pub mod models;
 mod package;
+mod repo;
+mod repo_index;
 mod settings;
 mod stats;
 mod utils;
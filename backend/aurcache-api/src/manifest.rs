@@ -0,0 +1,212 @@
+use crate::models::authenticated::Authenticated;
+use crate::models::manifest::{ManifestApplyModel, ManifestApplyQuery, ManifestChangeModel};
+use aurcache_activitylog::activity_utils::ActivityLog;
+use aurcache_activitylog::package_add_activity::PackageAddActivity;
+use aurcache_activitylog::package_delete_activity::PackageDeleteActivity;
+use aurcache_activitylog::package_patch_activity::PackagePatchActivity;
+use aurcache_activitylog::setting_update_activity::SettingUpdateActivity;
+use aurcache_db::activities::ActivityType;
+use aurcache_types::builder::Action;
+use aurcache_utils::manifest::{AppliedChange, Manifest, ManifestChange, ManifestFormat};
+use rocket::data::{Data, ToByteUnit};
+use rocket::http::{ContentType, Status};
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use rocket::{State, get, post};
+use sea_orm::DatabaseConnection;
+use std::str::FromStr;
+use tokio::sync::broadcast::Sender;
+use tracing::warn;
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(manifest_apply, manifest_export))]
+pub struct ManifestApi;
+
+/// Format from the `format` query parameter, falling back to the content type
+fn parse_format(
+    format: Option<&str>,
+    content_type: Option<&ContentType>,
+) -> Result<ManifestFormat, Custom<String>> {
+    match format {
+        Some(format) => {
+            ManifestFormat::from_str(format).map_err(|e| Custom(Status::BadRequest, e.to_string()))
+        }
+        None if content_type.is_some_and(|c| c.sub().as_str().contains("yaml")) => {
+            Ok(ManifestFormat::Yaml)
+        }
+        None => Ok(ManifestFormat::Toml),
+    }
+}
+
+/// Diff a TOML or YAML manifest against the current packages and settings and apply the changes.
+/// With `prune`, packages missing in the manifest are removed and stored settings missing in it are reset.
+#[utoipa::path(
+    request_body(content = String, description = "Manifest in TOML or YAML format", content_type = "application/toml"),
+    responses(
+            (status = 200, description = "Planned or applied changes", body = ManifestApplyModel),
+            (status = 400, description = "Invalid manifest"),
+    ),
+    params(
+            ("dry_run" = Option<bool>, Query, description = "Only list the changes without applying them"),
+            ("prune" = Option<bool>, Query, description = "Remove packages and reset settings missing in the manifest"),
+            ("format" = Option<String>, Query, description = "toml or yaml, defaults to the content type or toml"),
+    )
+)]
+#[post("/manifest/apply?<query..>", data = "<data>")]
+pub async fn manifest_apply(
+    db: &State<DatabaseConnection>,
+    tx: &State<Sender<Action>>,
+    query: ManifestApplyQuery,
+    content_type: Option<&ContentType>,
+    data: Data<'_>,
+    a: Authenticated,
+    al: &State<ActivityLog>,
+) -> Result<Json<ManifestApplyModel>, Custom<String>> {
+    let db = db as &DatabaseConnection;
+    let format = parse_format(query.format.as_deref(), content_type)?;
+    let dry_run = query.dry_run.unwrap_or(false);
+
+    let input = data
+        .open(1.mebibytes())
+        .into_string()
+        .await
+        .map_err(|e| Custom(Status::BadRequest, e.to_string()))?;
+    if !input.is_complete() {
+        return Err(Custom(
+            Status::PayloadTooLarge,
+            "Manifest exceeds 1 MiB".to_string(),
+        ));
+    }
+    let manifest = Manifest::parse(&input, format)
+        .map_err(|e| Custom(Status::BadRequest, format!("Invalid manifest: {e}")))?;
+
+    let changes = aurcache_utils::manifest::plan(db, &manifest, query.prune.unwrap_or(false))
+        .await
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+
+    let applied = if dry_run {
+        changes
+            .into_iter()
+            .map(|change| AppliedChange {
+                change,
+                pkg_id: None,
+                error: None,
+            })
+            .collect()
+    } else {
+        let applied = aurcache_utils::manifest::apply(db, tx, &manifest, changes)
+            .await
+            .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+        // the changes are applied already, a failed log entry must not hide their results
+        for change in applied.iter().filter(|c| c.error.is_none()) {
+            if let Err(e) = log_change(al, &a, change).await {
+                warn!("Failed to log manifest change {:?}: {e}", change.change);
+            }
+        }
+        applied
+    };
+
+    Ok(Json(ManifestApplyModel {
+        dry_run,
+        failed: applied.iter().filter(|c| c.error.is_some()).count(),
+        changes: applied.into_iter().map(ManifestChangeModel::from).collect(),
+    }))
+}
+
+async fn log_change(
+    al: &ActivityLog,
+    a: &Authenticated,
+    applied: &AppliedChange,
+) -> anyhow::Result<()> {
+    let meta = a.activity_meta(applied.pkg_id);
+    match &applied.change {
+        ManifestChange::AddPackage { package } => {
+            al.add(
+                PackageAddActivity {
+                    package: package.clone(),
+                },
+                ActivityType::AddPackage,
+                meta,
+            )
+            .await
+        }
+        ManifestChange::RemovePackage { package } => {
+            al.add(
+                PackageDeleteActivity {
+                    package: package.clone(),
+                },
+                ActivityType::RemovePackage,
+                meta,
+            )
+            .await
+        }
+        ManifestChange::UpdatePackage { package, old, new } => {
+            al.add(
+                PackagePatchActivity::new(package.clone(), old, new)?,
+                ActivityType::PatchPackage,
+                meta,
+            )
+            .await
+        }
+        ManifestChange::SetSetting { key, old, new, .. } => {
+            al.add(
+                SettingUpdateActivity {
+                    key: key.clone(),
+                    pkg_id: applied.pkg_id,
+                    old: old.clone(),
+                    new: Some(new.clone()),
+                },
+                ActivityType::PatchSetting,
+                meta,
+            )
+            .await
+        }
+        ManifestChange::ResetSetting { key, old, .. } => {
+            al.add(
+                SettingUpdateActivity {
+                    key: key.clone(),
+                    pkg_id: applied.pkg_id,
+                    old: Some(old.clone()),
+                    new: None,
+                },
+                ActivityType::ResetSetting,
+                meta,
+            )
+            .await
+        }
+    }
+}
+
+/// Dump the current packages and stored settings as manifest. Uploaded packages are skipped.
+#[utoipa::path(
+    responses(
+            (status = 200, description = "Manifest of the current state", content_type = "application/toml"),
+            (status = 400, description = "Unknown format"),
+    ),
+    params(
+            ("format" = Option<String>, Query, description = "toml (default) or yaml"),
+    )
+)]
+#[get("/manifest/export?<format>")]
+pub async fn manifest_export(
+    db: &State<DatabaseConnection>,
+    format: Option<&str>,
+    _a: Authenticated,
+) -> Result<(ContentType, String), Custom<String>> {
+    let db = db as &DatabaseConnection;
+    let format = parse_format(format, None)?;
+
+    let manifest = aurcache_utils::manifest::export(db)
+        .await
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+    let serialized = manifest
+        .serialize(format)
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+
+    let (top, sub) = format
+        .content_type()
+        .split_once('/')
+        .unwrap_or(("text", "plain"));
+    Ok((ContentType::new(top, sub), serialized))
+}
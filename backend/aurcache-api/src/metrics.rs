@@ -0,0 +1,94 @@
+use aurcache_db::builds;
+use aurcache_db::packages;
+use aurcache_db::prelude::{Builds, Packages};
+use aurcache_metrics::DB_GAUGES;
+use aurcache_types::builder::BuildStates;
+use aurcache_utils::utils::dir_size::dir_size;
+use rocket::http::{ContentType, Status};
+use rocket::response::status::Custom;
+use rocket::{State, get};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter};
+use sea_orm::{FromQueryResult, QuerySelect};
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(metrics))]
+pub struct MetricsApi;
+
+#[utoipa::path(
+    responses(
+            (status = 200, description = "Metrics in Prometheus text exposition format", content_type = "text/plain"),
+    )
+)]
+#[get("/metrics")]
+pub async fn metrics(
+    db: &State<DatabaseConnection>,
+) -> Result<(ContentType, String), Custom<String>> {
+    let db = db as &DatabaseConnection;
+
+    refresh_db_gauges(db)
+        .await
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+
+    let body = aurcache_metrics::gather()
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+    Ok((
+        ContentType::new("text", "plain").with_params(("version", "0.0.4")),
+        body,
+    ))
+}
+
+/// Update all gauges which are derived from the database and the repo directory
+async fn refresh_db_gauges(db: &DatabaseConnection) -> anyhow::Result<()> {
+    #[derive(Debug, FromQueryResult)]
+    struct BuildCount {
+        status: Option<i32>,
+        platform: String,
+        count: i64,
+    }
+
+    let build_counts = Builds::find()
+        .select_only()
+        .column(builds::Column::Status)
+        .column(builds::Column::Platform)
+        .column_as(builds::Column::Id.count(), "count")
+        .group_by(builds::Column::Status)
+        .group_by(builds::Column::Platform)
+        .into_model::<BuildCount>()
+        .all(db)
+        .await?;
+
+    DB_GAUGES.builds.reset();
+    for build_count in build_counts {
+        DB_GAUGES
+            .builds
+            .with_label_values(&[status_label(build_count.status), &build_count.platform])
+            .set(build_count.count);
+    }
+
+    DB_GAUGES
+        .packages
+        .set(Packages::find().count(db).await?.try_into()?);
+    DB_GAUGES.out_of_date_packages.set(
+        Packages::find()
+            .filter(packages::Column::OutOfDate.eq(1))
+            .count(db)
+            .await?
+            .try_into()?,
+    );
+    // walks the whole repo, so keep it off the async workers
+    let repo_size = tokio::task::spawn_blocking(|| dir_size("repo/").unwrap_or(0)).await?;
+    DB_GAUGES.repo_size.set(repo_size.try_into()?);
+    Ok(())
+}
+
+fn status_label(status: Option<i32>) -> &'static str {
+    match status {
+        Some(BuildStates::ACTIVE_BUILD) => "active",
+        Some(BuildStates::SUCCESSFUL_BUILD) => "successful",
+        Some(BuildStates::FAILED_BUILD) => "failed",
+        Some(BuildStates::ENQUEUED_BUILD) => "enqueued",
+        Some(BuildStates::CANCELED_BUILD) => "canceled",
+        _ => "unknown",
+    }
+}
@@ -0,0 +1,110 @@
+use crate::models::authenticated::Authenticated;
+use crate::models::mirrors::{MirrorBenchmarkModel, MirrorlistModel};
+use aurcache_builder::build_mode::mirrorlist_file;
+use aurcache_db::mirror_benchmarks;
+use aurcache_db::prelude::MirrorBenchmarks;
+use aurcache_types::builder::Action;
+use aurcache_utils::mirrors::mirrorlist_override;
+use pacman_mirrors::platforms::{Platform, Platforms};
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use rocket::{State, get, post};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use std::time::UNIX_EPOCH;
+use tokio::fs;
+use tokio::sync::broadcast::Sender;
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(mirrors, rank_mirrors))]
+pub struct MirrorsApi;
+
+#[utoipa::path(
+    responses(
+            (status = 200, description = "Current mirrorlist and last ranking per platform", body = [Vec<MirrorlistModel>]),
+    )
+)]
+#[get("/mirrors")]
+pub async fn mirrors(
+    db: &State<DatabaseConnection>,
+    _a: Authenticated,
+) -> Result<Json<Vec<MirrorlistModel>>, Custom<String>> {
+    let db = db as &DatabaseConnection;
+
+    let mut mirrorlists = vec![];
+    for platform in Platforms {
+        let benchmarks = MirrorBenchmarks::find()
+            .filter(mirror_benchmarks::Column::Platform.eq(platform.as_str()))
+            .order_by_asc(mirror_benchmarks::Column::Rank)
+            .all(db)
+            .await
+            .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+
+        // todo arm mirrorlists unsupported for now!
+        let (generated, servers) = if platform == Platform::X86_64 {
+            read_mirrorlist(&mirrorlist_file()).await
+        } else {
+            (None, vec![])
+        };
+        if generated.is_none() && benchmarks.is_empty() {
+            continue;
+        }
+
+        mirrorlists.push(MirrorlistModel {
+            platform: platform.to_string(),
+            generated,
+            servers,
+            ranked: benchmarks.first().map(|b| b.timestamp),
+            benchmarks: benchmarks
+                .into_iter()
+                .map(MirrorBenchmarkModel::from)
+                .collect(),
+            manual: platform == Platform::X86_64 && mirrorlist_override(),
+        });
+    }
+
+    Ok(Json(mirrorlists))
+}
+
+/// mtime and `Server` entries of a mirrorlist file
+async fn read_mirrorlist(path: &str) -> (Option<i64>, Vec<String>) {
+    let generated = fs::metadata(path)
+        .await
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+        .and_then(|d| i64::try_from(d.as_secs()).ok());
+    let servers = fs::read_to_string(path)
+        .await
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Server"))
+        .filter_map(|rest| rest.trim_start().strip_prefix('='))
+        .map(|server| server.trim().to_string())
+        .collect();
+    (generated, servers)
+}
+
+#[utoipa::path(
+    responses(
+            (status = 202, description = "Mirror ranking started in the background"),
+            (status = 409, description = "Mirrorlist is set manually, ranking is disabled"),
+    )
+)]
+#[post("/mirrors/rank")]
+pub async fn rank_mirrors(
+    tx: &State<Sender<Action>>,
+    _a: Authenticated,
+) -> Result<Status, Custom<String>> {
+    if mirrorlist_override() {
+        return Err(Custom(
+            Status::Conflict,
+            "Mirrorlist is set via MIRRORLIST_SERVERS_X86_64, ranking is disabled".to_string(),
+        ));
+    }
+
+    tx.send(Action::RankMirrors)
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+    Ok(Status::Accepted)
+}
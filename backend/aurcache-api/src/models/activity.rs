@@ -0,0 +1,33 @@
+use aurcache_activitylog::activity_utils::ActivityFilter;
+use aurcache_db::activities::ActivityType;
+use rocket::FromForm;
+use std::str::FromStr;
+
+#[derive(FromForm, Debug, Default)]
+pub struct ActivityQuery {
+    pub user: Option<String>,
+    pub typ: Vec<String>,
+    pub pkgid: Option<i32>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub limit: Option<u64>,
+    pub page: Option<u64>,
+}
+
+impl ActivityQuery {
+    pub fn into_filter(self) -> anyhow::Result<ActivityFilter> {
+        Ok(ActivityFilter {
+            user: self.user,
+            types: self
+                .typ
+                .iter()
+                .map(|t| ActivityType::from_str(t))
+                .collect::<anyhow::Result<Vec<_>>>()?,
+            pkg_id: self.pkgid,
+            from: self.from,
+            to: self.to,
+            limit: self.limit,
+            page: self.page,
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use aurcache_activitylog::activity_utils::ActivityMeta;
 use rocket::Request;
 use rocket::http::Status;
 use rocket::request::{FromRequest, Outcome};
@@ -5,9 +6,47 @@ use rocket::request::{FromRequest, Outcome};
 #[derive(Debug, Clone)]
 pub struct OauthEnabled(pub bool);
 
+/// Tokens of `API_TOKENS`, accepted as `Authorization: Bearer <token>` for scripts and the cli
+#[derive(Debug, Clone, Default)]
+pub struct ApiTokens(pub Vec<ApiToken>);
+
+#[derive(Debug, Clone)]
+pub struct ApiToken {
+    /// shown as user in the activity log
+    pub name: String,
+    pub token: String,
+}
+
+impl ApiTokens {
+    /// Name of the matching token
+    fn find(&self, token: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|t| constant_time_eq(t.token.as_bytes(), token.as_bytes()))
+            .map(|t| t.name.as_str())
+    }
+}
+
+/// Compare without short-circuiting on the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 #[derive(Debug)]
 pub struct Authenticated {
     pub username: Option<String>,
+    pub ip: Option<String>,
+}
+
+impl Authenticated {
+    /// Activity log metadata for an action performed by this user
+    pub fn activity_meta(&self, pkg_id: Option<i32>) -> ActivityMeta {
+        ActivityMeta {
+            user: self.username.clone(),
+            ip: self.ip.clone(),
+            pkg_id,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -20,6 +59,22 @@ impl<'r> FromRequest<'r> for Authenticated {
     type Error = LoginError;
 
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let ip = req.client_ip().map(|ip| ip.to_string());
+        if let Some(token) = req
+            .headers()
+            .get_one("Authorization")
+            .and_then(|h| h.strip_prefix("Bearer "))
+        {
+            let tokens = req.rocket().state::<ApiTokens>();
+            return match tokens.and_then(|tokens| tokens.find(token.trim())) {
+                Some(name) => Outcome::Success(Authenticated {
+                    username: Some(name.to_string()),
+                    ip,
+                }),
+                None => Outcome::Error((Status::Unauthorized, LoginError::InvalidData)),
+            };
+        }
+
         let oauth_enabled = req
             .rocket()
             .state::<OauthEnabled>()
@@ -36,11 +91,11 @@ impl<'r> FromRequest<'r> for Authenticated {
                             .get_private("username")
                             .and_then(|cookie| cookie.value().parse().ok());
 
-                        Outcome::Success(Authenticated { username })
+                        Outcome::Success(Authenticated { username, ip })
                     },
                 )
         } else {
-            Outcome::Success(Authenticated { username: None })
+            Outcome::Success(Authenticated { username: None, ip })
         }
     }
 }
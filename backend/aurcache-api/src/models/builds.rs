@@ -1,15 +1,59 @@
+use aurcache_types::builder::LintFinding;
 use rocket::serde::{Deserialize, Serialize};
 use sea_orm::FromQueryResult;
 use utoipa::ToSchema;
 
 #[derive(FromQueryResult, Deserialize, ToSchema, Serialize)]
 pub struct ListBuildsModel {
-    id: i32,
-    pkg_id: i32,
-    pkg_name: String,
-    version: String,
-    status: i32,
-    start_time: Option<i64>,
-    end_time: Option<i64>,
-    platform: String,
+    pub id: i32,
+    pub pkg_id: i32,
+    pub pkg_name: String,
+    pub version: String,
+    pub status: i32,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub platform: String,
+    /// peak memory usage in bytes
+    pub peak_memory: Option<i64>,
+    /// total cpu time in milliseconds
+    pub cpu_time: Option<i64>,
+    /// container wall time in milliseconds
+    pub wall_time: Option<i64>,
+    pub net_rx: Option<i64>,
+    pub net_tx: Option<i64>,
+    pub blk_read: Option<i64>,
+    pub blk_write: Option<i64>,
+}
+
+#[derive(Deserialize, ToSchema, Serialize)]
+pub struct BuildDetailsModel {
+    #[serde(flatten)]
+    pub build: ListBuildsModel,
+    /// namcap findings, `None` if namcap didn't run for this build
+    pub lint: Option<Vec<LintFinding>>,
+}
+
+#[derive(FromQueryResult, Deserialize, ToSchema, Serialize)]
+pub struct BuildResourceUsageModel {
+    pub id: i32,
+    pub version: String,
+    pub platform: String,
+    pub status: Option<i32>,
+    pub start_time: Option<i64>,
+    pub peak_memory: Option<i64>,
+    pub cpu_time: Option<i64>,
+    pub wall_time: Option<i64>,
+    pub net_rx: Option<i64>,
+    pub net_tx: Option<i64>,
+    pub blk_read: Option<i64>,
+    pub blk_write: Option<i64>,
+}
+
+/// Resource usage of the recent builds of a package, oldest first
+#[derive(Deserialize, ToSchema, Serialize)]
+pub struct ResourceTrendModel {
+    pub builds: Vec<BuildResourceUsageModel>,
+    pub max_peak_memory: Option<i64>,
+    pub avg_cpu_time: Option<i64>,
+    pub avg_wall_time: Option<i64>,
 }
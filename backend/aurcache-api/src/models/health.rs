@@ -0,0 +1,9 @@
+use crate::models::mirrors::MirrorHealthModel;
+use rocket::serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct HealthModel {
+    /// last mirror health probe, none before the first one finished
+    pub mirrors: Option<MirrorHealthModel>,
+}
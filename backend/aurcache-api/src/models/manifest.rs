@@ -0,0 +1,71 @@
+use aurcache_utils::manifest::{AppliedChange, ManifestChange};
+use rocket::FromForm;
+use rocket::serde::json::{Value, to_value};
+use rocket::serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(FromForm, Debug, Default)]
+pub struct ManifestApplyQuery {
+    pub dry_run: Option<bool>,
+    pub prune: Option<bool>,
+    pub format: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ManifestApplyModel {
+    pub dry_run: bool,
+    /// changes in the order they were (or would be) applied
+    pub changes: Vec<ManifestChangeModel>,
+    /// number of changes that failed to apply
+    pub failed: usize,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ManifestChangeModel {
+    /// add_package, remove_package, update_package, set_setting or reset_setting
+    pub action: String,
+    /// none for global settings
+    pub package: Option<String>,
+    /// setting key of setting changes
+    pub key: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub old: Option<Value>,
+    #[schema(value_type = Option<Object>)]
+    pub new: Option<Value>,
+    pub error: Option<String>,
+}
+
+impl From<AppliedChange> for ManifestChangeModel {
+    fn from(applied: AppliedChange) -> Self {
+        let (action, key, old, new) = match &applied.change {
+            ManifestChange::AddPackage { .. } => ("add_package", None, None, None),
+            ManifestChange::RemovePackage { .. } => ("remove_package", None, None, None),
+            ManifestChange::UpdatePackage { old, new, .. } => (
+                "update_package",
+                None,
+                to_value(old).ok(),
+                to_value(new).ok(),
+            ),
+            ManifestChange::SetSetting { key, old, new, .. } => (
+                "set_setting",
+                Some(key.clone()),
+                old.clone().map(Value::String),
+                Some(Value::String(new.clone())),
+            ),
+            ManifestChange::ResetSetting { key, old, .. } => (
+                "reset_setting",
+                Some(key.clone()),
+                Some(Value::String(old.clone())),
+                None,
+            ),
+        };
+        Self {
+            action: action.to_string(),
+            package: applied.change.package().map(str::to_string),
+            key,
+            old,
+            new,
+            error: applied.error,
+        }
+    }
+}
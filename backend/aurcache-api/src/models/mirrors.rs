@@ -0,0 +1,105 @@
+use aurcache_db::mirror_benchmarks;
+use aurcache_utils::mirrors::MirrorHealthCheck;
+use pacman_mirrors::health::{MirrorHealth, MirrorState};
+use rocket::serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct MirrorBenchmarkModel {
+    /// position in the ranking, starting at 1
+    pub rank: i32,
+    pub url: String,
+    pub country: String,
+    pub protocol: String,
+    /// average transfer rate in KiB/s
+    pub throughput: f64,
+    /// average seconds until the response headers arrived
+    pub latency: f64,
+    /// score of archlinux.org, lower is better
+    pub upstream_score: Option<f64>,
+    /// combined score, higher is better
+    pub score: f64,
+}
+
+impl From<mirror_benchmarks::Model> for MirrorBenchmarkModel {
+    fn from(model: mirror_benchmarks::Model) -> Self {
+        MirrorBenchmarkModel {
+            rank: model.rank,
+            url: model.url,
+            country: model.country,
+            protocol: model.protocol,
+            throughput: model.throughput,
+            latency: model.latency,
+            upstream_score: model.upstream_score,
+            score: model.score,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MirrorlistModel {
+    pub platform: String,
+    /// unix timestamp of the last write of the mirrorlist
+    pub generated: Option<i64>,
+    /// `Server` entries of the mirrorlist in order
+    pub servers: Vec<String>,
+    /// unix timestamp of the last ranking
+    pub ranked: Option<i64>,
+    /// benchmark results of the last ranking, best first
+    pub benchmarks: Vec<MirrorBenchmarkModel>,
+    /// mirrorlist is set via `MIRRORLIST_SERVERS_X86_64` and not ranked
+    pub manual: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MirrorServerHealthModel {
+    /// `Server` entry of the mirrorlist
+    pub server: String,
+    /// `healthy`, `stale` or `dead`
+    pub state: String,
+    /// HTTP status of `core.db`
+    pub http_status: Option<u16>,
+    /// unix timestamp of the last sync of the mirror
+    pub last_sync: Option<i64>,
+    pub error: Option<String>,
+}
+
+impl From<MirrorHealth> for MirrorServerHealthModel {
+    fn from(health: MirrorHealth) -> Self {
+        MirrorServerHealthModel {
+            server: health.server,
+            state: format!("{:?}", health.state).to_lowercase(),
+            http_status: health.http_status,
+            last_sync: health.last_sync.map(|t| t.timestamp()),
+            error: health.error,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MirrorHealthModel {
+    /// unix timestamp of the last probe
+    pub checked: i64,
+    pub healthy: usize,
+    pub stale: usize,
+    pub dead: usize,
+    /// probed servers in mirrorlist order before demotion
+    pub servers: Vec<MirrorServerHealthModel>,
+}
+
+impl From<MirrorHealthCheck> for MirrorHealthModel {
+    fn from(check: MirrorHealthCheck) -> Self {
+        let count = |state| check.mirrors.iter().filter(|m| m.state == state).count();
+        MirrorHealthModel {
+            checked: check.timestamp,
+            healthy: count(MirrorState::Healthy),
+            stale: count(MirrorState::Stale),
+            dead: count(MirrorState::Dead),
+            servers: check
+                .mirrors
+                .into_iter()
+                .map(MirrorServerHealthModel::from)
+                .collect(),
+        }
+    }
+}
@@ -1,6 +1,11 @@
+pub mod activity;
 pub mod aur;
 pub mod authenticated;
 pub mod builds;
+pub mod health;
+pub mod manifest;
+pub mod mirrors;
 pub mod package;
+pub mod repo;
 pub mod settings;
 pub mod stats;
@@ -1,20 +1,21 @@
 use aurcache_db::packages::SourceData;
+use aurcache_types::package::PackageFilter;
 use rocket::serde::{Deserialize, Serialize};
 use sea_orm::FromQueryResult;
 use utoipa::ToSchema;
 
-#[derive(Deserialize, ToSchema, Clone)]
+#[derive(Deserialize, Serialize, ToSchema, Clone)]
 #[serde(crate = "rocket::serde")]
 pub struct AddPackage {
-    pub(crate) platforms: Option<Vec<String>>,
-    pub(crate) build_flags: Option<Vec<String>>,
-    pub(crate) source: SourceData,
+    pub platforms: Option<Vec<String>>,
+    pub build_flags: Option<Vec<String>>,
+    pub source: SourceData,
 }
 
-#[derive(Deserialize, ToSchema)]
+#[derive(Deserialize, Serialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct UpdatePackage {
-    pub(crate) force: bool,
+    pub force: bool,
 }
 
 #[derive(FromQueryResult, Deserialize, ToSchema, Serialize, Default)]
@@ -27,6 +28,63 @@ pub struct PackagePatchModel {
     pub platforms: Option<Vec<String>>,
 }
 
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BulkAddPackages {
+    /// AUR package names
+    pub names: Vec<String>,
+    pub platforms: Option<Vec<String>>,
+    pub build_flags: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BulkUpdatePackages {
+    pub filter: PackageFilter,
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BulkDeletePackages {
+    pub filter: PackageFilter,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BulkPatchPackages {
+    pub filter: PackageFilter,
+    pub platforms: Option<Vec<String>>,
+    pub build_flags: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct BulkResultsModel {
+    /// one result per selected package or given name
+    pub results: Vec<BulkResultModel>,
+    /// number of packages the operation failed for
+    pub failed: usize,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct BulkResultModel {
+    /// none if the package could not be added
+    pub id: Option<i32>,
+    pub name: String,
+    /// ids of the enqueued builds
+    pub builds: Vec<i32>,
+    pub error: Option<String>,
+}
+
+impl BulkResultsModel {
+    #[must_use]
+    pub fn new(results: Vec<BulkResultModel>) -> Self {
+        let failed = results.iter().filter(|r| r.error.is_some()).count();
+        Self { results, failed }
+    }
+}
+
 #[derive(FromQueryResult, Deserialize, ToSchema, Serialize)]
 pub struct SimplePackageModel {
     pub id: i32,
@@ -76,7 +134,7 @@ pub struct AurNotFoundPackage {}
 
 #[derive(Deserialize, ToSchema, Serialize, Default, Clone)]
 pub struct AurPackage {
-    pub(crate) name: String,
+    pub name: String,
     pub project_url: Option<String>,
     pub description: Option<String>,
     pub last_updated: u32,
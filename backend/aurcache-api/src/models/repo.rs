@@ -0,0 +1,148 @@
+use aurcache_utils::repo::check::{PlatformCheck, RepairOptions};
+use pacman_repo_utils::repo_packages::RepoPackage;
+use rocket::serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct RepoCheckModel {
+    pub platform: String,
+    pub consistent: bool,
+    /// package archives on disk which are not tracked in the database
+    pub orphan_archives: Vec<String>,
+    /// database file entries whose archive doesn't exist on disk
+    pub missing_files: Vec<String>,
+    /// package archives on disk without an entry in repo.db
+    pub unindexed_files: Vec<String>,
+    /// repo.db entries whose archive doesn't exist on disk
+    pub stale_entries: Vec<String>,
+    /// repo.db entries with a checksum not matching the archive on disk
+    pub checksum_mismatches: Vec<String>,
+}
+
+impl From<PlatformCheck> for RepoCheckModel {
+    fn from(check: PlatformCheck) -> Self {
+        RepoCheckModel {
+            consistent: check.is_consistent(),
+            platform: check.platform,
+            orphan_archives: check.orphan_archives,
+            missing_files: check.missing_files,
+            unindexed_files: check.unindexed_files,
+            stale_entries: check.stale_entries,
+            checksum_mismatches: check.checksum_mismatches,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema, Default)]
+#[serde(crate = "rocket::serde")]
+pub struct RepairRepoModel {
+    /// delete package archives which are not tracked in the database
+    #[serde(default)]
+    pub orphan_archives: bool,
+    /// delete database file entries whose archive is gone
+    #[serde(default)]
+    pub missing_files: bool,
+    /// rebuild repo.db and repo.files from the archives on disk
+    #[serde(default)]
+    pub repo_database: bool,
+}
+
+impl From<&RepairRepoModel> for RepairOptions {
+    fn from(model: &RepairRepoModel) -> Self {
+        RepairOptions {
+            orphan_archives: model.orphan_archives,
+            missing_files: model.missing_files,
+            repo_database: model.repo_database,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RepoFileMatchModel {
+    pub platform: String,
+    pub package: String,
+    pub version: String,
+    /// path of the matching file inside the package, without leading slash
+    pub path: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RepoPackageFilesModel {
+    pub platform: String,
+    pub package: String,
+    pub version: String,
+    /// paths without leading slash, directories end with `/`
+    pub files: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RepoIndexPlatformSummary {
+    pub platform: String,
+    pub packages: usize,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RepoIndexModel {
+    pub repo: String,
+    pub pacman_conf: String,
+    pub platforms: Vec<RepoIndexPlatformSummary>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RepoIndexPlatformModel {
+    pub repo: String,
+    pub platform: String,
+    pub pacman_conf: String,
+    pub packages: Vec<RepoIndexPackageModel>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RepoIndexPackageModel {
+    pub name: String,
+    pub base: String,
+    pub version: String,
+    pub description: String,
+    pub url: String,
+    pub arch: String,
+    pub packager: String,
+    pub licenses: Vec<String>,
+    pub groups: Vec<String>,
+    pub provides: Vec<String>,
+    pub depends: Vec<String>,
+    pub optdepends: Vec<String>,
+    /// size of the package file in bytes
+    pub size: u64,
+    /// installed size in bytes
+    pub installed_size: u64,
+    /// unix timestamp
+    pub build_date: i64,
+    /// download link relative to the platform index
+    pub download: String,
+}
+
+impl From<RepoPackage> for RepoIndexPackageModel {
+    fn from(pkg: RepoPackage) -> Self {
+        RepoIndexPackageModel {
+            name: pkg.name,
+            base: pkg.base,
+            version: pkg.version,
+            description: pkg.desc,
+            url: pkg.url,
+            arch: pkg.arch,
+            packager: pkg.packager,
+            licenses: pkg.licenses,
+            groups: pkg.groups,
+            provides: pkg.provides,
+            depends: pkg.depends,
+            optdepends: pkg.optdepends,
+            size: pkg.csize,
+            installed_size: pkg.isize,
+            build_date: pkg.builddate,
+            download: pkg.filename,
+        }
+    }
+}
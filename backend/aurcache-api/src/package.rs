@@ -7,6 +7,7 @@ use crate::models::package::{
 use aurcache_activitylog::activity_utils::ActivityLog;
 use aurcache_activitylog::package_add_activity::PackageAddActivity;
 use aurcache_activitylog::package_delete_activity::PackageDeleteActivity;
+use aurcache_activitylog::package_patch_activity::PackagePatchActivity;
 use aurcache_activitylog::package_update_activity::PackageUpdateActivity;
 use aurcache_db::activities::ActivityType;
 use aurcache_db::packages::SourceData;
@@ -64,7 +65,7 @@ pub async fn package_add_endpoint(
         ),
     };
 
-    let new_pkg_name = package_add(
+    let new_pkg = package_add(
         db,
         tx,
         platforms,
@@ -76,10 +77,10 @@ pub async fn package_add_endpoint(
 
     al.add(
         PackageAddActivity {
-            package: new_pkg_name,
+            package: new_pkg.name,
         },
         ActivityType::AddPackage,
-        a.username,
+        a.activity_meta(Some(new_pkg.id)),
     )
     .await
     .map_err(|e| BadRequest(e.to_string()))?;
@@ -99,10 +100,35 @@ pub async fn package_update_entity_endpoint(
     db: &State<DatabaseConnection>,
     input: Json<PackagePatchModel>,
     id: i32,
-    _a: Authenticated,
+    a: Authenticated,
+    al: &State<ActivityLog>,
 ) -> Result<(), BadRequest<String>> {
     let db = db as &DatabaseConnection;
 
+    let pkg = Packages::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|e| BadRequest(e.to_string()))?
+        .ok_or(BadRequest("id not found".to_string()))?;
+
+    // snapshot of the values which get overwritten by this patch
+    let old = PackagePatchModel {
+        name: input.name.as_ref().map(|_| pkg.name.clone()),
+        status: input.status.map(|_| pkg.status),
+        out_of_date: input.out_of_date.map(|_| pkg.out_of_date),
+        latest_build: input.latest_build.map(|_| pkg.latest_build),
+        build_flags: input.build_flags.as_ref().map(|_| {
+            pkg.build_flags
+                .split(';')
+                .map(ToString::to_string)
+                .collect()
+        }),
+        platforms: input
+            .platforms
+            .as_ref()
+            .map(|_| pkg.platforms.split(';').map(ToString::to_string).collect()),
+    };
+
     // Start building the update operation
     let update_pkg = packages::ActiveModel {
         id: Set(id),
@@ -126,6 +152,15 @@ pub async fn package_update_entity_endpoint(
         .await
         .map_err(|e| BadRequest(e.to_string()))?;
 
+    al.add(
+        PackagePatchActivity::new(pkg.name, &old, &input.0)
+            .map_err(|e| BadRequest(e.to_string()))?,
+        ActivityType::PatchPackage,
+        a.activity_meta(Some(id)),
+    )
+    .await
+    .map_err(|e| BadRequest(e.to_string()))?;
+
     Ok(())
 }
 
@@ -165,7 +200,7 @@ pub async fn package_update_endpoint(
             forced: input.force,
         },
         ActivityType::UpdatePackage,
-        a.username,
+        a.activity_meta(Some(id)),
     )
     .await
     .map_err(|e| BadRequest(e.to_string()))?;
@@ -203,7 +238,7 @@ pub async fn package_del(
     al.add(
         PackageDeleteActivity { package: pkg.name },
         ActivityType::RemovePackage,
-        a.username,
+        a.activity_meta(Some(id)),
     )
     .await
     .map_err(|e| BadRequest(e.to_string()))?;
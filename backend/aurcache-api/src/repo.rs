@@ -0,0 +1,157 @@
+use crate::models::authenticated::Authenticated;
+use crate::models::repo::{
+    RepairRepoModel, RepoCheckModel, RepoFileMatchModel, RepoPackageFilesModel,
+};
+use aurcache_activitylog::activity_utils::ActivityLog;
+use aurcache_activitylog::repo_repair_activity::RepoRepairActivity;
+use aurcache_db::activities::ActivityType;
+use aurcache_utils::repo::check::{check_repo, repair_repo};
+use aurcache_utils::repo::files::{files_index, files_indexes};
+use pacman_mirrors::platforms::Platform;
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use rocket::{State, get, post};
+use sea_orm::DatabaseConnection;
+use std::str::FromStr;
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(repo_check, repo_repair, repo_files_search, repo_package_files))]
+pub struct RepoApi;
+
+#[utoipa::path(
+    responses(
+            (status = 200, description = "Consistency report of the repo of each platform", body = [Vec<RepoCheckModel>]),
+    )
+)]
+#[get("/repo/check")]
+pub async fn repo_check(
+    db: &State<DatabaseConnection>,
+    _a: Authenticated,
+) -> Result<Json<Vec<RepoCheckModel>>, Custom<String>> {
+    let db = db as &DatabaseConnection;
+
+    check_repo(db)
+        .await
+        .map(|checks| Json(checks.into_iter().map(RepoCheckModel::from).collect()))
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))
+}
+
+#[utoipa::path(
+    request_body = RepairRepoModel,
+    responses(
+            (status = 200, description = "Consistency report after the repair", body = [Vec<RepoCheckModel>]),
+    )
+)]
+#[post("/repo/repair", data = "<input>")]
+pub async fn repo_repair(
+    db: &State<DatabaseConnection>,
+    input: Json<RepairRepoModel>,
+    a: Authenticated,
+    al: &State<ActivityLog>,
+) -> Result<Json<Vec<RepoCheckModel>>, Custom<String>> {
+    let db = db as &DatabaseConnection;
+
+    let checks = repair_repo(db, (&input.0).into())
+        .await
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+
+    al.add(
+        RepoRepairActivity {
+            orphan_archives: input.orphan_archives,
+            missing_files: input.missing_files,
+            repo_database: input.repo_database,
+        },
+        ActivityType::RepairRepo,
+        a.activity_meta(None),
+    )
+    .await
+    .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+
+    Ok(Json(checks.into_iter().map(RepoCheckModel::from).collect()))
+}
+
+#[utoipa::path(
+    params(
+            ("q", description = "Path like /usr/bin/foo or file name like foo"),
+            ("platform" = Option<String>, Query, description = "Only search the repo of this platform"),
+    ),
+    responses(
+            (status = 200, description = "Packages owning the file", body = [Vec<RepoFileMatchModel>]),
+            (status = 400, description = "Unknown platform"),
+    )
+)]
+#[get("/repo/files/search?<q>&<platform>")]
+pub async fn repo_files_search(
+    q: &str,
+    platform: Option<&str>,
+    _a: Authenticated,
+) -> Result<Json<Vec<RepoFileMatchModel>>, Custom<String>> {
+    let platform = platform
+        .map(Platform::from_str)
+        .transpose()
+        .map_err(|e| Custom(Status::BadRequest, e.to_string()))?;
+    let indexes = match platform {
+        Some(platform) => files_index(platform).await.map(|index| {
+            index
+                .map(|i| (platform.to_string(), i))
+                .into_iter()
+                .collect()
+        }),
+        None => files_indexes().await,
+    }
+    .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+
+    Ok(Json(
+        indexes
+            .iter()
+            .flat_map(|(platform, index)| {
+                index.search(q).into_iter().map(|m| RepoFileMatchModel {
+                    platform: platform.clone(),
+                    package: m.package.name.clone(),
+                    version: m.package.version.clone(),
+                    path: m.path.to_string(),
+                })
+            })
+            .collect(),
+    ))
+}
+
+#[utoipa::path(
+    params(
+            ("platform", description = "Platform of the repo"),
+            ("package", description = "Name of the package in the repo"),
+    ),
+    responses(
+            (status = 200, description = "Files of the package", body = RepoPackageFilesModel),
+            (status = 404, description = "Unknown platform or package not in the repo of this platform"),
+    )
+)]
+#[get("/repo/files/<platform>/<package>")]
+pub async fn repo_package_files(
+    platform: &str,
+    package: &str,
+    _a: Authenticated,
+) -> Result<Json<RepoPackageFilesModel>, Custom<String>> {
+    let platform = Platform::from_str(platform)
+        .map_err(|_| Custom(Status::NotFound, format!("Unknown platform {platform}")))?;
+    let index = files_index(platform)
+        .await
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or(Custom(
+            Status::NotFound,
+            format!("No repo for platform {platform}"),
+        ))?;
+    let files = index.package(package).ok_or(Custom(
+        Status::NotFound,
+        format!("Package {package} not in {platform} repo"),
+    ))?;
+
+    Ok(Json(RepoPackageFilesModel {
+        platform: platform.to_string(),
+        package: files.name.clone(),
+        version: files.version.clone(),
+        files: files.files.clone(),
+    }))
+}
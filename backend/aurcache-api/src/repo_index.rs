@@ -0,0 +1,314 @@
+use crate::models::repo::{
+    RepoIndexModel, RepoIndexPackageModel, RepoIndexPlatformModel, RepoIndexPlatformSummary,
+};
+use anyhow::anyhow;
+use pacman_repo_utils::repo_packages::{RepoPackage, repo_packages};
+use rocket::Request;
+use rocket::http::ContentType;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// name of the pacman repo, the databases are `<platform>/repo.db`
+const REPO_NAME: &str = "repo";
+const INDEX_JSON: &str = "index.json";
+
+/// Index page requested from the repo file server
+pub(crate) struct IndexRequest {
+    platform: Option<String>,
+    json: bool,
+}
+
+impl IndexRequest {
+    /// Match `/`, `/index.json`, `/<platform>/` and `/<platform>/index.json`
+    pub(crate) fn from_path(root: &Path, relative_path: &Path) -> Option<Self> {
+        let segments: Vec<&str> = relative_path.iter().filter_map(|s| s.to_str()).collect();
+        let (platform, json) = match segments.as_slice() {
+            [] => (None, false),
+            [INDEX_JSON] => (None, true),
+            [platform] => (Some(*platform), false),
+            [platform, INDEX_JSON] => (Some(*platform), true),
+            _ => return None,
+        };
+        if platform.is_some_and(|p| !root.join(p).is_dir()) {
+            return None;
+        }
+        Some(IndexRequest {
+            platform: platform.map(str::to_string),
+            json,
+        })
+    }
+
+    /// HTML index of a platform requested without trailing slash, relative links need one
+    pub(crate) fn needs_redirect(&self, req: &Request<'_>) -> bool {
+        self.platform.is_some() && !self.json && !req.uri().path().ends_with('/')
+    }
+
+    pub(crate) async fn render(
+        self,
+        root: &Path,
+        req: &Request<'_>,
+    ) -> anyhow::Result<(ContentType, String)> {
+        let query = req
+            .query_value::<String>("q")
+            .and_then(Result::ok)
+            .filter(|q| !q.trim().is_empty());
+        let pacman_conf = pacman_conf(req);
+        let root = root.to_path_buf();
+
+        match self.platform {
+            None => {
+                let platforms = tokio::task::spawn_blocking(move || platforms(&root))
+                    .await
+                    .map_err(|e| anyhow!(e))??;
+                Ok(if self.json {
+                    let model = RepoIndexModel {
+                        repo: REPO_NAME.to_string(),
+                        pacman_conf,
+                        platforms: platforms
+                            .into_iter()
+                            .map(|(platform, packages)| RepoIndexPlatformSummary {
+                                platform,
+                                packages,
+                            })
+                            .collect(),
+                    };
+                    (ContentType::JSON, rocket::serde::json::to_string(&model)?)
+                } else {
+                    (ContentType::HTML, render_root(&platforms, &pacman_conf))
+                })
+            }
+            Some(platform) => {
+                let db = db_path(&root, &platform);
+                let mut packages = tokio::task::spawn_blocking(move || repo_packages(&db))
+                    .await
+                    .map_err(|e| anyhow!(e))??;
+                if let Some(query) = &query {
+                    packages.retain(|p| p.matches(query));
+                }
+                Ok(if self.json {
+                    let model = RepoIndexPlatformModel {
+                        repo: REPO_NAME.to_string(),
+                        platform: platform.clone(),
+                        pacman_conf,
+                        packages: packages
+                            .into_iter()
+                            .map(RepoIndexPackageModel::from)
+                            .collect(),
+                    };
+                    (ContentType::JSON, rocket::serde::json::to_string(&model)?)
+                } else {
+                    let html =
+                        render_platform(&platform, &packages, query.as_deref(), &pacman_conf);
+                    (ContentType::HTML, html)
+                })
+            }
+        }
+    }
+}
+
+fn db_path(root: &Path, platform: &str) -> String {
+    root.join(platform)
+        .join(format!("{REPO_NAME}.db"))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Platform directories of the repo with their package count
+fn platforms(root: &Path) -> anyhow::Result<Vec<(String, usize)>> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(root)?
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.path())
+        .filter(|p| {
+            !p.file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        })
+        .collect();
+    dirs.sort();
+
+    Ok(dirs
+        .into_iter()
+        .filter_map(|dir| {
+            let platform = dir.file_name()?.to_string_lossy().to_string();
+            let count = repo_packages(&db_path(root, &platform)).map_or(0, |p| p.len());
+            Some((platform, count))
+        })
+        .collect())
+}
+
+/// `pacman.conf` section for this server, as reached by the client
+fn pacman_conf(req: &Request<'_>) -> String {
+    let scheme = req.headers().get_one("X-Forwarded-Proto").unwrap_or("http");
+    let host = req
+        .host()
+        .map_or_else(|| "<server_ip>:8081".to_string(), ToString::to_string);
+    format!("[{REPO_NAME}]\nSigLevel = Optional TrustAll\nServer = {scheme}://{host}/$arch\n")
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Link to the upstream url of a package, only for http(s) urls so `javascript:` and co. never end up in a href
+fn upstream_link(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => format!(
+            r#" <a class="muted" href="{}" rel="noopener noreferrer">&#8599;</a>"#,
+            escape(parsed.as_str())
+        ),
+        _ if url.is_empty() => String::new(),
+        _ => format!(r#" <span class="muted">{}</span>"#, escape(url)),
+    }
+}
+
+fn format_date(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|d| d.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default()
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; color: #222; }}
+table {{ border-collapse: collapse; width: 100%; }}
+th, td {{ text-align: left; padding: 0.3em 0.6em; border-bottom: 1px solid #ddd; vertical-align: top; }}
+pre {{ background: #f4f4f4; padding: 1em; }}
+.muted {{ color: #777; }}
+</style>
+</head>
+<body>
+{body}
+</body>
+</html>
+"#,
+        title = escape(title),
+    )
+}
+
+fn render_root(platforms: &[(String, usize)], pacman_conf: &str) -> String {
+    let mut body = format!("<h1>{REPO_NAME}</h1>\n<h2>Platforms</h2>\n<ul>\n");
+    for (platform, count) in platforms {
+        let platform = escape(platform);
+        _ = writeln!(
+            body,
+            r#"<li><a href="{platform}/">{platform}</a> <span class="muted">({count} packages)</span></li>"#
+        );
+    }
+    _ = write!(
+        body,
+        "</ul>\n<h2>Usage</h2>\n<p>Add to <code>/etc/pacman.conf</code>:</p>\n<pre>{}</pre>\n<p><a href=\"{INDEX_JSON}\">JSON</a></p>",
+        escape(pacman_conf)
+    );
+    page(REPO_NAME, &body)
+}
+
+fn render_platform(
+    platform: &str,
+    packages: &[RepoPackage],
+    query: Option<&str>,
+    pacman_conf: &str,
+) -> String {
+    let query = escape(query.unwrap_or_default());
+    let mut body = format!(
+        r#"<h1><a href="../">{REPO_NAME}</a> / {platform}</h1>
+<form method="get"><input type="search" name="q" value="{query}" placeholder="Search packages" autofocus> <button type="submit">Search</button></form>
+<p class="muted">{count} packages</p>
+<table>
+<tr><th>Name</th><th>Version</th><th>Description</th><th>Size</th><th>Installed</th><th>Build date</th><th>Depends</th></tr>
+"#,
+        platform = escape(platform),
+        count = packages.len(),
+    );
+    for pkg in packages {
+        _ = writeln!(
+            body,
+            r#"<tr><td><a href="{filename}" download>{name}</a>{url}</td><td>{version}</td><td>{desc}</td><td>{csize}</td><td>{isize}</td><td>{date}</td><td>{depends}</td></tr>"#,
+            filename = escape(&pkg.filename),
+            name = escape(&pkg.name),
+            url = upstream_link(&pkg.url),
+            version = escape(&pkg.version),
+            desc = escape(&pkg.desc),
+            csize = human_size(pkg.csize),
+            isize = human_size(pkg.isize),
+            date = format_date(pkg.builddate),
+            depends = escape(&pkg.depends.join(", ")),
+        );
+    }
+    _ = write!(
+        body,
+        "</table>\n<h2>Usage</h2>\n<p>Add to <code>/etc/pacman.conf</code>:</p>\n<pre>{}</pre>\n<p><a href=\"{INDEX_JSON}\">JSON</a></p>",
+        escape(pacman_conf)
+    );
+    page(&format!("{REPO_NAME} / {platform}"), &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(url: &str) -> RepoPackage {
+        RepoPackage {
+            filename: "foo-1.0-1-x86_64.pkg.tar.zst".to_string(),
+            name: "foo".to_string(),
+            version: "1.0-1".to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn links_http_urls_only() {
+        let page = render_platform(
+            "x86_64",
+            &[package("https://example.org/a?b=1&c=2")],
+            None,
+            "",
+        );
+        assert!(page.contains(r#"href="https://example.org/a?b=1&amp;c=2""#));
+
+        for url in [
+            "javascript:alert(1)",
+            "JavaScript:alert(1)",
+            "data:text/html,x",
+            "example.org",
+        ] {
+            let page = render_platform("x86_64", &[package(url)], None, "");
+            assert!(!page.contains("&#8599;"), "{url} rendered as link");
+            assert!(page.contains(&escape(url)), "{url} missing as text");
+        }
+    }
+
+    #[test]
+    fn escapes_platform_once() {
+        let page = render_platform("a&b", &[], None, "");
+        assert!(page.contains("<title>repo / a&amp;b</title>"));
+        assert!(page.contains("</a> / a&amp;b</h1>"));
+        assert!(!page.contains("&amp;amp;"));
+    }
+}
@@ -1,5 +1,8 @@
 use crate::models::authenticated::Authenticated;
 use crate::models::settings::{SettingResponse, SettingValue};
+use aurcache_activitylog::activity_utils::ActivityLog;
+use aurcache_activitylog::setting_update_activity::SettingUpdateActivity;
+use aurcache_db::activities::ActivityType;
 use aurcache_types::settings::{ApplicationSettings, Setting};
 use aurcache_utils::settings::general::SettingsTraits;
 use rocket::http::Status;
@@ -84,14 +87,31 @@ pub async fn setting_patch(
     key: &str,
     pkgid: Option<i32>,
     input: Json<SettingValue>,
-    _a: Authenticated,
+    a: Authenticated,
+    al: &State<ActivityLog>,
 ) -> Result<(), Custom<String>> {
     let setting = parse_setting(key)?;
     let db = db as &DatabaseConnection;
 
+    let old = ApplicationSettings::get::<String>(setting, pkgid, db)
+        .await
+        .value;
     ApplicationSettings::patch(db, [(setting, pkgid, Some(input.value.clone()))])
         .await
-        .map_err(|e| Custom(Status::BadRequest, e.to_string()))
+        .map_err(|e| Custom(Status::BadRequest, e.to_string()))?;
+
+    al.add(
+        SettingUpdateActivity {
+            key: key.to_string(),
+            pkg_id: pkgid,
+            old: Some(old),
+            new: Some(input.value.clone()),
+        },
+        ActivityType::PatchSetting,
+        a.activity_meta(pkgid),
+    )
+    .await
+    .map_err(|e| Custom(Status::InternalServerError, e.to_string()))
 }
 
 /// Reset a setting back to its default by deleting any stored override.
@@ -110,12 +130,29 @@ pub async fn setting_reset(
     db: &State<DatabaseConnection>,
     key: &str,
     pkgid: Option<i32>,
-    _a: Authenticated,
+    a: Authenticated,
+    al: &State<ActivityLog>,
 ) -> Result<(), Custom<String>> {
     let setting = parse_setting(key)?;
     let db = db as &DatabaseConnection;
 
+    let old = ApplicationSettings::get::<String>(setting, pkgid, db)
+        .await
+        .value;
     ApplicationSettings::patch(db, [(setting, pkgid, None)])
         .await
-        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+
+    al.add(
+        SettingUpdateActivity {
+            key: key.to_string(),
+            pkg_id: pkgid,
+            old: Some(old),
+            new: None,
+        },
+        ActivityType::ResetSetting,
+        a.activity_meta(pkgid),
+    )
+    .await
+    .map_err(|e| Custom(Status::InternalServerError, e.to_string()))
 }
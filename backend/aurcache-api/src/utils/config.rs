@@ -1,3 +1,4 @@
+use crate::models::authenticated::{ApiToken, ApiTokens};
 use rocket_oauth2::{OAuthConfig, StaticProvider};
 use std::env;
 
@@ -15,3 +16,27 @@ pub fn oauth_config_from_env() -> anyhow::Result<OAuthConfig> {
         Some(env::var("OAUTH_REDIRECT_URI")?),
     ))
 }
+
+/// `API_TOKENS` as comma separated list of `name:token` or plain tokens
+pub fn api_tokens_from_env() -> ApiTokens {
+    let tokens = env::var("API_TOKENS").unwrap_or_default();
+    ApiTokens(
+        tokens
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .enumerate()
+            .map(|(i, t)| match t.split_once(':') {
+                Some((name, token)) => ApiToken {
+                    name: name.trim().to_string(),
+                    token: token.trim().to_string(),
+                },
+                None => ApiToken {
+                    name: format!("api-token-{}", i + 1),
+                    token: t.to_string(),
+                },
+            })
+            .filter(|t| !t.token.is_empty())
+            .collect(),
+    )
+}
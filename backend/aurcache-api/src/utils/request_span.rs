@@ -0,0 +1,72 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::route::{Handler, Outcome};
+use rocket::{Data, Request, Response, Route};
+use tracing::field::Empty;
+use tracing::{Instrument, Span, info_span};
+
+/// Opens a tracing span for every HTTP request which lives until the request is dropped.
+///
+/// Handlers only run inside the span if their routes are wrapped with [`instrument_routes`].
+pub struct RequestSpan;
+
+struct RequestSpanGuard(Span);
+
+fn request_span<'r>(req: &'r Request<'_>) -> &'r Span {
+    &req.local_cache(|| RequestSpanGuard(Span::none())).0
+}
+
+#[rocket::async_trait]
+impl Fairing for RequestSpan {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request span",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let span = info_span!(
+            "http_request",
+            otel.name = %format!("{} {}", req.method(), req.uri().path()),
+            otel.kind = "server",
+            http.request.method = %req.method(),
+            url.path = %req.uri().path(),
+            http.route = Empty,
+            http.response.status_code = Empty,
+        );
+        req.local_cache(|| RequestSpanGuard(span));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let span = request_span(req);
+        if let Some(route) = req.route() {
+            span.record("otel.name", format!("{} {}", req.method(), route.uri));
+            span.record("http.route", route.uri.to_string());
+        }
+        span.record("http.response.status_code", res.status().code);
+    }
+}
+
+/// Runs the wrapped handler, including its request guards, inside the request span
+#[derive(Clone)]
+struct InRequestSpan(Box<dyn Handler>);
+
+#[rocket::async_trait]
+impl Handler for InRequestSpan {
+    async fn handle<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r> {
+        let span = request_span(req).clone();
+        self.0.handle(req, data).instrument(span).await
+    }
+}
+
+/// Make spans and events of the handlers of `routes` children of their request span
+pub fn instrument_routes(routes: impl Into<Vec<Route>>) -> Vec<Route> {
+    routes
+        .into()
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(InRequestSpan(route.handler));
+            route
+        })
+        .collect()
+}
@@ -1,8 +1,15 @@
 use crate::logger::BuildLogger;
 use crate::path_utils::create_active_build_path;
+use crate::resource_usage::{ResourceUsage, sample_resource_usage};
 use anyhow::{anyhow, bail};
+use aurcache_activitylog::activity_serializer::ActivitySerializer;
+use aurcache_activitylog::activity_utils::{ActivityLog, ActivityMeta};
+use aurcache_activitylog::build_finish_activity::BuildFinishActivity;
+use aurcache_activitylog::build_start_activity::BuildStartActivity;
+use aurcache_db::activities::ActivityType;
 use aurcache_db::helpers::active_value_ext::ActiveValueExt;
 use aurcache_db::{builds, packages};
+use aurcache_metrics::{ACTIVE_BUILDS, BUILD_DURATION};
 use aurcache_types::builder::BuildStates;
 use aurcache_types::settings::{ApplicationSettings, Setting, SettingSource, SettingsEntry};
 use aurcache_utils::settings::general::SettingsTraits;
@@ -16,10 +23,10 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::Mutex;
 use tokio::time::timeout;
-use tracing::{debug, info};
+use tracing::{Instrument, debug, info, instrument, warn};
 
 struct BuildDirGuard {
     path: PathBuf,
@@ -39,7 +46,10 @@ pub struct Builder {
     pub(crate) package_model: packages::ActiveModel,
     pub(crate) build_model: builds::ActiveModel,
     pub(crate) logger: BuildLogger,
+    pub(crate) activity_log: ActivityLog,
     pub(crate) docker: Docker,
+    pub(crate) resource_usage: Arc<std::sync::Mutex<ResourceUsage>>,
+    pub(crate) wall_time: Option<Duration>,
 }
 
 impl Builder {
@@ -61,12 +71,15 @@ impl Builder {
         };
 
         Ok(Builder {
+            activity_log: ActivityLog::new(db.clone()),
             db,
             job_containers,
             package_model: package_model.into_active_model(),
             build_model: build_model.into_active_model(),
             logger,
             docker,
+            resource_usage: Arc::default(),
+            wall_time: None,
         })
     }
 
@@ -121,9 +134,12 @@ impl Builder {
         let id2 = id.clone();
         let build_logger2 = self.logger.clone();
         // start listening to container before starting it
-        tokio::spawn(async move {
-            _ = Self::monitor_build_output(&build_logger2, &docker2, id2).await;
-        });
+        tokio::spawn(
+            async move {
+                _ = Self::monitor_build_output(&build_logger2, &docker2, id2).await;
+            }
+            .in_current_span(),
+        );
 
         // start build container
         info!(
@@ -133,12 +149,16 @@ impl Builder {
         self.docker
             .start_container(&id, None::<StartContainerOptions>)
             .await?;
+        let container_start = Instant::now();
+        let sampler =
+            sample_resource_usage(self.docker.clone(), id.clone(), self.resource_usage.clone());
 
         // insert container id to container map
-        self.job_containers
-            .lock()
-            .await
-            .insert(*self.build_model.id.get()?, id.clone());
+        {
+            let mut job_containers = self.job_containers.lock().await;
+            job_containers.insert(*self.build_model.id.get()?, id.clone());
+            ACTIVE_BUILDS.set(job_containers.len() as i64);
+        }
 
         // monitor build output
         debug!(
@@ -160,9 +180,18 @@ impl Builder {
         .value;
         let job_timeout = Duration::from_secs(job_timeout);
         debug!("job_timeout: {} sec", job_timeout.as_secs());
-        self.wait_container_exit(&id, job_timeout).await?;
+        let exit_result = self.wait_container_exit(&id, job_timeout).await;
+        self.wall_time = Some(container_start.elapsed());
+        // the stats stream ends with the container, give it a moment to deliver the last sample
+        let sampler_abort = sampler.abort_handle();
+        if timeout(Duration::from_secs(2), sampler).await.is_err() {
+            sampler_abort.abort();
+        }
+        exit_result?;
         info!("Build #{id}: docker container exited successfully");
 
+        self.check_lint_report(&host_active_build_path).await?;
+
         // move built tar.gz archives to host and repo-add
         info!(
             "Build {}: Move built packages to repo",
@@ -174,6 +203,7 @@ impl Builder {
         Ok(())
     }
 
+    #[instrument(name = "wait_container", skip_all)]
     async fn wait_container_exit(
         &self,
         container_id: &str,
@@ -235,12 +265,26 @@ impl Builder {
         }
     }
 
+    #[instrument(skip_all)]
     pub async fn post_build(&mut self, result: anyhow::Result<()>) -> anyhow::Result<()> {
         let txn = self.db.begin().await?;
         self.build_model.end_time = Set(Some(
             SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64,
         ));
 
+        let activity = BuildFinishActivity {
+            package: self.package_model.name.get()?.clone(),
+            build_id: *self.build_model.id.get()?,
+            version: self.build_model.version.get()?.clone(),
+            platform: self.build_model.platform.get()?.clone(),
+            success: result.is_ok(),
+            duration: (*self.build_model.start_time.get()?)
+                .zip(*self.build_model.end_time.get()?)
+                .map(|(start, end)| end - start),
+            error: result.as_ref().err().map(ToString::to_string),
+        };
+        self.set_resource_usage();
+
         match result {
             Ok(()) => {
                 // update package success status
@@ -273,15 +317,29 @@ impl Builder {
             }
         }
 
+        if let Some(duration) = activity.duration {
+            BUILD_DURATION
+                .with_label_values(&[
+                    activity.platform.as_str(),
+                    if activity.success {
+                        "successful"
+                    } else {
+                        "failed"
+                    },
+                ])
+                .observe(duration as f64);
+        }
+        self.log_activity(activity, ActivityType::FinishBuild).await;
+
         // remove build from container map
-        self.job_containers
-            .lock()
-            .await
-            .remove(self.build_model.id.get()?)
-            .ok_or(anyhow!("Failed to get job container"))?;
+        let mut job_containers = self.job_containers.lock().await;
+        let removed = job_containers.remove(self.build_model.id.get()?);
+        ACTIVE_BUILDS.set(job_containers.len() as i64);
+        removed.ok_or(anyhow!("Failed to get job container"))?;
         Ok(())
     }
 
+    #[instrument(skip_all)]
     pub async fn prepare_build(&mut self) -> anyhow::Result<String> {
         // set build status to building
         self.build_model.status = Set(Some(BuildStates::ACTIVE_BUILD));
@@ -294,7 +352,54 @@ impl Builder {
         self.package_model.status = Set(BuildStates::ACTIVE_BUILD);
         self.package_model = self.package_model.clone().save(&self.db).await?;
 
+        self.log_activity(
+            BuildStartActivity {
+                package: self.package_model.name.get()?.clone(),
+                build_id: *self.build_model.id.get()?,
+                version: self.build_model.version.get()?.clone(),
+                platform: self.build_model.platform.get()?.clone(),
+            },
+            ActivityType::StartBuild,
+        )
+        .await;
+
         let target_platform = format!("linux/{}", self.build_model.platform.get()?);
         Ok(target_platform)
     }
+
+    /// Copy the sampled container resource usage into the build model
+    fn set_resource_usage(&mut self) {
+        let usage = self
+            .resource_usage
+            .lock()
+            .map(|u| u.clone())
+            .unwrap_or_default();
+        let to_i64 = |v: Option<u64>| v.and_then(|v| i64::try_from(v).ok());
+
+        self.build_model.peak_memory = Set(to_i64(usage.peak_memory));
+        self.build_model.cpu_time = Set(to_i64(usage.cpu_time.map(|ns| ns / 1_000_000)));
+        self.build_model.wall_time = Set(self
+            .wall_time
+            .and_then(|d| i64::try_from(d.as_millis()).ok()));
+        self.build_model.net_rx = Set(to_i64(usage.net_rx));
+        self.build_model.net_tx = Set(to_i64(usage.net_tx));
+        self.build_model.blk_read = Set(to_i64(usage.blk_read));
+        self.build_model.blk_write = Set(to_i64(usage.blk_write));
+    }
+
+    /// Record a build activity, a failing activity log must never fail the build itself
+    async fn log_activity<T: serde::Serialize + ActivitySerializer>(
+        &self,
+        activity: T,
+        activity_type: ActivityType,
+    ) {
+        let pkg_id = self.package_model.id.get().ok().copied();
+        if let Err(e) = self
+            .activity_log
+            .add(activity, activity_type, ActivityMeta::server(pkg_id))
+            .await
+        {
+            warn!("Failed to record build activity: {e}");
+        }
+    }
 }
@@ -84,6 +84,16 @@ pub fn get_build_mode() -> BuildMode {
     }
 }
 
+/// x86_64 mirrorlist as seen from inside the aurcache container
+#[must_use]
+pub fn mirrorlist_file() -> String {
+    let mirrorlist_path = match get_build_mode() {
+        BuildMode::DinD(cfg) => cfg.mirrorlist_path,
+        BuildMode::Host(cfg) => cfg.mirrorlist_path_aurcache,
+    };
+    format!("{mirrorlist_path}/mirrorlist")
+}
+
 /// create config dir if not existing
 fn create_config_dir(config_dir: String) {
     if fs::metadata(config_dir.as_str()).is_err() {
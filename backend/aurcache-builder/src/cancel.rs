@@ -1,6 +1,8 @@
 use anyhow::anyhow;
 use aurcache_db::builds;
 use aurcache_db::prelude::Builds;
+use aurcache_metrics::ACTIVE_BUILDS;
+use aurcache_types::builder::BuildStates;
 use bollard::Docker;
 use bollard::query_parameters::RemoveContainerOptions;
 use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
@@ -20,7 +22,7 @@ pub(crate) async fn cancel_build(
         .ok_or(anyhow!("No build found"))?;
 
     let mut build: builds::ActiveModel = build.into();
-    build.status = Set(Some(4));
+    build.status = Set(Some(BuildStates::CANCELED_BUILD));
     build.end_time = Set(Some(
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -47,12 +49,10 @@ pub(crate) async fn cancel_build(
         )
         .await?;
 
-    job_containers
-        .lock()
-        .await
-        .remove(&build_id)
-        .ok_or(anyhow!(
-            "Failed to remove build container from active build map"
-        ))?;
+    let mut job_containers = job_containers.lock().await;
+    job_containers.remove(&build_id).ok_or(anyhow!(
+        "Failed to remove build container from active build map"
+    ))?;
+    ACTIVE_BUILDS.set(job_containers.len() as i64);
     Ok(())
 }
@@ -2,6 +2,7 @@ use crate::build::Builder;
 use crate::build_mode::{BuildMode, get_build_mode};
 use crate::logger::BuildLogger;
 use crate::makepkg_utils::{create_makepkg_config, read_pacman_config};
+use crate::namcap::namcap_cmd;
 use anyhow::anyhow;
 use aurcache_db::helpers::active_value_ext::ActiveValueExt;
 use aurcache_db::packages::SourceData;
@@ -28,7 +29,7 @@ use std::str::FromStr;
 use tempfile::tempdir;
 use tokio::fs::File;
 use tokio_util::io::ReaderStream;
-use tracing::{debug, info, trace};
+use tracing::{debug, info, instrument, trace};
 
 /// git repo path inside builder container in git build mode
 static GIT_REPO_PATH: &str = "/tmp";
@@ -48,6 +49,7 @@ and check also if the 'DOCKER_HOST=unix:///var/run/user/1000/podman/podman.sock'
 
     /// repull docker image with specified arch
     /// returns image id hash
+    #[instrument(name = "pull_image", skip(self))]
     pub async fn repull_image(&self, image: &str, arch: String) -> anyhow::Result<()> {
         self.logger.append(format!("Pulling image: {image}")).await;
         // repull image to make sure it's up to date
@@ -130,6 +132,7 @@ and check also if the 'DOCKER_HOST=unix:///var/run/user/1000/podman/podman.sock'
         Ok(())
     }
 
+    #[instrument(skip(self))]
     pub async fn create_build_container(
         &self,
         arch: String,
@@ -232,13 +235,18 @@ and check also if the 'DOCKER_HOST=unix:///var/run/user/1000/podman/podman.sock'
             SourceData::Aur { .. } => {
                 // -Ga forces paru to clone from AUR even when a same-named package exists in a repo
                 format!(
-                    "mkdir -p {container_build_dir} && cd {container_build_dir} && {self_update} && paru -Ga {name} && paru {build_flags} *",
+                    "mkdir -p {container_build_dir} && cd {container_build_dir} && {self_update} && paru -Ga {name} && paru {build_flags} * && {lint}",
                     container_build_dir = container_build_dir.display(),
+                    lint = namcap_cmd(
+                        &format!("{}/*/PKGBUILD", container_build_dir.display()),
+                        container_pkgdest_dir
+                    ),
                 )
             }
             SourceData::Git { .. } => {
                 format!(
-                    "sudo chmod -R 1777 {GIT_REPO_PATH} && {self_update} && cd {GIT_REPO_PATH} && paru {build_flags} ."
+                    "sudo chmod -R 1777 {GIT_REPO_PATH} && {self_update} && cd {GIT_REPO_PATH} && paru {build_flags} . && {lint}",
+                    lint = namcap_cmd(&format!("{GIT_REPO_PATH}/PKGBUILD"), container_pkgdest_dir),
                 )
             }
             SourceData::Upload { .. } => {
@@ -383,6 +391,7 @@ and check also if the 'DOCKER_HOST=unix:///var/run/user/1000/podman/podman.sock'
         Ok(())
     }
 
+    #[instrument(name = "attach_logs", skip_all)]
     pub async fn monitor_build_output(
         build_logger: &BuildLogger,
         docker: &Docker,
@@ -45,6 +45,7 @@ pub fn init_build_queue(db: DatabaseConnection, tx: Sender<Action>) -> JoinHandl
                     Action::Cancel(build_id) => {
                         let _ = cancel_build(build_id, job_containers.clone(), db.clone()).await;
                     }
+                    Action::RankMirrors => {}
                 }
             }
         }
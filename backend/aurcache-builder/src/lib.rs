@@ -6,6 +6,8 @@ pub mod init;
 mod logger;
 mod makepkg_utils;
 mod move_location;
+mod namcap;
 mod path_utils;
 mod queue;
+mod resource_usage;
 pub mod utils;
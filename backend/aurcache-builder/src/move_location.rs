@@ -4,8 +4,12 @@ use anyhow::{anyhow, bail};
 use aurcache_db::helpers::active_value_ext::ActiveValueExt;
 use aurcache_db::prelude::{Files, PackagesFiles};
 use aurcache_db::{files, packages_files};
-use aurcache_utils::utils::remove_archive_file::try_remove_archive_file;
+use aurcache_utils::repo::lock::lock_repo;
+use aurcache_utils::utils::remove_archive_file::{release_archive_file, remove_archive_from_disk};
+use pacman_repo_utils::repo_add::calc_checksums;
+use pacman_repo_utils::repo_validate::validate_package;
 use sea_orm::ColumnTrait;
+use sea_orm::ConnectionTrait;
 use sea_orm::ModelTrait;
 use sea_orm::PaginatorTrait;
 use sea_orm::QueryFilter;
@@ -14,6 +18,7 @@ use sea_orm::{ActiveModelTrait, EntityTrait, JoinType, RelationTrait, Set, Trans
 use std::fs;
 use std::fs::DirEntry;
 use std::path::PathBuf;
+use tracing::instrument;
 
 // todo this pkg file structure might be migrated to the sql database in the future
 //  if it is used more often than here once
@@ -30,6 +35,7 @@ struct ParsedPkg {
 
 impl Builder {
     /// move built files from build container to host and add them to the repo
+    #[instrument(skip_all)]
     pub(crate) async fn move_and_add_pkgs(&self, host_build_path: PathBuf) -> anyhow::Result<()> {
         let archive_paths = fs::read_dir(host_build_path.clone())?.collect::<Vec<_>>();
         if archive_paths.is_empty() {
@@ -37,10 +43,21 @@ impl Builder {
         }
 
         let build_pkgs = build_output_map(archive_paths)?;
+        // nothing is published unless every package of the build is valid
+        self.validate_build_output(&build_pkgs).await?;
+
+        // held from copying the archives until their `files` rows are committed,
+        // a concurrent repo repair would delete them as orphans otherwise
+        let platform = self.build_model.platform.get()?.clone();
+        let lock = lock_repo(&platform).await?;
+
         let txn = self.db.begin().await?;
 
         // ADD NEW FILES FIRST
         let mut new_file_ids = std::collections::HashMap::new();
+        let mut new_pkg_paths = vec![];
+        // archives no package uses anymore, removed from the repo together with the additions
+        let mut removed_files = vec![];
 
         for (archive_path, parsed) in &build_pkgs {
             let archive_name = archive_path.file_name().to_str().unwrap().to_string();
@@ -85,21 +102,7 @@ impl Builder {
 
             let new_file_id = file.id.clone().unwrap();
             new_file_ids.insert(parsed.name.clone(), new_file_id);
-
-            self.logger
-                .append(format!(
-                    "Add {} to repo.db.tar.gz and repo.files.tar.gz\n",
-                    parsed.filename
-                ))
-                .await;
-            pacman_repo_utils::repo_add::repo_add(
-                &pkg_path,
-                format!("./repo/{}/repo.db.tar.gz", self.build_model.platform.get()?),
-                format!(
-                    "./repo/{}/repo.files.tar.gz",
-                    self.build_model.platform.get()?
-                ),
-            )?;
+            new_pkg_paths.push(pkg_path);
 
             // handle other package depending on an older version of this new package
             let older_versions = Files::find()
@@ -142,7 +145,7 @@ impl Builder {
                                 old_v.filename
                             ))
                             .await;
-                        try_remove_archive_file(old_v, &txn).await?;
+                        removed_files.extend(release_archive_file(old_v, &txn).await?);
                     }
                 }
             }
@@ -173,7 +176,7 @@ impl Builder {
                         .append(format!("Removing dropped sub-package: {}\n", file.filename))
                         .await;
                     pkg_file.delete(&txn).await?;
-                    try_remove_archive_file(file, &txn).await?;
+                    removed_files.extend(release_archive_file(file, &txn).await?);
                 } else {
                     self.logger
                         .append(format!(
@@ -186,12 +189,87 @@ impl Builder {
             }
         }
 
+        // write all new and removed packages to the repo archives at once
+        // the archive rewrite blocks, keep it off the async workers
+        let (db_archive, files_archive) = (
+            format!("./repo/{platform}/repo.db"),
+            format!("./repo/{platform}/repo.files"),
+        );
+        let removed = removed_files.clone();
+        let lock = tokio::task::spawn_blocking(move || {
+            pacman_repo_utils::repo_add::repo_update_locked(
+                &lock,
+                &new_pkg_paths,
+                &removed,
+                db_archive,
+                files_archive,
+            )
+            .map(|()| lock)
+        })
+        .await??;
+        for (_, parsed) in &build_pkgs {
+            self.logger
+                .append(format!(
+                    "Added {} to repo.db and repo.files\n",
+                    parsed.filename
+                ))
+                .await;
+        }
+
         txn.commit().await?;
+        for filename in &removed_files {
+            remove_archive_from_disk(&platform, filename);
+        }
+        drop(lock);
         self.logger
             .append("Successfully updated repo and cleaned up old files\n".to_string())
             .await;
         Ok(())
     }
+
+    /// Reject corrupt, mislabeled or conflicting packages before they reach the repo
+    async fn validate_build_output(
+        &self,
+        build_pkgs: &[(DirEntry, ParsedPkg)],
+    ) -> anyhow::Result<()> {
+        let platform = self.build_model.platform.get()?.clone();
+        let package_id = *self.package_model.id.get()?;
+
+        for (archive_path, parsed) in build_pkgs {
+            self.logger
+                .append(format!("Validate {}\n", parsed.filename))
+                .await;
+            let validated = validate_package(&archive_path.path(), &platform)
+                .map_err(|e| anyhow!("Package validation failed: {e}"))?;
+
+            let Some(existing) = Files::find()
+                .filter(files::Column::Filename.eq(validated.filename.clone()))
+                .filter(files::Column::Platform.eq(platform.clone()))
+                .one(&self.db)
+                .await?
+            else {
+                continue;
+            };
+
+            // a file shared with other packages may only be replaced by identical content
+            let repo_path = format!("./repo/{platform}/{}", validated.filename);
+            if dependent_packages(&self.db, existing.id, package_id)
+                .await?
+                .is_empty()
+                || !fs::exists(&repo_path)?
+            {
+                continue;
+            }
+            let (_, sha256sum) = calc_checksums(&repo_path)?;
+            if sha256sum != validated.sha256sum {
+                bail!(
+                    "Package validation failed: {} would overwrite a different package file owned by another package",
+                    validated.filename
+                );
+            }
+        }
+        Ok(())
+    }
 }
 
 fn parse_arch_pkg(filename: &str) -> anyhow::Result<ParsedPkg> {
@@ -246,7 +324,7 @@ fn build_output_map(
 }
 
 async fn dependent_packages(
-    txn: &sea_orm::DatabaseTransaction,
+    txn: &impl ConnectionTrait,
     file_id: i32,
     current_pkg_id: i32,
 ) -> anyhow::Result<Vec<packages_files::Model>> {
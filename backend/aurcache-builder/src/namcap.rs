@@ -0,0 +1,152 @@
+use crate::build::Builder;
+use anyhow::bail;
+use aurcache_db::helpers::active_value_ext::ActiveValueExt;
+use aurcache_types::builder::{LintFinding, LintSeverity};
+use aurcache_types::settings::{ApplicationSettings, Setting};
+use aurcache_utils::settings::general::SettingsTraits;
+use sea_orm::Set;
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+/// namcap output inside the build dir, a dotfile so it is never published as a package
+pub(crate) const NAMCAP_LOG: &str = ".aurcache_namcap.log";
+
+/// Shell snippet linting the PKGBUILD and all built packages into [`NAMCAP_LOG`].
+///
+/// Builder images without namcap just skip the check.
+pub(crate) fn namcap_cmd(pkgbuild: &str, pkgdest: &Path) -> String {
+    format!(
+        "if command -v namcap >/dev/null; then namcap {pkgbuild} {pkgdest}/*.pkg.tar* > {pkgdest}/{NAMCAP_LOG} 2>&1 || true; fi",
+        pkgdest = pkgdest.display()
+    )
+}
+
+/// Parse namcap lines like `foo E: Dependency bar detected and not included`
+fn parse_namcap(output: &str) -> Vec<LintFinding> {
+    output
+        .lines()
+        .filter_map(|line| {
+            [
+                (" E: ", LintSeverity::Error),
+                (" W: ", LintSeverity::Warning),
+                (" I: ", LintSeverity::Info),
+            ]
+            .into_iter()
+            .filter_map(|(tag, severity)| line.find(tag).map(|pos| (pos, tag, severity)))
+            .min_by_key(|(pos, _, _)| *pos)
+            .map(|(pos, tag, severity)| LintFinding {
+                target: line[..pos].trim().to_string(),
+                severity,
+                message: line[pos + tag.len()..].trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+impl Builder {
+    /// Store the namcap findings with the build and fail it on errors if configured
+    pub(crate) async fn check_lint_report(&mut self, host_build_path: &Path) -> anyhow::Result<()> {
+        let Ok(output) = fs::read_to_string(host_build_path.join(NAMCAP_LOG)) else {
+            debug!("No namcap report for build #{}", self.build_model.id.get()?);
+            return Ok(());
+        };
+
+        let findings = parse_namcap(&output);
+        let count = |severity| findings.iter().filter(|f| f.severity == severity).count();
+        let (errors, warnings) = (count(LintSeverity::Error), count(LintSeverity::Warning));
+        self.logger
+            .append(format!("namcap: {errors} errors, {warnings} warnings\n"))
+            .await;
+        self.build_model.lint = Set(Some(serde_json::to_string(&findings)?));
+
+        let fail_on_error: bool = ApplicationSettings::get(
+            Setting::LintFailOnError,
+            Some(*self.package_model.id.get()?),
+            &self.db,
+        )
+        .await
+        .value;
+        if fail_on_error && errors > 0 {
+            bail!("namcap reported {errors} errors");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(target: &str, severity: LintSeverity, message: &str) -> LintFinding {
+        LintFinding {
+            target: target.to_string(),
+            severity,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn pkgbuild_and_package_findings() {
+        let output = "\
+PKGBUILD (paru) W: Reference to x86_64 should be changed to $CARCH
+PKGBUILD (paru) E: Missing custom license directory (usr/share/licenses/paru)
+paru E: Dependency pacman detected and not included (libraries ['usr/lib/libalpm.so.15'] needed in files ['usr/bin/paru'])
+paru W: Dependency included, but may not be needed ('git')
+paru I: Link-level dependence (glibc) in file ['usr/bin/paru']
+";
+        assert_eq!(
+            parse_namcap(output),
+            [
+                finding(
+                    "PKGBUILD (paru)",
+                    LintSeverity::Warning,
+                    "Reference to x86_64 should be changed to $CARCH"
+                ),
+                finding(
+                    "PKGBUILD (paru)",
+                    LintSeverity::Error,
+                    "Missing custom license directory (usr/share/licenses/paru)"
+                ),
+                finding(
+                    "paru",
+                    LintSeverity::Error,
+                    "Dependency pacman detected and not included (libraries ['usr/lib/libalpm.so.15'] needed in files ['usr/bin/paru'])"
+                ),
+                finding(
+                    "paru",
+                    LintSeverity::Warning,
+                    "Dependency included, but may not be needed ('git')"
+                ),
+                finding(
+                    "paru",
+                    LintSeverity::Info,
+                    "Link-level dependence (glibc) in file ['usr/bin/paru']"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn first_tag_wins() {
+        assert_eq!(
+            parse_namcap("foo-debug W: Message mentions foo I: and foo E: tags\n"),
+            [finding(
+                "foo-debug",
+                LintSeverity::Warning,
+                "Message mentions foo I: and foo E: tags"
+            )]
+        );
+    }
+
+    #[test]
+    fn other_lines_are_skipped() {
+        let output = "\
+sh: line 1: namcap: command not found
+
+Traceback (most recent call last):
+paru W:missing space after the tag
+";
+        assert!(parse_namcap(output).is_empty());
+    }
+}
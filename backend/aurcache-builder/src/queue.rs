@@ -1,11 +1,12 @@
 use crate::build::Builder;
 use aurcache_db::helpers::active_value_ext::ActiveValueExt;
 use aurcache_db::{builds, packages};
+use aurcache_metrics::QUEUE_DEPTH;
 use sea_orm::DatabaseConnection;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{Mutex, Semaphore};
-use tracing::error;
+use tracing::{error, instrument};
 
 /// Queue a package for building
 pub(crate) async fn queue_package(
@@ -17,14 +18,26 @@ pub(crate) async fn queue_package(
 ) -> anyhow::Result<()> {
     let permits = Arc::clone(&semaphore);
 
+    QUEUE_DEPTH.inc();
     // spawn new thread for each pkg build
     tokio::spawn(async move {
         let _permit = permits.acquire().await.unwrap();
+        QUEUE_DEPTH.dec();
         start_build(*build_model, &db, *package_model, job_containers).await;
     });
     Ok(())
 }
 
+#[instrument(
+    name = "build",
+    skip_all,
+    fields(
+        build_id = build_model.id,
+        package = %package_model.name,
+        platform = %build_model.platform,
+        version = %build_model.version,
+    )
+)]
 async fn start_build(
     build_model: builds::Model,
     db: &DatabaseConnection,
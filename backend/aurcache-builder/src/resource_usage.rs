@@ -0,0 +1,167 @@
+use bollard::Docker;
+use bollard::models::ContainerStatsResponse;
+use bollard::query_parameters::StatsOptions;
+use futures::StreamExt;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{Instrument, debug};
+
+/// Resources consumed by a build container, aggregated over all docker stats samples
+#[derive(Debug, Clone, Default)]
+pub struct ResourceUsage {
+    /// bytes
+    pub peak_memory: Option<u64>,
+    /// nanoseconds
+    pub cpu_time: Option<u64>,
+    pub net_rx: Option<u64>,
+    pub net_tx: Option<u64>,
+    pub blk_read: Option<u64>,
+    pub blk_write: Option<u64>,
+}
+
+impl ResourceUsage {
+    /// Merge a stats sample. All counters reported by docker are cumulative,
+    /// so the latest sample wins, except for memory where the peak is kept.
+    fn update(&mut self, stats: &ContainerStatsResponse) {
+        if let Some(memory) = &stats.memory_stats {
+            // max_usage is only reported on cgroup v1
+            if let Some(usage) = memory.max_usage.or(memory.usage) {
+                self.peak_memory = Some(self.peak_memory.unwrap_or(0).max(usage));
+            }
+        }
+
+        if let Some(total) = stats
+            .cpu_stats
+            .as_ref()
+            .and_then(|cpu| cpu.cpu_usage.as_ref())
+            .and_then(|usage| usage.total_usage)
+        {
+            self.cpu_time = Some(total);
+        }
+
+        if let Some(networks) = &stats.networks {
+            self.net_rx = Some(networks.values().filter_map(|n| n.rx_bytes).sum());
+            self.net_tx = Some(networks.values().filter_map(|n| n.tx_bytes).sum());
+        }
+
+        if let Some(entries) = stats
+            .blkio_stats
+            .as_ref()
+            .and_then(|blkio| blkio.io_service_bytes_recursive.as_ref())
+        {
+            let sum_op = |op: &str| -> u64 {
+                entries
+                    .iter()
+                    .filter(|e| e.op.as_deref().is_some_and(|o| o.eq_ignore_ascii_case(op)))
+                    .filter_map(|e| e.value)
+                    .sum()
+            };
+            self.blk_read = Some(sum_op("read"));
+            self.blk_write = Some(sum_op("write"));
+        }
+    }
+}
+
+/// Sample docker stats of a running container until its stats stream ends.
+/// The aggregated usage is continuously written into `usage`.
+pub(crate) fn sample_resource_usage(
+    docker: Docker,
+    container_id: String,
+    usage: Arc<Mutex<ResourceUsage>>,
+) -> JoinHandle<()> {
+    tokio::spawn(
+        async move {
+            let mut stream = docker.stats(
+                &container_id,
+                Some(StatsOptions {
+                    stream: true,
+                    one_shot: false,
+                }),
+            );
+            while let Some(sample) = stream.next().await {
+                match sample {
+                    Ok(stats) => {
+                        if let Ok(mut usage) = usage.lock() {
+                            usage.update(&stats);
+                        }
+                    }
+                    Err(e) => {
+                        debug!("Failed to read container stats: {e}");
+                        break;
+                    }
+                }
+            }
+        }
+        .in_current_span(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample(
+        memory: u64,
+        cpu: u64,
+        (rx, tx): (u64, u64),
+        (read, write): (u64, u64),
+    ) -> ContainerStatsResponse {
+        serde_json::from_value(json!({
+            "memory_stats": { "usage": memory, "limit": 8_589_934_592_u64 },
+            "cpu_stats": { "cpu_usage": { "total_usage": cpu } },
+            "networks": {
+                "eth0": { "rx_bytes": rx, "tx_bytes": tx },
+                "eth1": { "rx_bytes": 100, "tx_bytes": 10 },
+            },
+            "blkio_stats": {
+                "io_service_bytes_recursive": [
+                    { "major": 8, "minor": 0, "op": "read", "value": read },
+                    { "major": 8, "minor": 0, "op": "write", "value": write },
+                    { "major": 8, "minor": 16, "op": "Read", "value": 1 },
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn keeps_peak_memory_and_latest_counters() {
+        let mut usage = ResourceUsage::default();
+        usage.update(&sample(
+            512 * 1024 * 1024,
+            2_000_000_000,
+            (1_000, 200),
+            (4_096, 8_192),
+        ));
+        usage.update(&sample(
+            256 * 1024 * 1024,
+            5_000_000_000,
+            (3_000, 500),
+            (8_192, 16_384),
+        ));
+
+        assert_eq!(usage.peak_memory, Some(512 * 1024 * 1024));
+        assert_eq!(usage.cpu_time, Some(5_000_000_000));
+        assert_eq!(usage.net_rx, Some(3_100));
+        assert_eq!(usage.net_tx, Some(510));
+        assert_eq!(usage.blk_read, Some(8_193));
+        assert_eq!(usage.blk_write, Some(16_384));
+    }
+
+    #[test]
+    fn cgroup_v1_max_usage() {
+        let mut usage = ResourceUsage::default();
+        usage.update(
+            &serde_json::from_value(json!({
+                "memory_stats": { "usage": 1_000, "max_usage": 4_000 }
+            }))
+            .unwrap(),
+        );
+
+        assert_eq!(usage.peak_memory, Some(4_000));
+        assert_eq!(usage.cpu_time, None);
+        assert_eq!(usage.net_rx, None);
+        assert_eq!(usage.blk_read, None);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::client::ApiClient;
+use crate::output::{build_status, format_time, print_json, print_table};
+use crate::package::resolve_package;
+use aurcache_api::models::builds::{BuildDetailsModel, ListBuildsModel};
+use aurcache_types::builder::BuildStates;
+use clap::Subcommand;
+use reqwest::Method;
+use std::time::Duration;
+
+/// Interval between two polls of a running build
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Subcommand)]
+pub enum BuildCommand {
+    /// List builds, newest first
+    List {
+        /// Only builds of this package (name or id)
+        #[arg(long)]
+        package: Option<String>,
+        #[arg(long)]
+        limit: Option<u64>,
+        #[arg(long)]
+        page: Option<u64>,
+    },
+
+    /// Show details and lint findings of a build
+    Get { id: i32 },
+
+    /// Print the build log
+    Logs {
+        id: i32,
+        /// Keep printing new output until the build finished
+        #[arg(short, long)]
+        follow: bool,
+    },
+
+    /// Cancel an enqueued or running build
+    Cancel { id: i32 },
+
+    /// Build the same version again
+    Retry { id: i32 },
+}
+
+pub async fn run(client: &ApiClient, cmd: BuildCommand, json: bool) -> anyhow::Result<()> {
+    match cmd {
+        BuildCommand::List {
+            package,
+            limit,
+            page,
+        } => {
+            let mut query = vec![];
+            if let Some(package) = package {
+                query.push(format!(
+                    "pkgid={}",
+                    resolve_package(client, &package).await?
+                ));
+            }
+            if let Some(limit) = limit {
+                query.push(format!("limit={limit}"));
+            }
+            if let Some(page) = page {
+                query.push(format!("page={page}"));
+            }
+            let builds: Vec<ListBuildsModel> =
+                client.get(&format!("builds?{}", query.join("&"))).await?;
+            if json {
+                return print_json(&builds);
+            }
+            print_table(
+                [
+                    "ID", "PACKAGE", "VERSION", "PLATFORM", "STATUS", "STARTED", "ENDED",
+                ],
+                builds
+                    .into_iter()
+                    .map(|b| {
+                        [
+                            b.id.to_string(),
+                            b.pkg_name,
+                            b.version,
+                            b.platform,
+                            build_status(b.status).to_string(),
+                            format_time(b.start_time),
+                            format_time(b.end_time),
+                        ]
+                    })
+                    .collect(),
+            );
+        }
+        BuildCommand::Get { id } => {
+            let details: BuildDetailsModel = client.get(&format!("build/{id}")).await?;
+            if json {
+                return print_json(&details);
+            }
+            let build = &details.build;
+            println!("Id:       {}", build.id);
+            println!("Package:  {} (#{})", build.pkg_name, build.pkg_id);
+            println!("Version:  {}", build.version);
+            println!("Platform: {}", build.platform);
+            println!("Status:   {}", build_status(build.status));
+            println!("Started:  {}", format_time(build.start_time));
+            println!("Ended:    {}", format_time(build.end_time));
+            match &details.lint {
+                Some(findings) if !findings.is_empty() => {
+                    println!("Lint:");
+                    for finding in findings {
+                        println!(
+                            "  {:?} {}: {}",
+                            finding.severity, finding.target, finding.message
+                        );
+                    }
+                }
+                Some(_) => println!("Lint:     no findings"),
+                None => {}
+            }
+        }
+        BuildCommand::Logs { id, follow } => logs(client, id, follow).await?,
+        BuildCommand::Cancel { id } => {
+            client
+                .execute(Method::POST, &format!("build/{id}/cancel"))
+                .await?;
+            if !json {
+                println!("Canceled build #{id}");
+            }
+        }
+        BuildCommand::Retry { id } => {
+            let build: i32 = client
+                .post::<(), _>(&format!("build/{id}/retry"), None)
+                .await?;
+            if json {
+                return print_json(&build);
+            }
+            println!("Enqueued build #{build}");
+        }
+    }
+    Ok(())
+}
+
+/// Print the build output, polling for new lines while the build is running if `follow` is set
+async fn logs(client: &ApiClient, id: i32, follow: bool) -> anyhow::Result<()> {
+    let mut printed = 0;
+    loop {
+        // check the state first, so no output written after the last poll is missed
+        let running = follow && {
+            let details: BuildDetailsModel = client.get(&format!("build/{id}")).await?;
+            matches!(
+                details.build.status,
+                BuildStates::ACTIVE_BUILD | BuildStates::ENQUEUED_BUILD
+            )
+        };
+
+        match client
+            .get_text(&format!("build/{id}/output?startline={printed}"))
+            .await
+        {
+            Ok(output) => {
+                let output = output.trim_end_matches('\n');
+                if !output.is_empty() {
+                    println!("{output}");
+                    printed += output.lines().count();
+                }
+            }
+            // no output yet while the build is enqueued
+            Err(_) if running => {}
+            Err(e) => return Err(e),
+        }
+
+        if !running {
+            return Ok(());
+        }
+        tokio::time::sleep(FOLLOW_INTERVAL).await;
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::bail;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// Thin wrapper around the `/api` endpoints of an AURCache instance
+pub struct ApiClient {
+    client: Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl ApiClient {
+    pub fn new(url: &str, token: Option<String>) -> anyhow::Result<Self> {
+        Ok(ApiClient {
+            client: Client::builder()
+                .user_agent(concat!("aurcache-cli/", env!("CARGO_PKG_VERSION")))
+                .timeout(Duration::from_mins(5))
+                .build()?,
+            base_url: format!("{}/api", url.trim_end_matches('/')),
+            token: token.filter(|t| !t.is_empty()),
+        })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let req = self
+            .client
+            .request(method, format!("{}/{path}", self.base_url));
+        match &self.token {
+            Some(token) => req.bearer_auth(token),
+            None => req,
+        }
+    }
+
+    /// Fail with the status and the error message of the api
+    async fn send(req: RequestBuilder) -> anyhow::Result<Response> {
+        let res = req.send().await?;
+        let status = res.status();
+        if status.is_success() {
+            return Ok(res);
+        }
+
+        let body = res.text().await.unwrap_or_default();
+        match status.as_u16() {
+            401 => bail!("{status}: missing or invalid api token"),
+            _ if body.trim().is_empty() => bail!("{status}"),
+            _ => bail!("{status}: {}", body.trim()),
+        }
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> anyhow::Result<T> {
+        Ok(Self::send(self.request(Method::GET, path))
+            .await?
+            .json()
+            .await?)
+    }
+
+    pub async fn get_text(&self, path: &str) -> anyhow::Result<String> {
+        Ok(Self::send(self.request(Method::GET, path))
+            .await?
+            .text()
+            .await?)
+    }
+
+    /// Request with a JSON body, the response body is ignored
+    pub async fn send_json<B: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &B,
+    ) -> anyhow::Result<()> {
+        Self::send(self.request(method, path).json(body)).await?;
+        Ok(())
+    }
+
+    pub async fn post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: Option<&B>,
+    ) -> anyhow::Result<T> {
+        let req = self.request(Method::POST, path);
+        let req = match body {
+            Some(body) => req.json(body),
+            None => req,
+        };
+        Ok(Self::send(req).await?.json().await?)
+    }
+
+    /// POST a raw body with the given content type
+    pub async fn post_raw<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: String,
+        content_type: &str,
+    ) -> anyhow::Result<T> {
+        let req = self
+            .request(Method::POST, path)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(body);
+        Ok(Self::send(req).await?.json().await?)
+    }
+
+    /// Request without body whose response body is ignored
+    pub async fn execute(&self, method: Method, path: &str) -> anyhow::Result<()> {
+        Self::send(self.request(method, path)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use crate::client::ApiClient;
+use clap::{Parser, Subcommand};
+use std::process::ExitCode;
+
+mod build;
+mod client;
+mod manifest;
+mod output;
+mod package;
+mod settings;
+mod stats;
+
+/// Command line client for the AURCache REST API
+#[derive(Parser)]
+#[command(name = "aurcache-cli", version)]
+struct Cli {
+    /// Url of the AURCache frontend/API, without `/api`
+    #[arg(
+        long,
+        env = "AURCACHE_URL",
+        default_value = "http://localhost:8080",
+        global = true
+    )]
+    url: String,
+
+    /// One of the tokens configured in `API_TOKENS`
+    #[arg(long, env = "AURCACHE_TOKEN", hide_env_values = true, global = true)]
+    token: Option<String>,
+
+    /// Print the API responses as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Add, remove, list and update packages
+    #[command(subcommand, alias = "packages")]
+    Package(package::PackageCommand),
+
+    /// List builds, tail their logs, cancel or retry them
+    #[command(subcommand, alias = "builds")]
+    Build(build::BuildCommand),
+
+    /// Get and set global or per-package settings
+    #[command(subcommand)]
+    Settings(settings::SettingsCommand),
+
+    /// Apply or export a declarative package and settings manifest
+    #[command(subcommand)]
+    Manifest(manifest::ManifestCommand),
+
+    /// Show build-server statistics
+    Stats,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match ApiClient::new(&cli.url, cli.token) {
+        Ok(client) => match cli.command {
+            Command::Package(cmd) => package::run(&client, cmd, cli.json).await,
+            Command::Build(cmd) => build::run(&client, cmd, cli.json).await,
+            Command::Settings(cmd) => settings::run(&client, cmd, cli.json).await,
+            Command::Manifest(cmd) => manifest::run(&client, cmd, cli.json).await,
+            Command::Stats => stats::run(&client, cli.json).await,
+        },
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::client::ApiClient;
+use crate::output::{print_json, print_table};
+use anyhow::{anyhow, bail};
+use aurcache_api::models::manifest::ManifestApplyModel;
+use clap::{Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+
+#[derive(Subcommand)]
+pub enum ManifestCommand {
+    /// Add and update packages and settings until they match the manifest
+    Apply {
+        /// TOML or YAML file, `.yaml` and `.yml` files are sent as YAML
+        file: PathBuf,
+        /// Only show the changes
+        #[arg(long)]
+        dry_run: bool,
+        /// Also remove packages and reset settings missing in the manifest
+        #[arg(long)]
+        prune: bool,
+    },
+
+    /// Print the current packages and settings as manifest
+    Export {
+        #[arg(long, value_enum, default_value_t = Format::Toml)]
+        format: Format,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    fn of(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml" | "yml") => Format::Yaml,
+            _ => Format::Toml,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Format::Toml => "toml",
+            Format::Yaml => "yaml",
+        }
+    }
+}
+
+pub async fn run(client: &ApiClient, cmd: ManifestCommand, json: bool) -> anyhow::Result<()> {
+    match cmd {
+        ManifestCommand::Apply {
+            file,
+            dry_run,
+            prune,
+        } => {
+            let manifest = tokio::fs::read_to_string(&file)
+                .await
+                .map_err(|e| anyhow!("failed to read {}: {e}", file.display()))?;
+            let format = Format::of(&file).as_str();
+            let result: ManifestApplyModel = client
+                .post_raw(
+                    &format!("manifest/apply?dry_run={dry_run}&prune={prune}&format={format}"),
+                    manifest,
+                    &format!("application/{format}"),
+                )
+                .await?;
+            if json {
+                print_json(&result)?;
+            } else if result.changes.is_empty() {
+                println!("Nothing to do");
+            } else {
+                print_table(
+                    ["ACTION", "PACKAGE", "SETTING", "RESULT"],
+                    result
+                        .changes
+                        .iter()
+                        .map(|c| {
+                            [
+                                c.action.clone(),
+                                c.package.clone().unwrap_or_else(|| "-".to_string()),
+                                c.key.clone().unwrap_or_default(),
+                                match (&c.error, result.dry_run) {
+                                    (Some(error), _) => format!("failed: {error}"),
+                                    (None, true) => "planned".to_string(),
+                                    (None, false) => "done".to_string(),
+                                },
+                            ]
+                        })
+                        .collect(),
+                );
+            }
+            if result.failed > 0 {
+                bail!(
+                    "{} of {} changes failed",
+                    result.failed,
+                    result.changes.len()
+                );
+            }
+        }
+        ManifestCommand::Export { format, output } => {
+            let manifest = client
+                .get_text(&format!("manifest/export?format={}", format.as_str()))
+                .await?;
+            match output {
+                Some(path) => tokio::fs::write(&path, manifest)
+                    .await
+                    .map_err(|e| anyhow!("failed to write {}: {e}", path.display()))?,
+                None => print!("{manifest}"),
+            }
+        }
+    }
+    Ok(())
+}
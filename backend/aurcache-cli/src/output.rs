@@ -0,0 +1,51 @@
+use aurcache_types::builder::BuildStates;
+use chrono::DateTime;
+use serde::Serialize;
+
+/// Pretty printed JSON for scripting
+pub fn print_json<T: Serialize>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Left aligned columns separated by two spaces
+pub fn print_table<const N: usize>(headers: [&str; N], rows: Vec<[String; N]>) {
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: &[&str]| {
+        let line = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    };
+    line(&headers);
+    for row in &rows {
+        line(&row.each_ref().map(String::as_str));
+    }
+}
+
+pub fn build_status(status: i32) -> &'static str {
+    match status {
+        BuildStates::ACTIVE_BUILD => "building",
+        BuildStates::SUCCESSFUL_BUILD => "successful",
+        BuildStates::FAILED_BUILD => "failed",
+        BuildStates::ENQUEUED_BUILD => "enqueued",
+        _ => "unknown",
+    }
+}
+
+/// Unix timestamp as UTC date and time
+pub fn format_time(timestamp: Option<i64>) -> String {
+    timestamp
+        .and_then(|t| DateTime::from_timestamp(t, 0))
+        .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,224 @@
+use crate::client::ApiClient;
+use crate::output::{build_status, print_json, print_table};
+use anyhow::anyhow;
+use aurcache_api::models::package::{
+    AddPackage, ExtendedPackageModel, PackageSource, SimplePackageModel, UpdatePackage,
+};
+use aurcache_db::packages::SourceData;
+use clap::{Args, Subcommand};
+use reqwest::Method;
+
+#[derive(Subcommand)]
+pub enum PackageCommand {
+    /// List packages, out of date packages first
+    List {
+        #[arg(long)]
+        limit: Option<u64>,
+        #[arg(long)]
+        page: Option<u64>,
+    },
+
+    /// Show details of a package
+    Get {
+        /// Package name or id
+        package: String,
+    },
+
+    /// Add a package and build it
+    #[command(subcommand)]
+    Add(AddSource),
+
+    /// Remove a package and its builds
+    #[command(alias = "rm")]
+    Remove {
+        /// Package name or id
+        package: String,
+    },
+
+    /// Build the latest upstream version of a package
+    Update {
+        /// Package name or id
+        package: String,
+        /// Rebuild even if the package is up to date
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AddSource {
+    /// Package from the AUR
+    Aur {
+        name: String,
+        #[command(flatten)]
+        options: AddOptions,
+    },
+
+    /// PKGBUILD from a git repository
+    Git {
+        url: String,
+        /// Branch, tag or commit to build
+        #[arg(long = "ref", default_value = "main")]
+        git_ref: String,
+        /// Directory of the PKGBUILD inside the repository
+        #[arg(long, default_value = "")]
+        subfolder: String,
+        #[command(flatten)]
+        options: AddOptions,
+    },
+}
+
+#[derive(Args)]
+pub struct AddOptions {
+    /// Platform to build for, can be repeated (default: x86_64)
+    #[arg(long = "platform")]
+    platforms: Vec<String>,
+
+    /// makepkg flag, can be repeated
+    #[arg(long = "build-flag", allow_hyphen_values = true)]
+    build_flags: Vec<String>,
+}
+
+pub async fn run(client: &ApiClient, cmd: PackageCommand, json: bool) -> anyhow::Result<()> {
+    match cmd {
+        PackageCommand::List { limit, page } => {
+            let mut query = vec![];
+            if let Some(limit) = limit {
+                query.push(format!("limit={limit}"));
+            }
+            if let Some(page) = page {
+                query.push(format!("page={page}"));
+            }
+            let packages: Vec<SimplePackageModel> = client
+                .get(&format!("packages/list?{}", query.join("&")))
+                .await?;
+            if json {
+                return print_json(&packages);
+            }
+            print_table(
+                ["ID", "NAME", "VERSION", "UPSTREAM", "STATUS", "OUT OF DATE"],
+                packages
+                    .into_iter()
+                    .map(|p| {
+                        [
+                            p.id.to_string(),
+                            p.name,
+                            p.latest_version.unwrap_or_default(),
+                            p.upstream_version,
+                            build_status(p.status).to_string(),
+                            if p.outofdate != 0 { "yes" } else { "" }.to_string(),
+                        ]
+                    })
+                    .collect(),
+            );
+        }
+        PackageCommand::Get { package } => {
+            let id = resolve_package(client, &package).await?;
+            let package: ExtendedPackageModel = client.get(&format!("package/{id}")).await?;
+            if json {
+                return print_json(&package);
+            }
+            print_package(&package);
+        }
+        PackageCommand::Add(source) => {
+            let (source, options) = match source {
+                AddSource::Aur { name, options } => (SourceData::Aur { name }, options),
+                AddSource::Git {
+                    url,
+                    git_ref,
+                    subfolder,
+                    options,
+                } => (
+                    SourceData::Git {
+                        url,
+                        r#ref: git_ref,
+                        subfolder,
+                    },
+                    options,
+                ),
+            };
+            let body = AddPackage {
+                platforms: (!options.platforms.is_empty()).then_some(options.platforms),
+                build_flags: (!options.build_flags.is_empty()).then_some(options.build_flags),
+                source,
+            };
+            client.send_json(Method::POST, "package", &body).await?;
+            if !json {
+                println!("Package added, build enqueued");
+            }
+        }
+        PackageCommand::Remove { package } => {
+            let id = resolve_package(client, &package).await?;
+            client
+                .execute(Method::DELETE, &format!("package/{id}"))
+                .await?;
+            if !json {
+                println!("Removed package {package}");
+            }
+        }
+        PackageCommand::Update { package, force } => {
+            let id = resolve_package(client, &package).await?;
+            let builds: Vec<i32> = client
+                .post(
+                    &format!("package/{id}/update"),
+                    Some(&UpdatePackage { force }),
+                )
+                .await?;
+            if json {
+                return print_json(&builds);
+            }
+            for build in builds {
+                println!("Enqueued build #{build}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Id of a package given by id or name
+pub async fn resolve_package(client: &ApiClient, package: &str) -> anyhow::Result<i32> {
+    if let Ok(id) = package.parse() {
+        return Ok(id);
+    }
+    let packages: Vec<SimplePackageModel> = client.get("packages/list").await?;
+    packages
+        .into_iter()
+        .find(|p| p.name == package)
+        .map(|p| p.id)
+        .ok_or_else(|| anyhow!("no package named {package}"))
+}
+
+fn print_package(package: &ExtendedPackageModel) {
+    let source = match &package.package_source {
+        PackageSource::Aur(aur) => format!("aur ({})", aur.aur_url),
+        PackageSource::AurNotFound(_) => "aur (not found)".to_string(),
+        PackageSource::Git(git) => {
+            let mut source = format!("git {} @ {}", git.git_url, git.git_ref);
+            if !git.subfolder.is_empty() {
+                source.push_str(&format!(" in {}", git.subfolder));
+            }
+            source
+        }
+        PackageSource::Upload(_) => "upload".to_string(),
+    };
+
+    println!("Id:          {}", package.id);
+    println!("Name:        {}", package.name);
+    println!(
+        "Version:     {}",
+        package.latest_version.as_deref().unwrap_or("-")
+    );
+    println!("Upstream:    {}", package.upstream_version);
+    println!("Status:      {}", build_status(package.status));
+    println!("Out of date: {}", package.outofdate != 0);
+    println!("Platforms:   {}", package.selected_platforms.join(", "));
+    println!(
+        "Build flags: {}",
+        package
+            .selected_build_flags
+            .as_deref()
+            .unwrap_or_default()
+            .join(" ")
+    );
+    println!("Source:      {source}");
+}
@@ -0,0 +1,124 @@
+use crate::client::ApiClient;
+use crate::output::{print_json, print_table};
+use crate::package::resolve_package;
+use aurcache_api::models::settings::{SettingResponse, SettingValue};
+use aurcache_types::settings::{ApplicationSettings, SettingSource, SettingsEntry};
+use clap::Subcommand;
+use reqwest::Method;
+use serde::Serialize;
+use std::fmt::Debug;
+
+#[derive(Subcommand)]
+pub enum SettingsCommand {
+    /// Show the general settings and where their values come from
+    List {
+        /// Settings of this package (name or id) instead of the global ones
+        #[arg(long)]
+        package: Option<String>,
+    },
+
+    /// Show a single setting, e.g. `cpu_limit` or `mirror_countries`
+    Get {
+        key: String,
+        #[arg(long)]
+        package: Option<String>,
+    },
+
+    /// Store a setting, settings forced by an environment variable can't be changed
+    Set {
+        key: String,
+        /// Raw value, an empty string unsets optional settings
+        value: String,
+        #[arg(long)]
+        package: Option<String>,
+    },
+
+    /// Remove a stored setting so the default applies again
+    Reset {
+        key: String,
+        #[arg(long)]
+        package: Option<String>,
+    },
+}
+
+pub async fn run(client: &ApiClient, cmd: SettingsCommand, json: bool) -> anyhow::Result<()> {
+    match cmd {
+        SettingsCommand::List { package } => {
+            let query = pkgid_query(client, package).await?;
+            let settings: ApplicationSettings = client.get(&format!("settings{query}")).await?;
+            if json {
+                return print_json(&settings);
+            }
+            print_table(
+                ["KEY", "VALUE", "SOURCE"],
+                vec![
+                    row("cpu_limit", &settings.cpu_limit),
+                    row("memory_limit", &settings.memory_limit),
+                    row("max_concurrent_builds", &settings.max_concurrent_builds),
+                    row("version_check_interval", &settings.version_check_interval),
+                    row("auto_update_interval", &settings.auto_update_interval),
+                    row("job_timeout", &settings.job_timeout),
+                    row("builder_image", &settings.builder_image),
+                ],
+            );
+        }
+        SettingsCommand::Get { key, package } => {
+            let query = pkgid_query(client, package).await?;
+            let setting: SettingResponse = client.get(&format!("settings/{key}{query}")).await?;
+            if json {
+                return print_json(&setting);
+            }
+            println!("{}", setting.value);
+        }
+        SettingsCommand::Set {
+            key,
+            value,
+            package,
+        } => {
+            let query = pkgid_query(client, package).await?;
+            client
+                .send_json(
+                    Method::PATCH,
+                    &format!("settings/{key}{query}"),
+                    &SettingValue { value },
+                )
+                .await?;
+            if !json {
+                println!("Updated {key}");
+            }
+        }
+        SettingsCommand::Reset { key, package } => {
+            let query = pkgid_query(client, package).await?;
+            client
+                .execute(Method::DELETE, &format!("settings/{key}{query}"))
+                .await?;
+            if !json {
+                println!("Reset {key}");
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn pkgid_query(client: &ApiClient, package: Option<String>) -> anyhow::Result<String> {
+    Ok(match package {
+        Some(package) => format!("?pkgid={}", resolve_package(client, &package).await?),
+        None => String::new(),
+    })
+}
+
+fn row<T: Serialize + Debug>(key: &str, entry: &SettingsEntry<T>) -> [String; 3] {
+    let value = match serde_json::to_value(&entry.value) {
+        Ok(serde_json::Value::String(s)) => s,
+        Ok(serde_json::Value::Null) => String::new(),
+        Ok(value) => value.to_string(),
+        Err(_) => format!("{:?}", entry.value),
+    };
+    let source = match entry.source {
+        SettingSource::Env => "env",
+        SettingSource::Package => "package",
+        SettingSource::Global => "global",
+        SettingSource::Default => "default",
+    };
+    [key.to_string(), value, source.to_string()]
+}
@@ -0,0 +1,26 @@
+use crate::client::ApiClient;
+use crate::output::print_json;
+use aurcache_api::models::stats::ListStats;
+
+pub async fn run(client: &ApiClient, json: bool) -> anyhow::Result<()> {
+    let stats: ListStats = client.get("stats").await?;
+    if json {
+        return print_json(&stats);
+    }
+
+    println!("Packages:            {}", stats.total_packages);
+    println!(
+        "Builds:              {} ({:+.1}% last 30 days)",
+        stats.total_builds,
+        stats.total_build_trend * 100.0
+    );
+    println!("Successful builds:   {}", stats.successful_builds);
+    println!("Failed builds:       {}", stats.failed_builds);
+    println!(
+        "Average build time:  {}s ({:+.1}% last 30 days)",
+        stats.avg_build_time,
+        stats.avg_build_time_trend * 100.0
+    );
+    println!("Repo size:           {} MiB", stats.repo_size / 1024 / 1024);
+    Ok(())
+}
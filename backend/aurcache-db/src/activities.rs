@@ -1,6 +1,8 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.2
 
+use sea_orm::Iterable;
 use sea_orm::entity::prelude::*;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, EnumIter, DeriveActiveEnum, Eq)]
 #[sea_orm(rs_type = "i32", db_type = "Integer")]
@@ -15,6 +17,51 @@ pub enum ActivityType {
     StartBuild,
     #[sea_orm(num_value = 4)]
     FinishBuild,
+    #[sea_orm(num_value = 5)]
+    PatchPackage,
+    #[sea_orm(num_value = 6)]
+    PatchSetting,
+    #[sea_orm(num_value = 7)]
+    ResetSetting,
+    #[sea_orm(num_value = 8)]
+    CancelBuild,
+    #[sea_orm(num_value = 9)]
+    RetryBuild,
+    #[sea_orm(num_value = 10)]
+    DeleteBuild,
+    #[sea_orm(num_value = 11)]
+    RepairRepo,
+}
+
+impl ActivityType {
+    /// Returns the string representation used in the API.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityType::AddPackage => "add_package",
+            ActivityType::RemovePackage => "remove_package",
+            ActivityType::UpdatePackage => "update_package",
+            ActivityType::StartBuild => "start_build",
+            ActivityType::FinishBuild => "finish_build",
+            ActivityType::PatchPackage => "patch_package",
+            ActivityType::PatchSetting => "patch_setting",
+            ActivityType::ResetSetting => "reset_setting",
+            ActivityType::CancelBuild => "cancel_build",
+            ActivityType::RetryBuild => "retry_build",
+            ActivityType::DeleteBuild => "delete_build",
+            ActivityType::RepairRepo => "repair_repo",
+        }
+    }
+}
+
+impl FromStr for ActivityType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown activity type: {s}"))
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -26,6 +73,8 @@ pub struct Model {
     pub data: String, // json object
     pub timestamp: i64,
     pub user: Option<String>,
+    pub ip: Option<String>,
+    pub pkg_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
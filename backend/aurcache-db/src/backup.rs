@@ -0,0 +1,419 @@
+//! Logical dump and restore of all tables, independent of the database backend.
+//! Used for backups and to copy an instance between sqlite and postgres.
+
+use crate::migration::Migrator;
+use anyhow::{anyhow, bail};
+use sea_orm::sea_query::{Alias, PostgresQueryBuilder, Query, SqliteQueryBuilder};
+use sea_orm::{
+    AccessMode, ConnectionTrait, DatabaseConnection, DbBackend, FromQueryResult, IsolationLevel,
+    JsonValue, Statement, TransactionTrait, Value,
+};
+use sea_orm_migration::MigratorTrait;
+use std::collections::BTreeMap;
+use tracing::info;
+
+/// Rows inserted by a single statement on restore
+const INSERT_BATCH_SIZE: usize = 500;
+
+pub struct TableDump {
+    pub name: String,
+    /// rows as column -> value objects
+    pub rows: Vec<JsonValue>,
+}
+
+/// Names of all migrations known to this version, in the order they are applied
+#[must_use]
+pub fn known_migrations() -> Vec<String> {
+    Migrator::migrations()
+        .iter()
+        .map(|m| m.name().to_string())
+        .collect()
+}
+
+pub async fn applied_migrations(db: &DatabaseConnection) -> anyhow::Result<Vec<String>> {
+    Ok(Migrator::get_applied_migrations(db)
+        .await?
+        .iter()
+        .map(|m| m.name().to_string())
+        .collect())
+}
+
+async fn table_names(db: &impl ConnectionTrait) -> anyhow::Result<Vec<String>> {
+    let backend = db.get_database_backend();
+    let sql = match backend {
+        DbBackend::Sqlite => {
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name"
+        }
+        DbBackend::Postgres => {
+            "SELECT tablename AS name FROM pg_tables WHERE schemaname = 'public' ORDER BY tablename"
+        }
+        DbBackend::MySql => bail!("Unsupported database type"),
+    };
+    Ok(db
+        .query_all(Statement::from_string(backend, sql))
+        .await?
+        .iter()
+        .map(|row| row.try_get::<String>("", "name"))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|name| name != "seaql_migrations")
+        .collect())
+}
+
+/// Dump all tables from a single snapshot of the database
+pub async fn dump_tables(db: &DatabaseConnection) -> anyhow::Result<Vec<TableDump>> {
+    let backend = db.get_database_backend();
+    let txn = match backend {
+        DbBackend::Postgres => {
+            db.begin_with_config(
+                Some(IsolationLevel::RepeatableRead),
+                Some(AccessMode::ReadOnly),
+            )
+            .await?
+        }
+        // sqlite transactions are serializable anyway
+        _ => db.begin().await?,
+    };
+
+    let mut tables = vec![];
+    for name in table_names(&txn).await? {
+        let rows = JsonValue::find_by_statement(Statement::from_string(
+            backend,
+            format!("SELECT * FROM {name}"),
+        ))
+        .all(&txn)
+        .await?;
+        tables.push(TableDump { name, rows });
+    }
+    txn.commit().await?;
+    Ok(tables)
+}
+
+/// Migrate an empty database to the schema of a backup taken after `migrations` were applied
+pub async fn prepare_restore(db: &DatabaseConnection, migrations: &[String]) -> anyhow::Result<()> {
+    let known = known_migrations();
+    if let Some(unknown) = migrations.iter().find(|m| !known.contains(m)) {
+        bail!("Backup contains the unknown migration {unknown}, it was created by a newer version");
+    }
+    if known.get(..migrations.len()) != Some(migrations) {
+        bail!("Backup migrations don't match the migration history of this version");
+    }
+    if !applied_migrations(db).await?.is_empty() {
+        bail!("Target database is not empty, only new databases can be restored into");
+    }
+
+    Migrator::up(db, Some(u32::try_from(migrations.len())?)).await?;
+    Ok(())
+}
+
+fn json_to_value(value: &JsonValue) -> Value {
+    match value {
+        JsonValue::Null => Value::String(None),
+        JsonValue::Bool(b) => Value::Bool(Some(*b)),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) => Value::BigInt(Some(i)),
+            None => Value::Double(n.as_f64()),
+        },
+        JsonValue::String(s) => Value::String(Some(Box::new(s.clone()))),
+        other => Value::String(Some(Box::new(other.to_string()))),
+    }
+}
+
+/// Insert dumped rows, ids are kept as they are
+pub async fn restore_table(
+    db: &impl ConnectionTrait,
+    table: &str,
+    rows: &[JsonValue],
+) -> anyhow::Result<()> {
+    for batch in rows.chunks(INSERT_BATCH_SIZE) {
+        let Some(JsonValue::Object(first)) = batch.first() else {
+            continue;
+        };
+        let columns: Vec<&String> = first.keys().collect();
+
+        let mut insert = Query::insert();
+        insert
+            .into_table(Alias::new(table))
+            .columns(columns.iter().map(|c| Alias::new(c.as_str())));
+        for row in batch {
+            insert.values(
+                columns
+                    .iter()
+                    .map(|c| json_to_value(row.get(c.as_str()).unwrap_or(&JsonValue::Null)).into()),
+            )?;
+        }
+
+        // values are inlined, bound NULLs would be typed as text on postgres
+        let sql = match db.get_database_backend() {
+            DbBackend::Postgres => insert.to_string(PostgresQueryBuilder),
+            _ => insert.to_string(SqliteQueryBuilder),
+        };
+        db.execute_unprepared(&sql).await?;
+    }
+    Ok(())
+}
+
+/// Move the id sequences past the restored ids, only needed on postgres
+pub async fn reset_sequences(db: &impl ConnectionTrait) -> anyhow::Result<()> {
+    if db.get_database_backend() != DbBackend::Postgres {
+        return Ok(());
+    }
+
+    let serial_columns = db
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT table_name, column_name FROM information_schema.columns \
+             WHERE table_schema = 'public' AND column_default LIKE 'nextval(%'",
+        ))
+        .await?;
+    for row in serial_columns {
+        let table: String = row.try_get("", "table_name")?;
+        let column: String = row.try_get("", "column_name")?;
+        db.execute_unprepared(&format!(
+            "SELECT setval(pg_get_serial_sequence('public.{table}', '{column}'), \
+             COALESCE(MAX({column}), 1), MAX({column}) IS NOT NULL) FROM public.{table}"
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+/// Apply the migrations added since the backup was taken
+pub async fn finish_restore(db: &DatabaseConnection) -> anyhow::Result<()> {
+    reset_sequences(db).await?;
+    Migrator::up(db, None).await?;
+    Ok(())
+}
+
+async fn count_rows(db: &impl ConnectionTrait, table: &str) -> anyhow::Result<i64> {
+    let row = db
+        .query_one(Statement::from_string(
+            db.get_database_backend(),
+            format!("SELECT COUNT(*) AS count FROM {table}"),
+        ))
+        .await?
+        .ok_or_else(|| anyhow!("Failed to count rows of {table}"))?;
+    Ok(row.try_get("", "count")?)
+}
+
+/// Copy all tables from `source` into the empty `target`, keeping ids.
+/// `target` ends up at the latest schema version, `source` is not modified.
+pub async fn copy_database(
+    source: &DatabaseConnection,
+    target: &DatabaseConnection,
+) -> anyhow::Result<BTreeMap<String, usize>> {
+    let migrations = applied_migrations(source).await?;
+    prepare_restore(target, &migrations).await?;
+
+    let tables = dump_tables(source).await?;
+    let txn = target.begin().await?;
+    for table in &tables {
+        restore_table(&txn, &table.name, &table.rows).await?;
+        info!("Copied {} rows of {}", table.rows.len(), table.name);
+    }
+    txn.commit().await?;
+
+    for table in &tables {
+        let copied = count_rows(target, &table.name).await?;
+        if usize::try_from(copied)? != table.rows.len() {
+            bail!(
+                "Table {} has {copied} rows in the target, expected {}",
+                table.name,
+                table.rows.len()
+            );
+        }
+    }
+    finish_restore(target).await?;
+
+    Ok(tables.into_iter().map(|t| (t.name, t.rows.len())).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::init::connect_url;
+    use crate::packages::SourceType;
+    use crate::{builds, packages, settings};
+    use sea_orm::{ActiveModelTrait, EntityTrait, ModelTrait, Set};
+
+    async fn empty_db() -> DatabaseConnection {
+        connect_url("sqlite::memory:").await.unwrap()
+    }
+
+    /// Packages with an id gap, builds with text that needs quoting and nulls, and
+    /// a global (`pkg_id` -1) and a package setting
+    async fn seeded_db() -> DatabaseConnection {
+        let db = empty_db().await;
+        Migrator::up(&db, None).await.unwrap();
+
+        let mut pkgs = vec![];
+        for name in ["foo", "bar", "baz"] {
+            let pkg = packages::ActiveModel {
+                name: Set(name.to_string()),
+                status: Set(1),
+                out_of_date: Set(0),
+                platforms: Set("x86_64;aarch64".to_string()),
+                build_flags: Set("-B;--noconfirm".to_string()),
+                source_type: Set(SourceType::Aur),
+                source_data: Set(format!(r#"{{"type":"aur","name":"{name}"}}"#)),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+            pkgs.push(pkg);
+        }
+        pkgs.remove(1).delete(&db).await.unwrap();
+
+        for (pkg, output) in pkgs
+            .iter()
+            .zip([Some("it's \"quoted\"\nand ünïcode"), None])
+        {
+            builds::ActiveModel {
+                pkg_id: Set(pkg.id),
+                output: Set(output.map(str::to_string)),
+                status: Set(Some(1)),
+                start_time: Set(Some(1_718_000_000)),
+                platform: Set("x86_64".to_string()),
+                version: Set("1.0-1".to_string()),
+                peak_memory: Set(Some(i64::from(i32::MAX) + 1)),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        for (key, pkg_id) in [("cpu_limit", -1), ("builder_image", pkgs[1].id)] {
+            settings::ActiveModel {
+                key: Set(key.to_string()),
+                value: Set(Some("2000".to_string())),
+                pkg_id: Set(Some(pkg_id)),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        db
+    }
+
+    fn rows_by_table(tables: Vec<TableDump>) -> BTreeMap<String, Vec<JsonValue>> {
+        tables.into_iter().map(|t| (t.name, t.rows)).collect()
+    }
+
+    #[tokio::test]
+    async fn backup_round_trip() {
+        let source = seeded_db().await;
+        let migrations = applied_migrations(&source).await.unwrap();
+        // through json lines like the backup archive
+        let dumped: Vec<(String, Vec<String>)> = dump_tables(&source)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| {
+                let lines = t.rows.iter().map(|r| serde_json::to_string(r).unwrap());
+                (t.name, lines.collect())
+            })
+            .collect();
+
+        let target = empty_db().await;
+        prepare_restore(&target, &migrations).await.unwrap();
+        let txn = target.begin().await.unwrap();
+        for (table, lines) in &dumped {
+            let rows: Vec<JsonValue> = lines
+                .iter()
+                .map(|l| serde_json::from_str(l).unwrap())
+                .collect();
+            restore_table(&txn, table, &rows).await.unwrap();
+        }
+        txn.commit().await.unwrap();
+        finish_restore(&target).await.unwrap();
+
+        let expected = rows_by_table(dump_tables(&source).await.unwrap());
+        assert_eq!(expected["packages"].len(), 2);
+        assert_eq!(expected["builds"].len(), 2);
+        assert_eq!(expected["settings"].len(), 2);
+        assert_eq!(rows_by_table(dump_tables(&target).await.unwrap()), expected);
+        assert_eq!(applied_migrations(&target).await.unwrap(), migrations);
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_unknown_migrations() {
+        let mut migrations = known_migrations();
+        migrations.push("m29990101_000000_from_the_future".to_string());
+
+        let err = prepare_restore(&empty_db().await, &migrations)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown migration"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_out_of_order_migrations() {
+        let known = known_migrations();
+        let mut swapped = known.clone();
+        swapped.swap(0, 1);
+        // a migration of the history missing in between
+        let gap = [known[0].clone(), known[2].clone()];
+
+        for migrations in [&swapped[..], &gap[..]] {
+            let err = prepare_restore(&empty_db().await, migrations)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("don't match"), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_non_empty_target() {
+        let target = seeded_db().await;
+        let err = prepare_restore(&target, &known_migrations())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not empty"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn copy_sqlite_to_sqlite() {
+        let source = seeded_db().await;
+        let target = empty_db().await;
+
+        let copied = copy_database(&source, &target).await.unwrap();
+        assert_eq!(copied["packages"], 2);
+        assert_eq!(copied["builds"], 2);
+        assert_eq!(copied["settings"], 2);
+
+        assert_eq!(
+            rows_by_table(dump_tables(&target).await.unwrap()),
+            rows_by_table(dump_tables(&source).await.unwrap())
+        );
+        assert_eq!(
+            applied_migrations(&target).await.unwrap(),
+            applied_migrations(&source).await.unwrap()
+        );
+
+        // ids of new rows continue after the copied ones
+        let max_id = packages::Entity::find()
+            .all(&target)
+            .await
+            .unwrap()
+            .iter()
+            .map(|p| p.id)
+            .max()
+            .unwrap();
+        let pkg = packages::ActiveModel {
+            name: Set("new".to_string()),
+            status: Set(3),
+            out_of_date: Set(0),
+            platforms: Set("x86_64".to_string()),
+            build_flags: Set(String::new()),
+            source_type: Set(SourceType::Aur),
+            source_data: Set(r#"{"type":"aur","name":"new"}"#.to_string()),
+            ..Default::default()
+        }
+        .insert(&target)
+        .await
+        .unwrap();
+        assert_eq!(pkg.id, max_id + 1);
+    }
+}
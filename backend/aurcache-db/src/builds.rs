@@ -17,6 +17,18 @@ pub struct Model {
     pub end_time: Option<i64>,
     pub platform: String,
     pub version: String,
+    /// peak memory usage of the build container in bytes
+    pub peak_memory: Option<i64>,
+    /// total cpu time of the build container in milliseconds
+    pub cpu_time: Option<i64>,
+    /// wall time of the build container in milliseconds
+    pub wall_time: Option<i64>,
+    pub net_rx: Option<i64>,
+    pub net_tx: Option<i64>,
+    pub blk_read: Option<i64>,
+    pub blk_write: Option<i64>,
+    /// json encoded namcap findings, `None` if namcap didn't run
+    pub lint: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -8,29 +8,27 @@ use std::{env, fs};
 use tracing::log::LevelFilter;
 
 pub async fn init_db() -> anyhow::Result<DatabaseConnection> {
-    let db: DatabaseConnection = match database_type() {
+    let db = connect_db().await?;
+    Migrator::up(&db, None).await?;
+    Ok(db)
+}
+
+/// Connect to the configured database without running migrations
+pub async fn connect_db() -> anyhow::Result<DatabaseConnection> {
+    connect_url(&configured_db_url()?).await
+}
+
+/// Connection url of the database configured by the `DB_*` environment variables,
+/// creates `./db` for sqlite
+pub fn configured_db_url() -> anyhow::Result<String> {
+    match database_type() {
         DbBackend::Sqlite => {
             if fs::metadata("./db").is_err() {
                 fs::create_dir("./db")?;
             }
 
             let db_name = env::var("DB_NAME").unwrap_or("db.sqlite".to_string());
-
-            let mut conn_opts = ConnectOptions::new(format!("sqlite://db/{db_name}?mode=rwc"));
-            conn_opts
-                .max_connections(1)
-                .min_connections(1)
-                .acquire_timeout(Duration::from_secs(30))
-                .sqlx_logging_level(LevelFilter::Trace);
-            let db = Database::connect(conn_opts).await?;
-            db.execute_unprepared("
-                PRAGMA journal_mode = WAL;          -- read/write concurrency; persistent on the db file
-                PRAGMA synchronous = NORMAL;        -- fsync at WAL checkpoint, not every write
-                PRAGMA busy_timeout = 5000;         -- wait up to 5s for the write lock before SQLITE_BUSY
-                PRAGMA wal_autocheckpoint = 1000;   -- checkpoint every 1000 pages (~1MB WAL)
-                PRAGMA wal_checkpoint(TRUNCATE);    -- truncate any massive WAL left by a previous run
-            ").await?;
-            db
+            Ok(format!("sqlite://db/{db_name}?mode=rwc"))
         }
         DbBackend::Postgres => {
             let db_user = env::var("DB_USER")
@@ -41,14 +39,36 @@ pub async fn init_db() -> anyhow::Result<DatabaseConnection> {
                 .map_err(|_| anyhow!("No DB_HOST envvar for POSTGRES HOST specified"))?;
             let db_name = env::var("DB_NAME").unwrap_or("postgres".to_string());
 
-            let conn_str = format!("postgres://{db_user}:{db_pwd}@{db_host}/{db_name}");
-            let mut conn_opts = ConnectOptions::new(conn_str);
-            conn_opts.sqlx_logging_level(LevelFilter::Trace);
-            Database::connect(conn_opts).await?
+            Ok(format!("postgres://{db_user}:{db_pwd}@{db_host}/{db_name}"))
         }
         _ => bail!("Unsupported database type"),
-    };
+    }
+}
 
-    Migrator::up(&db, None).await?;
+/// Connect to a `sqlite://` or `postgres://` url without running migrations
+pub async fn connect_url(url: &str) -> anyhow::Result<DatabaseConnection> {
+    let mut conn_opts = ConnectOptions::new(url);
+    conn_opts.sqlx_logging_level(LevelFilter::Trace);
+    if url.starts_with("sqlite:") {
+        conn_opts
+            .max_connections(1)
+            .min_connections(1)
+            .acquire_timeout(Duration::from_secs(30));
+    }
+
+    let db = Database::connect(conn_opts).await?;
+    match db.get_database_backend() {
+        DbBackend::Sqlite => {
+            db.execute_unprepared("
+                PRAGMA journal_mode = WAL;          -- read/write concurrency; persistent on the db file
+                PRAGMA synchronous = NORMAL;        -- fsync at WAL checkpoint, not every write
+                PRAGMA busy_timeout = 5000;         -- wait up to 5s for the write lock before SQLITE_BUSY
+                PRAGMA wal_autocheckpoint = 1000;   -- checkpoint every 1000 pages (~1MB WAL)
+                PRAGMA wal_checkpoint(TRUNCATE);    -- truncate any massive WAL left by a previous run
+            ").await?;
+        }
+        DbBackend::Postgres => {}
+        DbBackend::MySql => bail!("Unsupported database type"),
+    }
     Ok(db)
 }
@@ -3,11 +3,13 @@
 pub mod prelude;
 
 pub mod activities;
+pub mod backup;
 pub mod builds;
 pub mod files;
 pub mod helpers;
 pub mod init;
 pub mod migration;
+pub mod mirror_benchmarks;
 pub mod packages;
 pub mod packages_files;
 pub mod settings;
@@ -1,4 +1,3 @@
-use crate::helpers::dbtype::database_type;
 use sea_orm::DbBackend;
 use sea_orm_migration::prelude::*;
 
@@ -10,7 +9,7 @@ impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         let db = manager.get_connection();
 
-        match database_type() {
+        match manager.get_database_backend() {
             DbBackend::Sqlite => {
                 db.execute_unprepared(
                     r"
@@ -0,0 +1,99 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match manager.get_database_backend() {
+            DbBackend::Sqlite => {
+                // source ip and affected package of each activity
+                db.execute_unprepared(
+                    r"
+alter table activity
+add ip TEXT;
+",
+                )
+                .await?;
+                db.execute_unprepared(
+                    r"
+alter table activity
+add pkg_id INTEGER;
+",
+                )
+                .await?;
+                db.execute_unprepared(
+                    r"
+create index activity_timestamp_index
+on activity (timestamp);
+",
+                )
+                .await?;
+            }
+            DbBackend::Postgres => {
+                db.execute_unprepared(
+                    r"
+ALTER TABLE public.activity
+ADD COLUMN ip TEXT,
+ADD COLUMN pkg_id INTEGER;
+",
+                )
+                .await?;
+                db.execute_unprepared(
+                    r"
+CREATE INDEX activity_timestamp_index
+ON public.activity (timestamp);
+",
+                )
+                .await?;
+            }
+            _ => Err(DbErr::Migration("Unsupported database type".to_string()))?,
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match manager.get_database_backend() {
+            DbBackend::Sqlite => {
+                db.execute_unprepared(r"DROP INDEX IF EXISTS activity_timestamp_index;")
+                    .await?;
+                db.execute_unprepared(
+                    r"
+alter table activity
+drop column ip;
+",
+                )
+                .await?;
+                db.execute_unprepared(
+                    r"
+alter table activity
+drop column pkg_id;
+",
+                )
+                .await?;
+            }
+            DbBackend::Postgres => {
+                db.execute_unprepared(r"DROP INDEX IF EXISTS activity_timestamp_index;")
+                    .await?;
+                db.execute_unprepared(
+                    r"
+ALTER TABLE public.activity
+DROP COLUMN ip,
+DROP COLUMN pkg_id;
+",
+                )
+                .await?;
+            }
+            _ => Err(DbErr::Migration("Unsupported database type".to_string()))?,
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// resource usage columns sampled from the build container
+const COLUMNS: [&str; 7] = [
+    "peak_memory",
+    "cpu_time",
+    "wall_time",
+    "net_rx",
+    "net_tx",
+    "blk_read",
+    "blk_write",
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match manager.get_database_backend() {
+            DbBackend::Sqlite => {
+                // sqlite supports only one column per alter table
+                for column in COLUMNS {
+                    db.execute_unprepared(&format!(
+                        r"
+alter table builds
+add {column} INTEGER;
+"
+                    ))
+                    .await?;
+                }
+            }
+            DbBackend::Postgres => {
+                for column in COLUMNS {
+                    db.execute_unprepared(&format!(
+                        r"
+ALTER TABLE public.builds
+ADD COLUMN {column} BIGINT;
+"
+                    ))
+                    .await?;
+                }
+            }
+            _ => Err(DbErr::Migration("Unsupported database type".to_string()))?,
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match manager.get_database_backend() {
+            DbBackend::Sqlite => {
+                for column in COLUMNS {
+                    db.execute_unprepared(&format!(
+                        r"
+alter table builds
+drop column {column};
+"
+                    ))
+                    .await?;
+                }
+            }
+            DbBackend::Postgres => {
+                for column in COLUMNS {
+                    db.execute_unprepared(&format!(
+                        r"
+ALTER TABLE public.builds
+DROP COLUMN {column};
+"
+                    ))
+                    .await?;
+                }
+            }
+            _ => Err(DbErr::Migration("Unsupported database type".to_string()))?,
+        }
+
+        Ok(())
+    }
+}
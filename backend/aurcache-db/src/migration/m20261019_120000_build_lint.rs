@@ -0,0 +1,65 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match manager.get_database_backend() {
+            DbBackend::Sqlite => {
+                // json encoded namcap findings
+                db.execute_unprepared(
+                    r"
+alter table builds
+add lint TEXT;
+",
+                )
+                .await?;
+            }
+            DbBackend::Postgres => {
+                db.execute_unprepared(
+                    r"
+ALTER TABLE public.builds
+ADD COLUMN lint TEXT;
+",
+                )
+                .await?;
+            }
+            _ => Err(DbErr::Migration("Unsupported database type".to_string()))?,
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match manager.get_database_backend() {
+            DbBackend::Sqlite => {
+                db.execute_unprepared(
+                    r"
+alter table builds
+drop column lint;
+",
+                )
+                .await?;
+            }
+            DbBackend::Postgres => {
+                db.execute_unprepared(
+                    r"
+ALTER TABLE public.builds
+DROP COLUMN lint;
+",
+                )
+                .await?;
+            }
+            _ => Err(DbErr::Migration("Unsupported database type".to_string()))?,
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match manager.get_database_backend() {
+            DbBackend::Sqlite => {
+                db.execute_unprepared(
+                    r"
+CREATE TABLE mirror_benchmarks
+(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    platform TEXT NOT NULL,
+    rank INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    country TEXT NOT NULL,
+    protocol TEXT NOT NULL,
+    throughput REAL NOT NULL,
+    latency REAL NOT NULL,
+    upstream_score REAL,
+    score REAL NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+",
+                )
+                .await?;
+            }
+            DbBackend::Postgres => {
+                db.execute_unprepared(
+                    r"
+CREATE TABLE public.mirror_benchmarks
+(
+    id SERIAL PRIMARY KEY,
+    platform TEXT NOT NULL,
+    rank INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    country TEXT NOT NULL,
+    protocol TEXT NOT NULL,
+    throughput DOUBLE PRECISION NOT NULL,
+    latency DOUBLE PRECISION NOT NULL,
+    upstream_score DOUBLE PRECISION,
+    score DOUBLE PRECISION NOT NULL,
+    timestamp BIGINT NOT NULL
+);
+",
+                )
+                .await?;
+            }
+            _ => Err(DbErr::Migration("Unsupported database type".to_string()))?,
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match manager.get_database_backend() {
+            DbBackend::Sqlite | DbBackend::Postgres => {
+                db.execute_unprepared(
+                    r"
+drop table mirror_benchmarks;
+",
+                )
+                .await?;
+            }
+            _ => Err(DbErr::Migration("Unsupported database type".to_string()))?,
+        }
+
+        Ok(())
+    }
+}
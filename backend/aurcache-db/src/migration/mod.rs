@@ -7,6 +7,10 @@ mod m20251015_230000_pkg_sources;
 mod m20251106_100000_build_version;
 mod m20251107_000000_build_flags_no_install;
 mod m20251204_160000_settings;
+mod m20261019_100000_activity_audit;
+mod m20261019_110000_build_resources;
+mod m20261019_120000_build_lint;
+mod m20261019_130000_mirror_benchmarks;
 
 pub struct Migrator;
 
@@ -21,6 +25,10 @@ impl MigratorTrait for Migrator {
             Box::new(m20251015_230000_pkg_sources::Migration),
             Box::new(m20251204_160000_settings::Migration),
             Box::new(m20251107_000000_build_flags_no_install::Migration),
+            Box::new(m20261019_100000_activity_audit::Migration),
+            Box::new(m20261019_110000_build_resources::Migration),
+            Box::new(m20261019_120000_build_lint::Migration),
+            Box::new(m20261019_130000_mirror_benchmarks::Migration),
         ]
     }
 }
@@ -0,0 +1,29 @@
+use sea_orm::entity::prelude::*;
+
+/// Result of the last mirror ranking, one row per successfully benchmarked mirror
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "mirror_benchmarks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub platform: String,
+    /// position in the ranking, starting at 1
+    pub rank: i32,
+    pub url: String,
+    pub country: String,
+    pub protocol: String,
+    /// KiB/s
+    pub throughput: f64,
+    /// seconds until the response headers arrived
+    pub latency: f64,
+    /// score of archlinux.org, lower is better
+    pub upstream_score: Option<f64>,
+    /// combined score, higher is better
+    pub score: f64,
+    pub timestamp: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -3,6 +3,7 @@
 pub use super::activities::Entity as Activities;
 pub use super::builds::Entity as Builds;
 pub use super::files::Entity as Files;
+pub use super::mirror_benchmarks::Entity as MirrorBenchmarks;
 pub use super::packages::Entity as Packages;
 pub use super::packages_files::Entity as PackagesFiles;
 pub use super::settings::Entity as Settings;
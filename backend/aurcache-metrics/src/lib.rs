@@ -0,0 +1,147 @@
+//! Process wide Prometheus metrics of AURCache.
+//!
+//! Metrics which can be derived from the database (build counts, out-of-date packages, ...)
+//! are collected on scrape by the api, everything else is recorded here where it happens.
+
+use prometheus::{
+    Encoder, Gauge, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+pub static REGISTRY: LazyLock<Registry> =
+    LazyLock::new(|| Registry::new_custom(Some("aurcache".to_string()), None).unwrap());
+
+fn register<T: prometheus::core::Collector + Clone + 'static>(collector: T) -> T {
+    REGISTRY
+        .register(Box::new(collector.clone()))
+        .expect("metric registered twice");
+    collector
+}
+
+/// Duration of finished builds in seconds
+pub static BUILD_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new("build_duration_seconds", "Duration of finished builds").buckets(
+                vec![
+                    30.0, 60.0, 120.0, 300.0, 600.0, 1200.0, 1800.0, 3600.0, 7200.0, 14400.0,
+                ],
+            ),
+            &["platform", "status"],
+        )
+        .unwrap(),
+    )
+});
+
+/// Builds currently running in a container
+pub static ACTIVE_BUILDS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register(IntGauge::new("active_builds", "Builds currently running in a container").unwrap())
+});
+
+/// Builds waiting for a free build slot
+pub static QUEUE_DEPTH: LazyLock<IntGauge> = LazyLock::new(|| {
+    register(IntGauge::new("build_queue_depth", "Builds waiting for a free build slot").unwrap())
+});
+
+/// Latency of AUR RPC requests in seconds
+pub static AUR_REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "aur_request_duration_seconds",
+                "Latency of AUR RPC requests",
+            ),
+            &["endpoint"],
+        )
+        .unwrap(),
+    )
+});
+
+/// Failed AUR RPC requests
+pub static AUR_REQUEST_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new("aur_request_errors_total", "Failed AUR RPC requests"),
+            &["endpoint"],
+        )
+        .unwrap(),
+    )
+});
+
+/// Position of each mirror in the last generated mirrorlist (1 = fastest)
+pub static MIRROR_RANK: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new("mirror_rank", "Position of the mirror in the last ranking"),
+            &["url"],
+        )
+        .unwrap(),
+    )
+});
+
+/// Unix timestamp of the last successful mirror ranking
+pub static MIRROR_RANK_TIMESTAMP: LazyLock<Gauge> = LazyLock::new(|| {
+    register(
+        Gauge::new(
+            "mirror_rank_timestamp_seconds",
+            "Unix timestamp of the last successful mirror ranking",
+        )
+        .unwrap(),
+    )
+});
+
+/// Gauges refreshed from the database on each scrape
+pub static DB_GAUGES: LazyLock<DbGauges> = LazyLock::new(|| DbGauges {
+    builds: register(
+        IntGaugeVec::new(
+            Opts::new("builds", "Builds stored in the database"),
+            &["status", "platform"],
+        )
+        .unwrap(),
+    ),
+    packages: register(IntGauge::new("packages", "Packages managed by AURCache").unwrap()),
+    out_of_date_packages: register(
+        IntGauge::new(
+            "out_of_date_packages",
+            "Packages with a newer upstream version",
+        )
+        .unwrap(),
+    ),
+    repo_size: register(IntGauge::new("repo_size_bytes", "Size of the repo on disk").unwrap()),
+});
+
+pub struct DbGauges {
+    pub builds: IntGaugeVec,
+    pub packages: IntGauge,
+    pub out_of_date_packages: IntGauge,
+    pub repo_size: IntGauge,
+}
+
+/// Record the outcome of a single AUR request
+pub fn observe_aur_request<T, E>(endpoint: &str, elapsed: Duration, result: &Result<T, E>) {
+    AUR_REQUEST_DURATION
+        .with_label_values(&[endpoint])
+        .observe(elapsed.as_secs_f64());
+    if result.is_err() {
+        AUR_REQUEST_ERRORS.with_label_values(&[endpoint]).inc();
+    }
+}
+
+/// Encode all registered metrics in the Prometheus text exposition format
+pub fn gather() -> anyhow::Result<String> {
+    // touch lazily registered metrics so they show up before their first observation
+    LazyLock::force(&BUILD_DURATION);
+    LazyLock::force(&ACTIVE_BUILDS);
+    LazyLock::force(&QUEUE_DEPTH);
+    LazyLock::force(&AUR_REQUEST_DURATION);
+    LazyLock::force(&AUR_REQUEST_ERRORS);
+    LazyLock::force(&MIRROR_RANK);
+    LazyLock::force(&MIRROR_RANK_TIMESTAMP);
+    LazyLock::force(&DB_GAUGES);
+
+    let mut buffer = vec![];
+    TextEncoder::new().encode(&REGISTRY.gather(), &mut buffer)?;
+    Ok(String::from_utf8(buffer)?)
+}
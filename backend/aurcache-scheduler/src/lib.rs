@@ -1,3 +1,4 @@
 pub mod auto_update;
+pub mod mirror_health;
 pub mod mirror_ranking;
 pub mod update_version_check;
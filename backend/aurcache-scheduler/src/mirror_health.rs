@@ -0,0 +1,81 @@
+use aurcache_builder::build_mode::mirrorlist_file;
+use aurcache_types::settings::{ApplicationSettings, Setting, SettingsEntry};
+use aurcache_utils::mirrors::{MirrorHealthCheck, MirrorHealthReport, rank_options};
+use aurcache_utils::settings::general::SettingsTraits;
+use chrono::{TimeDelta, Utc};
+use pacman_mirrors::health::{MirrorState, demote_unhealthy, parse_servers, probe};
+use sea_orm::DatabaseConnection;
+use std::time::Duration;
+use tokio::fs;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Sleep while the probe is disabled, so enabling it applies without restart
+const DISABLED_RECHECK: Duration = Duration::from_mins(15);
+
+#[must_use]
+pub fn start_mirror_health_job(
+    db: DatabaseConnection,
+    report: MirrorHealthReport,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let interval: SettingsEntry<u64> =
+                ApplicationSettings::get(Setting::MirrorHealthInterval, None, &db).await;
+            if interval.value == 0 {
+                tokio::time::sleep(DISABLED_RECHECK).await;
+                continue;
+            }
+
+            if let Err(e) = check_mirrors(&db, &report).await {
+                warn!("Mirror health check failed: {e}");
+            }
+            tokio::time::sleep(Duration::from_secs(interval.value)).await;
+        }
+    })
+}
+
+/// Probe the servers of the current mirrorlist and demote stale or dead ones
+async fn check_mirrors(db: &DatabaseConnection, report: &MirrorHealthReport) -> anyhow::Result<()> {
+    let mirrorlist_path = mirrorlist_file();
+    let Ok(mirrorlist) = fs::read_to_string(&mirrorlist_path).await else {
+        debug!("No mirrorlist at {mirrorlist_path} to check");
+        return Ok(());
+    };
+    let servers = parse_servers(&mirrorlist);
+    if servers.is_empty() {
+        return Ok(());
+    }
+
+    let max_sync_age: u32 = ApplicationSettings::get(Setting::MirrorHealthMaxSyncAge, None, db)
+        .await
+        .value;
+    let concurrency = rank_options(db).await.concurrency;
+    let mirrors = probe(
+        servers,
+        TimeDelta::hours(i64::from(max_sync_age.max(1))),
+        concurrency,
+    )
+    .await?;
+
+    let count = |state| mirrors.iter().filter(|m| m.state == state).count();
+    let (stale, dead) = (count(MirrorState::Stale), count(MirrorState::Dead));
+    info!(
+        "Mirror health: {} healthy, {stale} stale, {dead} dead",
+        mirrors.len() - stale - dead
+    );
+
+    if let Some(demoted) = demote_unhealthy(&mirrorlist, &mirrors) {
+        // a ranking may have replaced the mirrorlist while probing
+        if fs::read_to_string(&mirrorlist_path).await? == mirrorlist {
+            fs::write(&mirrorlist_path, demoted).await?;
+            info!("Moved dead and stale mirrors to the end of {mirrorlist_path}");
+        }
+    }
+
+    report.set(MirrorHealthCheck {
+        timestamp: Utc::now().timestamp(),
+        mirrors,
+    });
+    Ok(())
+}
@@ -1,47 +1,73 @@
-use aurcache_builder::build_mode::{BuildMode, get_build_mode};
+use aurcache_builder::build_mode::mirrorlist_file;
+use aurcache_metrics::{MIRROR_RANK, MIRROR_RANK_TIMESTAMP};
 use aurcache_types::builder::Action;
+use aurcache_types::settings::{ApplicationSettings, Setting, SettingsEntry};
+use aurcache_utils::mirrors::{mirror_status, rank_options, save_benchmarks};
+use aurcache_utils::settings::general::SettingsTraits;
 use chrono::Utc;
 use cron::Schedule;
 use pacman_mirrors::benchmark::Bench;
 use pacman_mirrors::platforms::Platform;
 use sea_orm::DatabaseConnection;
-use std::env;
 use std::str::FromStr;
 use std::time::Duration;
 use tokio::fs;
 use tokio::sync::broadcast::Sender;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::task::JoinHandle;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+
+/// Longest sleep between schedule checks, so schedule changes apply without restart
+const RECHECK_INTERVAL: Duration = Duration::from_mins(15);
 
 pub fn start_mirror_rank_job(
-    _db: DatabaseConnection,
-    _tx: Sender<Action>,
+    db: DatabaseConnection,
+    tx: Sender<Action>,
 ) -> anyhow::Result<JoinHandle<()>> {
-    let cron_str = env::var("MIRROR_RANK_SCHEDULE").unwrap_or("0 0 2 * * 1".to_string());
-    // This parses the string following this spec: https://www.quartz-scheduler.org/documentation/quartz-2.3.0/tutorials/crontrigger.html
-    let schedule = Schedule::from_str(cron_str.as_str())?;
+    let mut rx = tx.subscribe();
 
     Ok(tokio::spawn(async move {
-        let mut upcoming = schedule.upcoming(Utc);
         loop {
-            // Get the next occurrence from now
-            if let Some(next_time) = upcoming.next() {
-                let now = Utc::now();
-                let duration = next_time
-                    .signed_duration_since(now)
-                    .to_std()
-                    .expect("Time went backwards?");
-                info!(
-                    "Waiting for scheduled mirror ranking until {} ({} seconds)",
-                    next_time,
-                    duration.as_secs()
-                );
+            // check everytime in loop since it may change per user setting
+            let schedule: SettingsEntry<Option<String>> =
+                ApplicationSettings::get(Setting::MirrorRankSchedule, None, &db).await;
+            // This parses the string following this spec: https://www.quartz-scheduler.org/documentation/quartz-2.3.0/tutorials/crontrigger.html
+            let next_time = match schedule.value.as_deref().map(Schedule::from_str) {
+                None => None,
+                Some(Err(e)) => {
+                    warn!("Invalid mirror rank cron expression: {e} -- Retry in 15min");
+                    None
+                }
+                Some(Ok(schedule)) => {
+                    let next_time = schedule.upcoming(Utc).next();
+                    if next_time.is_none() {
+                        warn!("Your defined mirror rank cron-job doesn't have a future schedule");
+                    }
+                    next_time
+                }
+            };
 
-                // Wait until the scheduled time
-                tokio::time::sleep(duration).await;
+            let until_next =
+                next_time.and_then(|t| t.signed_duration_since(Utc::now()).to_std().ok());
+            let due = until_next.is_some_and(|d| d <= RECHECK_INTERVAL);
+            if let Some(next_time) = next_time {
+                debug!("Next scheduled mirror ranking at {next_time}");
+            }
 
-                // Execute your scheduled code
-                match update_mirrorlist().await {
+            let run = tokio::select! {
+                () = tokio::time::sleep(until_next.unwrap_or(RECHECK_INTERVAL).min(RECHECK_INTERVAL)) => due,
+                action = rx.recv() => match action {
+                    Ok(Action::RankMirrors) => {
+                        info!("Mirror ranking triggered manually");
+                        true
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => false,
+                    Err(RecvError::Closed) => return,
+                },
+            };
+
+            if run {
+                match update_mirrorlist(&db).await {
                     Ok(()) => {
                         info!("Mirror ranking finished");
                     }
@@ -49,29 +75,34 @@ pub fn start_mirror_rank_job(
                         warn!("Mirror ranking failed: {e}");
                     }
                 }
-            } else {
-                // If there is no upcoming occurrence (unlikely with cron), wait a default duration before retrying.
-                warn!("Your defined cron-job doesn't have a future schedule: '{cron_str}'");
-                tokio::time::sleep(Duration::from_secs(60 * 30)).await;
             }
         }
     }))
 }
 
-async fn update_mirrorlist() -> anyhow::Result<()> {
+async fn update_mirrorlist(db: &DatabaseConnection) -> anyhow::Result<()> {
     info!("Executing mirror ranking job at: {}", Utc::now());
-    match pacman_mirrors::get_status(Platform::X86_64).await {
+    let options = rank_options(db).await;
+    match mirror_status(db, Platform::X86_64).await {
         Ok(status) => {
-            let mut urls = status.urls;
+            let urls = status.urls;
             info!("Ranking mirrorlist");
-            let mirrors = urls.rank().await?;
-            let mirrorlist = urls.gen_mirrorlist(mirrors)?;
+            let benchmarks = urls.rank(&options).await?;
+            MIRROR_RANK.reset();
+            for (i, benchmark) in benchmarks.iter().enumerate() {
+                MIRROR_RANK
+                    .with_label_values(&[benchmark.mirror.url.as_str()])
+                    .set(i as i64 + 1);
+            }
+            MIRROR_RANK_TIMESTAMP.set(Utc::now().timestamp() as f64);
+            if let Err(e) = save_benchmarks(db, Platform::X86_64, &benchmarks).await {
+                warn!("Failed to store mirror ranking: {e}");
+            }
 
-            let mirrorlist_path = match get_build_mode() {
-                BuildMode::DinD(cfg) => cfg.mirrorlist_path,
-                BuildMode::Host(cfg) => cfg.mirrorlist_path_aurcache,
-            };
-            let mirrorlist_path = format!("{mirrorlist_path}/mirrorlist");
+            let mirrors = benchmarks.into_iter().map(|b| b.mirror).collect();
+            let mirrorlist = urls.gen_mirrorlist(mirrors, &options)?;
+
+            let mirrorlist_path = mirrorlist_file();
             fs::write(mirrorlist_path.as_str(), mirrorlist).await?;
             info!("Wrote mirrorlist to {mirrorlist_path}");
         }
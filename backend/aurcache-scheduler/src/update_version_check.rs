@@ -5,6 +5,7 @@ use aurcache_db::helpers::active_value_ext::ActiveValueExt;
 use aurcache_db::packages::{SourceData, SourceType};
 use aurcache_db::prelude::{Builds, Packages};
 use aurcache_db::{builds, packages};
+use aurcache_metrics::observe_aur_request;
 use aurcache_types::settings::{ApplicationSettings, Setting, SettingsEntry};
 use aurcache_utils::git::checkout::checkout_repo_ref;
 use aurcache_utils::settings::general::SettingsTraits;
@@ -13,7 +14,7 @@ use sea_orm::{
 };
 use sea_orm::{ColumnTrait, QueryFilter, QueryOrder};
 use std::str::FromStr;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tempfile::tempdir;
 use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
@@ -46,9 +47,11 @@ async fn check_versions(db: DatabaseConnection) -> anyhow::Result<()> {
         vec![]
     } else {
         let request = Request::default();
+        let start = Instant::now();
         let response = request
             .search_multi_info_by_names(aur_names.as_slice())
             .await;
+        observe_aur_request("multiinfo", start.elapsed(), &response);
 
         let results: Vec<Package> = response
             .map_err(|_| anyhow!("couldn't download version update"))?
@@ -1,9 +1,13 @@
 use aurcache_db::{builds, packages};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 #[derive(Clone)]
 pub enum Action {
     Build(Box<packages::Model>, Box<builds::Model>),
     Cancel(i32),
+    /// rerank the mirrors outside of the schedule
+    RankMirrors,
 }
 
 #[derive(Clone, Debug)]
@@ -14,4 +18,22 @@ impl BuildStates {
     pub const SUCCESSFUL_BUILD: i32 = 1;
     pub const FAILED_BUILD: i32 = 2;
     pub const ENQUEUED_BUILD: i32 = 3;
+    pub const CANCELED_BUILD: i32 = 4;
+}
+
+#[derive(ToSchema, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// Single finding of namcap for the PKGBUILD or a built package
+#[derive(ToSchema, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct LintFinding {
+    /// package name, or `PKGBUILD (pkgbase)`
+    pub target: String,
+    pub severity: LintSeverity,
+    pub message: String,
 }
@@ -1,2 +1,3 @@
 pub mod builder;
+pub mod package;
 pub mod settings;
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Selects packages for bulk operations, all given criteria have to match
+#[derive(ToSchema, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct PackageFilter {
+    /// Only packages with one of these ids
+    #[serde(default)]
+    pub ids: Option<Vec<i32>>,
+    /// Only packages with a newer upstream version
+    #[serde(default)]
+    pub out_of_date: bool,
+    /// Only packages whose latest build failed
+    #[serde(default)]
+    pub failed: bool,
+    /// Only packages with this entry in their `tags` setting
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+impl PackageFilter {
+    /// A filter without criteria, which would select every package
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_none() && !self.out_of_date && !self.failed && self.tag.is_none()
+    }
+}
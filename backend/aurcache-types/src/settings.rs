@@ -52,6 +52,22 @@ pub enum Setting {
     BuilderImage,
     MakepkgConf,
     PacmanConf,
+    RepoCompression,
+    LintFailOnError,
+    MirrorCountries,
+    MirrorHttpsOnly,
+    MirrorIpv4,
+    MirrorIpv6,
+    MirrorMaxSyncAge,
+    MirrorMinCompletion,
+    MirrorCount,
+    MirrorBenchConcurrency,
+    MirrorBenchSamples,
+    MirrorRankSchedule,
+    MirrorStatusSource,
+    MirrorHealthInterval,
+    MirrorHealthMaxSyncAge,
+    Tags,
 }
 
 impl Setting {
@@ -66,6 +82,22 @@ impl Setting {
             "builder_image" => Some(Self::BuilderImage),
             "makepkg_conf" => Some(Self::MakepkgConf),
             "pacman_conf" => Some(Self::PacmanConf),
+            "repo_compression" => Some(Self::RepoCompression),
+            "lint_fail_on_error" => Some(Self::LintFailOnError),
+            "mirror_countries" => Some(Self::MirrorCountries),
+            "mirror_https_only" => Some(Self::MirrorHttpsOnly),
+            "mirror_ipv4" => Some(Self::MirrorIpv4),
+            "mirror_ipv6" => Some(Self::MirrorIpv6),
+            "mirror_max_sync_age" => Some(Self::MirrorMaxSyncAge),
+            "mirror_min_completion" => Some(Self::MirrorMinCompletion),
+            "mirror_count" => Some(Self::MirrorCount),
+            "mirror_bench_concurrency" => Some(Self::MirrorBenchConcurrency),
+            "mirror_bench_samples" => Some(Self::MirrorBenchSamples),
+            "mirror_rank_schedule" => Some(Self::MirrorRankSchedule),
+            "mirror_status_source" => Some(Self::MirrorStatusSource),
+            "mirror_health_interval" => Some(Self::MirrorHealthInterval),
+            "mirror_health_max_sync_age" => Some(Self::MirrorHealthMaxSyncAge),
+            "tags" => Some(Self::Tags),
             _ => None,
         }
     }
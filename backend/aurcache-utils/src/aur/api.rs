@@ -1,7 +1,8 @@
 use anyhow::anyhow;
 use aur_rs::{Package, Request};
+use aurcache_metrics::observe_aur_request;
 use backon::{FibonacciBuilder, Retryable};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // https://wiki.archlinux.org/title/Aurweb_RPC_interface
 // API rate limit 4000 requests per day
@@ -9,14 +10,19 @@ use std::time::Duration;
 /// Query the AUR for packages matching the given query string
 pub async fn query_aur(query: &str) -> anyhow::Result<Vec<Package>> {
     let request = Request::default();
-    let response = (|| async { request.search_package_by_name(query).await })
-        .retry(
-            FibonacciBuilder::default()
-                .with_min_delay(Duration::from_millis(500))
-                .with_max_times(4),
-        )
-        .await
-        .map_err(|e| anyhow!("failed to get package: {e}"))?;
+    let response = (|| async {
+        let start = Instant::now();
+        let result = request.search_package_by_name(query).await;
+        observe_aur_request("search", start.elapsed(), &result);
+        result
+    })
+    .retry(
+        FibonacciBuilder::default()
+            .with_min_delay(Duration::from_millis(500))
+            .with_max_times(4),
+    )
+    .await
+    .map_err(|e| anyhow!("failed to get package: {e}"))?;
 
     let mut response = response.results;
     response.sort_by(|x, x1| x.popularity.partial_cmp(&x1.popularity).unwrap().reverse());
@@ -28,14 +34,48 @@ pub async fn query_aur(query: &str) -> anyhow::Result<Vec<Package>> {
 /// Returns `None` if the package is not found
 pub async fn get_package_info(pkg_name: &str) -> anyhow::Result<Option<Package>> {
     let request = Request::default();
-    let mut response = (|| async { request.search_info_by_name(pkg_name).await })
+    let mut response = (|| async {
+        let start = Instant::now();
+        let result = request.search_info_by_name(pkg_name).await;
+        observe_aur_request("info", start.elapsed(), &result);
+        result
+    })
+    .retry(
+        FibonacciBuilder::default()
+            .with_min_delay(Duration::from_millis(500))
+            .with_max_times(4),
+    )
+    .await
+    .map_err(|e| anyhow!("failed to get package: {e}"))?;
+
+    Ok(response.results.pop())
+}
+
+/// Maximum number of names per multiinfo request, longer URLs are rejected by the AUR
+const MULTIINFO_CHUNK_SIZE: usize = 150;
+
+/// Retrieve AUR package information for many names with one request per
+/// [`MULTIINFO_CHUNK_SIZE`] names.
+/// Names not found on the AUR are missing in the result
+pub async fn get_packages_info(pkg_names: &[&str]) -> anyhow::Result<Vec<Package>> {
+    let request = Request::default();
+    let mut packages = Vec::with_capacity(pkg_names.len());
+    for chunk in pkg_names.chunks(MULTIINFO_CHUNK_SIZE) {
+        let response = (|| async {
+            let start = Instant::now();
+            let result = request.search_multi_info_by_names(chunk).await;
+            observe_aur_request("multiinfo", start.elapsed(), &result);
+            result
+        })
         .retry(
             FibonacciBuilder::default()
                 .with_min_delay(Duration::from_millis(500))
                 .with_max_times(4),
         )
         .await
-        .map_err(|e| anyhow!("failed to get package: {e}"))?;
+        .map_err(|e| anyhow!("failed to get packages: {e}"))?;
+        packages.extend(response.results);
+    }
 
-    Ok(response.results.pop())
+    Ok(packages)
 }
@@ -0,0 +1,249 @@
+//! Single archive with a database dump, the repo and the config directory.
+
+use crate::repo::lock::lock_repo;
+use anyhow::{anyhow, bail};
+use aurcache_db::backup::{
+    applied_migrations, dump_tables, finish_restore, prepare_restore, restore_table,
+};
+use chrono::Utc;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use pacman_mirrors::platforms::Platforms;
+use sea_orm::{DatabaseConnection, JsonValue, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tar::{Archive, Builder, Header};
+use tracing::{info, warn};
+
+/// Increased on incompatible changes of the archive layout
+const BACKUP_FORMAT: u32 = 1;
+/// First entry of every backup
+const MANIFEST_FILE: &str = "backup.json";
+const DB_DIR: &str = "db";
+/// Directories relative to the working directory which are backed up as they are
+const DATA_DIRS: [&str; 2] = ["repo", "config"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format: u32,
+    /// AURCache version that created the backup
+    pub version: String,
+    /// unix timestamp
+    pub created: i64,
+    /// migrations applied to the dumped database
+    pub migrations: Vec<String>,
+    /// dumped rows per table
+    pub tables: BTreeMap<String, usize>,
+}
+
+fn append_file(
+    builder: &mut Builder<GzEncoder<File>>,
+    path: &str,
+    data: &[u8],
+) -> anyhow::Result<()> {
+    let mut header = Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().try_into()?);
+    header.set_cksum();
+    builder.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+/// Write a backup of the database, `./repo` and `./config` to `output`
+pub async fn create_backup(
+    db: &DatabaseConnection,
+    output: &Path,
+    version: &str,
+) -> anyhow::Result<BackupManifest> {
+    // held until `./repo` is archived, so no build publishes packages in between and
+    // the repo databases, package files and the dumped `files` rows match
+    let mut repo_locks = vec![];
+    for platform in Platforms {
+        let platform = platform.to_string();
+        if Path::new(&format!("./repo/{platform}")).is_dir() {
+            repo_locks.push(lock_repo(&platform).await?);
+        }
+    }
+
+    let migrations = applied_migrations(db).await?;
+    let tables = dump_tables(db).await?;
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT,
+        version: version.to_string(),
+        created: Utc::now().timestamp(),
+        migrations,
+        tables: tables
+            .iter()
+            .map(|t| (t.name.clone(), t.rows.len()))
+            .collect(),
+    };
+
+    // written next to the target first, so a failed backup never replaces a good one
+    let partial = PathBuf::from(format!("{}.part", output.display()));
+    let result = (|| -> anyhow::Result<()> {
+        let mut builder = Builder::new(GzEncoder::new(
+            File::create(&partial)?,
+            Compression::default(),
+        ));
+        // repo.db and repo.files are symlinks to the compressed databases
+        builder.follow_symlinks(false);
+
+        append_file(
+            &mut builder,
+            MANIFEST_FILE,
+            &serde_json::to_vec_pretty(&manifest)?,
+        )?;
+        for table in &tables {
+            let mut jsonl = String::new();
+            for row in &table.rows {
+                jsonl.push_str(&serde_json::to_string(row)?);
+                jsonl.push('\n');
+            }
+            append_file(
+                &mut builder,
+                &format!("{DB_DIR}/{}.jsonl", table.name),
+                jsonl.as_bytes(),
+            )?;
+        }
+        for dir in DATA_DIRS {
+            if Path::new(dir).is_dir() {
+                builder.append_dir_all(dir, dir)?;
+            }
+        }
+        builder.into_inner()?.finish()?;
+        Ok(())
+    })();
+
+    drop(repo_locks);
+
+    match result {
+        Ok(()) => std::fs::rename(&partial, output)?,
+        Err(e) => {
+            _ = std::fs::remove_file(&partial);
+            return Err(e);
+        }
+    }
+    Ok(manifest)
+}
+
+/// Restore a backup into an empty database and migrate it to the current schema.
+/// Files of `./repo` and `./config` are overwritten by the ones of the backup.
+pub async fn restore_backup(
+    db: &DatabaseConnection,
+    archive: &Path,
+) -> anyhow::Result<BackupManifest> {
+    let mut archive = Archive::new(GzDecoder::new(File::open(archive)?));
+    let mut entries = archive.entries()?;
+
+    let manifest: BackupManifest = match entries.next() {
+        Some(entry) => {
+            let entry = entry?;
+            if entry.path()? != Path::new(MANIFEST_FILE) {
+                bail!("Not an AURCache backup, {MANIFEST_FILE} missing");
+            }
+            serde_json::from_reader(entry)?
+        }
+        None => bail!("Backup archive is empty"),
+    };
+    if manifest.format != BACKUP_FORMAT {
+        bail!(
+            "Unsupported backup format {}, expected {BACKUP_FORMAT}",
+            manifest.format
+        );
+    }
+    info!(
+        "Restoring backup of AURCache {} with {} migrations",
+        manifest.version,
+        manifest.migrations.len()
+    );
+    prepare_restore(db, &manifest.migrations).await?;
+
+    let txn = db.begin().await?;
+    for entry in entries {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+
+        if let Some(table) = path
+            .strip_prefix(DB_DIR)
+            .ok()
+            .and_then(Path::to_str)
+            .and_then(|p| p.strip_suffix(".jsonl"))
+        {
+            let mut jsonl = String::new();
+            entry.read_to_string(&mut jsonl)?;
+            let rows = jsonl
+                .lines()
+                .map(serde_json::from_str)
+                .collect::<Result<Vec<JsonValue>, _>>()
+                .map_err(|e| anyhow!("Invalid dump of table {table}: {e}"))?;
+            restore_table(&txn, table, &rows).await?;
+            info!("Restored {} rows of {table}", rows.len());
+        } else if DATA_DIRS.iter().any(|dir| path.starts_with(dir)) {
+            entry.unpack_in(".")?;
+        } else {
+            warn!("Skipping unknown backup entry {}", path.display());
+        }
+    }
+    txn.commit().await?;
+
+    finish_restore(db).await?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aurcache_db::backup::known_migrations;
+    use aurcache_db::init::connect_url;
+    use aurcache_db::packages;
+    use aurcache_db::packages::SourceType;
+    use sea_orm::ActiveModelTrait;
+    use sea_orm::ActiveValue::Set;
+
+    #[tokio::test]
+    async fn archive_round_trip() {
+        let source = connect_url("sqlite::memory:").await.unwrap();
+        prepare_restore(&source, &known_migrations()).await.unwrap();
+        for name in ["foo", "bar"] {
+            packages::ActiveModel {
+                name: Set(name.to_string()),
+                status: Set(1),
+                out_of_date: Set(0),
+                platforms: Set("x86_64".to_string()),
+                build_flags: Set("-B;--noconfirm".to_string()),
+                source_type: Set(SourceType::Aur),
+                source_data: Set(format!(r#"{{"type":"aur","name":"{name}"}}"#)),
+                ..Default::default()
+            }
+            .insert(&source)
+            .await
+            .unwrap();
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("backup.tar.gz");
+        let created = create_backup(&source, &archive, "0.0.0").await.unwrap();
+        assert_eq!(created.tables["packages"], 2);
+
+        let target = connect_url("sqlite::memory:").await.unwrap();
+        let restored = restore_backup(&target, &archive).await.unwrap();
+        assert_eq!(restored.migrations, created.migrations);
+        assert_eq!(restored.tables, created.tables);
+
+        let rows = |tables: Vec<aurcache_db::backup::TableDump>| {
+            tables
+                .into_iter()
+                .map(|t| (t.name, t.rows))
+                .collect::<BTreeMap<_, _>>()
+        };
+        assert_eq!(
+            rows(dump_tables(&target).await.unwrap()),
+            rows(dump_tables(&source).await.unwrap())
+        );
+    }
+}
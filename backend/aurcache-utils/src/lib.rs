@@ -1,5 +1,9 @@
 pub mod aur;
+pub mod backup;
 pub mod git;
+pub mod manifest;
+pub mod mirrors;
 pub mod package;
+pub mod repo;
 pub mod settings;
 pub mod utils;
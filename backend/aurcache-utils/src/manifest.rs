@@ -0,0 +1,809 @@
+//! Declarative list of packages and settings, diffed against the database and applied.
+
+use crate::package::add::package_add_named;
+use crate::package::delete::package_delete;
+use crate::settings::general::SettingsTraits;
+use anyhow::{anyhow, bail};
+use aurcache_db::packages;
+use aurcache_db::packages::{SourceData, SourceType};
+use aurcache_db::prelude::Packages;
+use aurcache_types::builder::Action;
+use aurcache_types::settings::{ApplicationSettings, Setting};
+use pacman_mirrors::platforms::Platform;
+use sea_orm::ActiveValue::{NotSet, Set};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use tokio::sync::broadcast::Sender;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ManifestFormat {
+    #[default]
+    Toml,
+    Yaml,
+}
+
+impl ManifestFormat {
+    #[must_use]
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ManifestFormat::Toml => "application/toml",
+            ManifestFormat::Yaml => "application/yaml",
+        }
+    }
+}
+
+impl FromStr for ManifestFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "toml" => Ok(ManifestFormat::Toml),
+            "yaml" | "yml" => Ok(ManifestFormat::Yaml),
+            _ => bail!("Unknown manifest format: {s}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// global settings by key, e.g. `cpu_limit`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub settings: BTreeMap<String, ManifestValue>,
+    #[serde(default)]
+    pub packages: Vec<PackageManifest>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageManifest {
+    /// pkgbase, for git packages the name from the PKGBUILD
+    pub name: String,
+    #[serde(flatten)]
+    pub source: ManifestSource,
+    /// none keeps the platforms of existing packages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platforms: Option<Vec<String>>,
+    /// none keeps the build flags of existing packages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_flags: Option<Vec<String>>,
+    /// per-package settings by key, e.g. `builder_image`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub settings: BTreeMap<String, ManifestValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum ManifestSource {
+    Aur,
+    Git {
+        url: String,
+        #[serde(rename = "ref")]
+        git_ref: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        subfolder: String,
+    },
+}
+
+/// Setting value, numbers and booleans may be written without quotes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ManifestValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl Display for ManifestValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManifestValue::Bool(v) => write!(f, "{v}"),
+            ManifestValue::Int(v) => write!(f, "{v}"),
+            ManifestValue::Float(v) => write!(f, "{v}"),
+            ManifestValue::String(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl Manifest {
+    pub fn parse(input: &str, format: ManifestFormat) -> anyhow::Result<Self> {
+        let manifest: Manifest = match format {
+            ManifestFormat::Toml => toml::from_str(input)?,
+            ManifestFormat::Yaml => serde_yaml::from_str(input)?,
+        };
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    pub fn serialize(&self, format: ManifestFormat) -> anyhow::Result<String> {
+        Ok(match format {
+            ManifestFormat::Toml => toml::to_string_pretty(self)?,
+            ManifestFormat::Yaml => serde_yaml::to_string(self)?,
+        })
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let mut names = HashMap::new();
+        for package in &self.packages {
+            if names.insert(package.name.as_str(), ()).is_some() {
+                bail!("Package {} is listed twice", package.name);
+            }
+            for platform in package.platforms.iter().flatten() {
+                Platform::from_str(platform).map_err(|e| anyhow!("{}: {e}", package.name))?;
+            }
+        }
+        let keys = self
+            .settings
+            .keys()
+            .chain(self.packages.iter().flat_map(|p| p.settings.keys()));
+        for key in keys {
+            if Setting::from_key(key).is_none() {
+                bail!("Unknown setting key: {key}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fields of a package changed by the manifest, unchanged ones are none
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PackageFields {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platforms: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_flags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceData>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ManifestChange {
+    AddPackage {
+        package: String,
+    },
+    RemovePackage {
+        package: String,
+    },
+    UpdatePackage {
+        package: String,
+        old: PackageFields,
+        new: PackageFields,
+    },
+    /// global setting if `package` is none
+    SetSetting {
+        package: Option<String>,
+        key: String,
+        old: Option<String>,
+        new: String,
+    },
+    ResetSetting {
+        package: Option<String>,
+        key: String,
+        old: String,
+    },
+}
+
+impl ManifestChange {
+    #[must_use]
+    pub fn package(&self) -> Option<&str> {
+        match self {
+            ManifestChange::AddPackage { package }
+            | ManifestChange::RemovePackage { package }
+            | ManifestChange::UpdatePackage { package, .. } => Some(package),
+            ManifestChange::SetSetting { package, .. }
+            | ManifestChange::ResetSetting { package, .. } => package.as_deref(),
+        }
+    }
+}
+
+/// Result of applying a single [`ManifestChange`]
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedChange {
+    #[serde(flatten)]
+    pub change: ManifestChange,
+    /// id of the package the change belongs to, none for global settings
+    #[serde(skip)]
+    pub pkg_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(';')
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn manifest_source(source: &SourceData) -> Option<ManifestSource> {
+    match source {
+        SourceData::Aur { .. } => Some(ManifestSource::Aur),
+        SourceData::Git {
+            url,
+            r#ref,
+            subfolder,
+        } => Some(ManifestSource::Git {
+            url: url.clone(),
+            git_ref: r#ref.clone(),
+            subfolder: subfolder.clone(),
+        }),
+        SourceData::Upload { .. } => None,
+    }
+}
+
+fn source_data(name: &str, source: &ManifestSource) -> SourceData {
+    match source {
+        ManifestSource::Aur => SourceData::Aur {
+            name: name.to_string(),
+        },
+        ManifestSource::Git {
+            url,
+            git_ref,
+            subfolder,
+        } => SourceData::Git {
+            url: url.clone(),
+            r#ref: git_ref.clone(),
+            subfolder: subfolder.clone(),
+        },
+    }
+}
+
+/// Setting changes turning `stored` into `wanted`, stored settings missing in `wanted`
+/// are only reset if `prune` is set
+fn diff_settings(
+    package: Option<&str>,
+    stored: &BTreeMap<String, String>,
+    wanted: &BTreeMap<String, ManifestValue>,
+    prune: bool,
+    changes: &mut Vec<ManifestChange>,
+) {
+    for (key, value) in wanted {
+        let value = value.to_string();
+        if stored.get(key) != Some(&value) {
+            changes.push(ManifestChange::SetSetting {
+                package: package.map(str::to_string),
+                key: key.clone(),
+                old: stored.get(key).cloned(),
+                new: value,
+            });
+        }
+    }
+    if !prune {
+        return;
+    }
+    for (key, old) in stored {
+        // keys unknown to this version are left alone
+        if !wanted.contains_key(key) && Setting::from_key(key).is_some() {
+            changes.push(ManifestChange::ResetSetting {
+                package: package.map(str::to_string),
+                key: key.clone(),
+                old: old.clone(),
+            });
+        }
+    }
+}
+
+/// Changes needed to bring the database in line with `manifest`: removals, additions,
+/// package updates and setting changes in that order.
+///
+/// Packages and stored settings missing in the manifest are only removed and reset with
+/// `prune`, otherwise a partial manifest only adds and updates.
+pub async fn plan(
+    db: &DatabaseConnection,
+    manifest: &Manifest,
+    prune: bool,
+) -> anyhow::Result<Vec<ManifestChange>> {
+    let existing = Packages::find().all(db).await?;
+    let mut changes = vec![];
+
+    for pkg in &existing {
+        if prune && !manifest.packages.iter().any(|p| p.name == pkg.name) {
+            changes.push(ManifestChange::RemovePackage {
+                package: pkg.name.clone(),
+            });
+        }
+    }
+
+    let mut updates = vec![];
+    let mut setting_changes = vec![];
+    for wanted in &manifest.packages {
+        let Some(pkg) = existing.iter().find(|p| p.name == wanted.name) else {
+            changes.push(ManifestChange::AddPackage {
+                package: wanted.name.clone(),
+            });
+            diff_settings(
+                Some(&wanted.name),
+                &BTreeMap::new(),
+                &wanted.settings,
+                prune,
+                &mut setting_changes,
+            );
+            continue;
+        };
+
+        let (mut old, mut new) = (PackageFields::default(), PackageFields::default());
+        let platforms = split_list(&pkg.platforms);
+        if let Some(wanted_platforms) = &wanted.platforms {
+            let mut sorted = wanted_platforms.clone();
+            sorted.sort();
+            let mut current = platforms.clone();
+            current.sort();
+            if sorted != current {
+                old.platforms = Some(platforms);
+                new.platforms = Some(wanted_platforms.clone());
+            }
+        }
+        let build_flags = split_list(&pkg.build_flags);
+        if let Some(wanted_flags) = &wanted.build_flags
+            && *wanted_flags != build_flags
+        {
+            old.build_flags = Some(build_flags);
+            new.build_flags = Some(wanted_flags.clone());
+        }
+        let current_source = SourceData::from_str(&pkg.source_data)?;
+        if manifest_source(&current_source).as_ref() != Some(&wanted.source) {
+            old.source = Some(current_source);
+            new.source = Some(source_data(&wanted.name, &wanted.source));
+        }
+        if new.platforms.is_some() || new.build_flags.is_some() || new.source.is_some() {
+            updates.push(ManifestChange::UpdatePackage {
+                package: wanted.name.clone(),
+                old,
+                new,
+            });
+        }
+
+        let stored = ApplicationSettings::get_stored(db, Some(pkg.id)).await?;
+        diff_settings(
+            Some(&wanted.name),
+            &stored,
+            &wanted.settings,
+            prune,
+            &mut setting_changes,
+        );
+    }
+
+    let stored = ApplicationSettings::get_stored(db, None).await?;
+    diff_settings(
+        None,
+        &stored,
+        &manifest.settings,
+        prune,
+        &mut setting_changes,
+    );
+
+    changes.extend(updates);
+    changes.extend(setting_changes);
+    Ok(changes)
+}
+
+/// Apply `changes` from [`plan`], a failed change doesn't stop the remaining ones
+pub async fn apply(
+    db: &DatabaseConnection,
+    tx: &Sender<Action>,
+    manifest: &Manifest,
+    changes: Vec<ManifestChange>,
+) -> anyhow::Result<Vec<AppliedChange>> {
+    let mut ids: HashMap<String, i32> = Packages::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| (p.name, p.id))
+        .collect();
+
+    let mut applied = Vec::with_capacity(changes.len());
+    for change in changes {
+        let pkg_id = change.package().and_then(|name| ids.get(name).copied());
+        let result = apply_change(db, tx, manifest, &change, pkg_id).await;
+        let pkg_id = match (&change, &result) {
+            (ManifestChange::AddPackage { package }, Ok(Some(id))) => {
+                ids.insert(package.clone(), *id);
+                Some(*id)
+            }
+            _ => pkg_id,
+        };
+        applied.push(AppliedChange {
+            change,
+            pkg_id,
+            error: result.err().map(|e| e.to_string()),
+        });
+    }
+    Ok(applied)
+}
+
+/// Id of the added package for [`ManifestChange::AddPackage`]
+async fn apply_change(
+    db: &DatabaseConnection,
+    tx: &Sender<Action>,
+    manifest: &Manifest,
+    change: &ManifestChange,
+    pkg_id: Option<i32>,
+) -> anyhow::Result<Option<i32>> {
+    let package_id =
+        || pkg_id.ok_or_else(|| anyhow!("Package {} not found", change.package().unwrap_or("")));
+
+    match change {
+        ManifestChange::AddPackage { package } => {
+            let wanted = manifest
+                .packages
+                .iter()
+                .find(|p| p.name == *package)
+                .ok_or_else(|| anyhow!("Package {package} not in manifest"))?;
+            let platforms = wanted
+                .platforms
+                .as_ref()
+                .map(|platforms| {
+                    platforms
+                        .iter()
+                        .map(|p| Platform::from_str(p).map_err(|e| anyhow!(e)))
+                        .collect::<anyhow::Result<Vec<_>>>()
+                })
+                .transpose()?;
+            let settings = wanted
+                .settings
+                .iter()
+                .map(|(key, value)| {
+                    Setting::from_key(key)
+                        .map(|setting| (setting, value.to_string()))
+                        .ok_or_else(|| anyhow!("Unknown setting key: {key}"))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            // a git package named differently would be removed and added again on every apply,
+            // the settings are stored before the first build is enqueued
+            let added = package_add_named(
+                db,
+                tx,
+                platforms,
+                wanted.build_flags.clone(),
+                source_data(&wanted.name, &wanted.source),
+                &wanted.name,
+                settings,
+            )
+            .await?;
+            return Ok(Some(added.id));
+        }
+        ManifestChange::RemovePackage { .. } => package_delete(db, package_id()?).await?,
+        ManifestChange::UpdatePackage { new, .. } => {
+            let source_type = new.source.as_ref().map(|source| match source {
+                SourceData::Aur { .. } => SourceType::Aur,
+                SourceData::Git { .. } => SourceType::Git,
+                SourceData::Upload { .. } => SourceType::Upload,
+            });
+            packages::ActiveModel {
+                id: Set(package_id()?),
+                platforms: new.platforms.clone().map_or(NotSet, |v| Set(v.join(";"))),
+                build_flags: new.build_flags.clone().map_or(NotSet, |v| Set(v.join(";"))),
+                source_type: source_type.map_or(NotSet, Set),
+                source_data: new.source.as_ref().map_or(NotSet, |s| Set(s.to_string())),
+                ..Default::default()
+            }
+            .update(db)
+            .await?;
+        }
+        ManifestChange::SetSetting {
+            package, key, new, ..
+        } => {
+            let setting =
+                Setting::from_key(key).ok_or_else(|| anyhow!("Unknown setting key: {key}"))?;
+            let pkg_id = package.as_ref().map(|_| package_id()).transpose()?;
+            ApplicationSettings::patch(db, [(setting, pkg_id, Some(new.clone()))]).await?;
+        }
+        ManifestChange::ResetSetting { package, key, .. } => {
+            let setting =
+                Setting::from_key(key).ok_or_else(|| anyhow!("Unknown setting key: {key}"))?;
+            let pkg_id = package.as_ref().map(|_| package_id()).transpose()?;
+            ApplicationSettings::patch(db, [(setting, pkg_id, None)]).await?;
+        }
+    }
+    Ok(None)
+}
+
+/// Current packages and stored settings as manifest, uploaded packages are skipped
+pub async fn export(db: &DatabaseConnection) -> anyhow::Result<Manifest> {
+    let to_manifest_values = |stored: BTreeMap<String, String>| {
+        stored
+            .into_iter()
+            .filter(|(key, _)| Setting::from_key(key).is_some())
+            .map(|(key, value)| (key, ManifestValue::String(value)))
+            .collect()
+    };
+
+    let mut packages = vec![];
+    for pkg in Packages::find().all(db).await? {
+        let Some(source) = manifest_source(&SourceData::from_str(&pkg.source_data)?) else {
+            continue;
+        };
+        packages.push(PackageManifest {
+            source,
+            platforms: Some(split_list(&pkg.platforms)),
+            build_flags: Some(split_list(&pkg.build_flags)),
+            settings: to_manifest_values(ApplicationSettings::get_stored(db, Some(pkg.id)).await?),
+            name: pkg.name,
+        });
+    }
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(Manifest {
+        settings: to_manifest_values(ApplicationSettings::get_stored(db, None).await?),
+        packages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aurcache_db::backup::{known_migrations, prepare_restore};
+    use aurcache_types::builder::BuildStates;
+
+    const TOML: &str = r#"
+[settings]
+cpu_limit = 2000
+
+[[packages]]
+name = "paru"
+source = "aur"
+platforms = ["x86_64", "aarch64"]
+
+[packages.settings]
+builder_image = "archlinux:base-devel"
+
+[[packages]]
+name = "foo"
+source = "git"
+url = "https://example.org/foo.git"
+ref = "main"
+"#;
+
+    fn values(entries: &[(&str, ManifestValue)]) -> BTreeMap<String, ManifestValue> {
+        entries
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), v.clone()))
+            .collect()
+    }
+
+    fn stored(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_toml() {
+        let manifest = Manifest::parse(TOML, ManifestFormat::Toml).unwrap();
+        assert_eq!(
+            manifest.settings,
+            values(&[("cpu_limit", ManifestValue::Int(2000))])
+        );
+        assert_eq!(manifest.packages.len(), 2);
+        assert_eq!(manifest.packages[0].source, ManifestSource::Aur);
+        assert_eq!(
+            manifest.packages[0].platforms,
+            Some(vec!["x86_64".to_string(), "aarch64".to_string()])
+        );
+        assert_eq!(manifest.packages[0].build_flags, None);
+        assert_eq!(
+            manifest.packages[1].source,
+            ManifestSource::Git {
+                url: "https://example.org/foo.git".to_string(),
+                git_ref: "main".to_string(),
+                subfolder: String::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_yaml() {
+        let yaml = "
+settings:
+  lint_fail_on_error: true
+packages:
+  - name: paru
+    source: aur
+    build_flags: [-B, --noconfirm]
+";
+        let manifest = Manifest::parse(yaml, ManifestFormat::Yaml).unwrap();
+        assert_eq!(
+            manifest.settings,
+            values(&[("lint_fail_on_error", ManifestValue::Bool(true))])
+        );
+        assert_eq!(
+            manifest.packages[0].build_flags,
+            Some(vec!["-B".to_string(), "--noconfirm".to_string()])
+        );
+    }
+
+    #[test]
+    fn reject_duplicate_packages() {
+        let toml = "
+[[packages]]
+name = \"paru\"
+source = \"aur\"
+
+[[packages]]
+name = \"paru\"
+source = \"aur\"
+";
+        let err = Manifest::parse(toml, ManifestFormat::Toml).unwrap_err();
+        assert_eq!(err.to_string(), "Package paru is listed twice");
+    }
+
+    #[test]
+    fn reject_unknown_setting_keys() {
+        let err =
+            Manifest::parse("[settings]\ncpu_limits = 1\n", ManifestFormat::Toml).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown setting key: cpu_limits");
+
+        let toml = "
+[[packages]]
+name = \"paru\"
+source = \"aur\"
+settings = { no_such_key = \"x\" }
+";
+        let err = Manifest::parse(toml, ManifestFormat::Toml).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown setting key: no_such_key");
+    }
+
+    #[test]
+    fn reject_unknown_fields() {
+        assert!(Manifest::parse("pakages = []\n", ManifestFormat::Toml).is_err());
+        assert!(Manifest::parse("packages:\n  - name: paru\n", ManifestFormat::Yaml).is_err());
+        assert!(
+            Manifest::parse(
+                "packages:\n  - name: paru\n    source: upload\n",
+                ManifestFormat::Yaml
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn reject_bad_platforms() {
+        let toml = "
+[[packages]]
+name = \"paru\"
+source = \"aur\"
+platforms = [\"x86_64\", \"riscv64\"]
+";
+        let err = Manifest::parse(toml, ManifestFormat::Toml).unwrap_err();
+        assert!(err.to_string().starts_with("paru: "), "{err}");
+    }
+
+    #[test]
+    fn diff_settings_without_prune() {
+        let mut changes = vec![];
+        diff_settings(
+            Some("paru"),
+            &stored(&[("cpu_limit", "1000"), ("job_timeout", "60"), ("tags", "x")]),
+            &values(&[
+                ("cpu_limit", ManifestValue::Int(2000)),
+                ("job_timeout", ManifestValue::String("60".to_string())),
+                ("builder_image", ManifestValue::String("img".to_string())),
+            ]),
+            false,
+            &mut changes,
+        );
+
+        let changes: Vec<_> = changes
+            .iter()
+            .map(|c| match c {
+                ManifestChange::SetSetting {
+                    package,
+                    key,
+                    old,
+                    new,
+                } => (package.clone(), key.as_str(), old.clone(), new.as_str()),
+                other => panic!("unexpected change {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (Some("paru".to_string()), "builder_image", None, "img"),
+                (
+                    Some("paru".to_string()),
+                    "cpu_limit",
+                    Some("1000".to_string()),
+                    "2000"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_settings_with_prune() {
+        let mut changes = vec![];
+        diff_settings(
+            None,
+            &stored(&[
+                ("cpu_limit", "1000"),
+                ("job_timeout", "60"),
+                ("removed_in_newer_version", "1"),
+            ]),
+            &values(&[("cpu_limit", ManifestValue::Int(1000))]),
+            true,
+            &mut changes,
+        );
+
+        assert_eq!(changes.len(), 1);
+        let ManifestChange::ResetSetting { package, key, old } = &changes[0] else {
+            panic!("unexpected change {:?}", changes[0]);
+        };
+        assert_eq!(
+            (package.as_deref(), key.as_str(), old.as_str()),
+            (None, "job_timeout", "60")
+        );
+    }
+
+    #[tokio::test]
+    async fn export_round_trip() {
+        let db = aurcache_db::init::connect_url("sqlite::memory:")
+            .await
+            .unwrap();
+        prepare_restore(&db, &known_migrations()).await.unwrap();
+
+        let sources = [
+            SourceData::Aur {
+                name: "paru".to_string(),
+            },
+            SourceData::Git {
+                url: "https://example.org/foo.git".to_string(),
+                r#ref: "main".to_string(),
+                subfolder: "foo".to_string(),
+            },
+        ];
+        let mut ids = vec![];
+        for source in sources {
+            let (name, source_type) = match &source {
+                SourceData::Aur { name } => (name.clone(), SourceType::Aur),
+                _ => ("foo".to_string(), SourceType::Git),
+            };
+            let pkg = packages::ActiveModel {
+                name: Set(name),
+                status: Set(BuildStates::SUCCESSFUL_BUILD),
+                out_of_date: Set(0),
+                platforms: Set("x86_64;aarch64".to_string()),
+                build_flags: Set("-B;--noconfirm".to_string()),
+                source_type: Set(source_type),
+                source_data: Set(source.to_string()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+            ids.push(pkg.id);
+        }
+        ApplicationSettings::patch(
+            &db,
+            [
+                (Setting::CpuLimit, None, Some("2000".to_string())),
+                (Setting::LintFailOnError, None, Some("true".to_string())),
+                (Setting::BuilderImage, Some(ids[0]), Some("img".to_string())),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let exported = export(&db).await.unwrap();
+        assert_eq!(
+            exported
+                .packages
+                .iter()
+                .map(|p| &p.name)
+                .collect::<Vec<_>>(),
+            ["foo", "paru"]
+        );
+        for format in [ManifestFormat::Toml, ManifestFormat::Yaml] {
+            let text = exported.serialize(format).unwrap();
+            let parsed = Manifest::parse(&text, format).unwrap();
+            assert_eq!(parsed, exported, "{format:?}:\n{text}");
+            // applying the export is a no-op, even when pruning
+            assert!(plan(&db, &parsed, true).await.unwrap().is_empty());
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::settings::general::SettingsTraits;
+use aurcache_db::mirror_benchmarks;
+use aurcache_db::prelude::MirrorBenchmarks;
+use aurcache_types::settings::{ApplicationSettings, Setting};
+use chrono::{TimeDelta, Utc};
+use pacman_mirrors::benchmark::MirrorBenchmark;
+use pacman_mirrors::health::MirrorHealth;
+use pacman_mirrors::platforms::Platform;
+use pacman_mirrors::{RankOptions, Status, StatusSource};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set, TransactionTrait};
+use std::env;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tracing::warn;
+
+/// Whether the mirrorlist is set via `MIRRORLIST_SERVERS_X86_64`, which disables the ranking
+#[must_use]
+pub fn mirrorlist_override() -> bool {
+    env::var("MIRRORLIST_SERVERS_X86_64").is_ok_and(|s| !s.trim().is_empty())
+}
+
+/// Result of a mirror health probe of the current mirrorlist
+#[derive(Debug, Clone)]
+pub struct MirrorHealthCheck {
+    /// unix timestamp of the probe
+    pub timestamp: i64,
+    /// probed servers in mirrorlist order before demotion
+    pub mirrors: Vec<MirrorHealth>,
+}
+
+/// Last mirror health probe, shared between the scheduler and the `/health` endpoint
+#[derive(Debug, Clone, Default)]
+pub struct MirrorHealthReport(Arc<RwLock<Option<MirrorHealthCheck>>>);
+
+impl MirrorHealthReport {
+    pub fn set(&self, check: MirrorHealthCheck) {
+        if let Ok(mut report) = self.0.write() {
+            *report = Some(check);
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self) -> Option<MirrorHealthCheck> {
+        self.0.read().ok().and_then(|report| report.clone())
+    }
+}
+
+/// Last successfully fetched mirror status, used when the status source is unreachable
+fn status_cache(platform: Platform) -> PathBuf {
+    PathBuf::from(format!("./config/mirror_status_{platform}.json"))
+}
+
+/// Mirror status of `platform` from the configured `MIRROR_STATUS_SOURCE`,
+/// falling back to the cached status of the last successful fetch
+pub async fn mirror_status(db: &DatabaseConnection, platform: Platform) -> anyhow::Result<Status> {
+    let source: String = ApplicationSettings::get(Setting::MirrorStatusSource, None, db)
+        .await
+        .value;
+    let source = source.parse().unwrap_or_else(|e| {
+        warn!("Invalid mirror status source {source:?}: {e} -- Using the default source");
+        StatusSource::Default
+    });
+    Status::get_from_source(platform, &source, Some(&status_cache(platform))).await
+}
+
+/// Mirror ranking filters configured in the global settings
+pub async fn rank_options(db: &DatabaseConnection) -> RankOptions {
+    let countries: String = ApplicationSettings::get(Setting::MirrorCountries, None, db)
+        .await
+        .value;
+    let max_sync_age: u32 = ApplicationSettings::get(Setting::MirrorMaxSyncAge, None, db)
+        .await
+        .value;
+    let min_completion: u32 = ApplicationSettings::get(Setting::MirrorMinCompletion, None, db)
+        .await
+        .value;
+    let count: u32 = ApplicationSettings::get(Setting::MirrorCount, None, db)
+        .await
+        .value;
+    let concurrency: u32 = ApplicationSettings::get(Setting::MirrorBenchConcurrency, None, db)
+        .await
+        .value;
+    let samples: u32 = ApplicationSettings::get(Setting::MirrorBenchSamples, None, db)
+        .await
+        .value;
+
+    RankOptions {
+        countries: RankOptions::parse_countries(&countries),
+        https_only: ApplicationSettings::get(Setting::MirrorHttpsOnly, None, db)
+            .await
+            .value,
+        ipv4: ApplicationSettings::get(Setting::MirrorIpv4, None, db)
+            .await
+            .value,
+        ipv6: ApplicationSettings::get(Setting::MirrorIpv6, None, db)
+            .await
+            .value,
+        max_sync_age: (max_sync_age > 0).then(|| TimeDelta::hours(i64::from(max_sync_age))),
+        min_completion: (min_completion > 0).then(|| f64::from(min_completion.min(100)) / 100.0),
+        count: count.max(1) as usize,
+        concurrency: concurrency.max(1) as usize,
+        samples: samples.max(1) as usize,
+    }
+}
+
+/// Replace the stored ranking of `platform` with `benchmarks`, which are sorted best first
+pub async fn save_benchmarks(
+    db: &DatabaseConnection,
+    platform: Platform,
+    benchmarks: &[MirrorBenchmark],
+) -> anyhow::Result<()> {
+    let timestamp = Utc::now().timestamp();
+    let txn = db.begin().await?;
+
+    MirrorBenchmarks::delete_many()
+        .filter(mirror_benchmarks::Column::Platform.eq(platform.as_str()))
+        .exec(&txn)
+        .await?;
+    if !benchmarks.is_empty() {
+        MirrorBenchmarks::insert_many(benchmarks.iter().enumerate().map(|(i, benchmark)| {
+            mirror_benchmarks::ActiveModel {
+                platform: Set(platform.to_string()),
+                rank: Set(i32::try_from(i + 1).unwrap_or(i32::MAX)),
+                url: Set(benchmark.mirror.url.to_string()),
+                country: Set(benchmark.mirror.country.kind.to_string()),
+                protocol: Set(format!("{:?}", benchmark.mirror.protocol).to_lowercase()),
+                throughput: Set(benchmark.throughput),
+                latency: Set(benchmark.latency),
+                upstream_score: Set(benchmark.mirror.score),
+                score: Set(benchmark.score),
+                timestamp: Set(timestamp),
+                ..Default::default()
+            }
+        }))
+        .exec(&txn)
+        .await?;
+    }
+
+    txn.commit().await?;
+    Ok(())
+}
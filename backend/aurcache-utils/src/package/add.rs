@@ -1,12 +1,16 @@
 use crate::aur::api::get_package_info;
 use crate::git::checkout::checkout_repo_ref;
+use crate::package::delete::package_delete;
+use crate::settings::general::SettingsTraits;
 use alpm_srcinfo::SourceInfoV1;
 use anyhow::{anyhow, bail};
+use aur_rs::Package;
 use aurcache_db::helpers::active_value_ext::ActiveValueExt;
 use aurcache_db::packages::{SourceData, SourceType};
 use aurcache_db::prelude::Packages;
 use aurcache_db::{builds, packages};
 use aurcache_types::builder::{Action, BuildStates};
+use aurcache_types::settings::{ApplicationSettings, Setting};
 use pacman_mirrors::platforms::{Platform, Platforms};
 use sea_orm::QueryFilter;
 use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set, TransactionTrait};
@@ -21,7 +25,103 @@ pub async fn package_add(
     platforms: Option<Vec<Platform>>,
     build_flags: Option<Vec<String>>,
     source_data: SourceData,
-) -> anyhow::Result<String> {
+) -> anyhow::Result<packages::Model> {
+    add_package(db, tx, platforms, build_flags, source_data, None, None).await
+}
+
+/// Same as [`package_add`], but fails before anything is stored if the package
+/// isn't named `name`, e.g. because the pkgbase of a git PKGBUILD differs.
+///
+/// `settings` are stored for the new package before its builds are enqueued,
+/// so even the first build uses them.
+pub async fn package_add_named(
+    db: &DatabaseConnection,
+    tx: &Sender<Action>,
+    platforms: Option<Vec<Platform>>,
+    build_flags: Option<Vec<String>>,
+    source_data: SourceData,
+    name: &str,
+    settings: Vec<(Setting, String)>,
+) -> anyhow::Result<packages::Model> {
+    let new_package =
+        create_package(db, platforms, build_flags, source_data, None, Some(name)).await?;
+
+    let pkg_id = *new_package.model.id.get()?;
+    let stored = ApplicationSettings::patch(
+        db,
+        settings
+            .into_iter()
+            .map(|(setting, value)| (setting, Some(pkg_id), Some(value))),
+    )
+    .await;
+    if let Err(e) = stored {
+        // without its settings the package must not be built
+        package_delete(db, pkg_id).await?;
+        return Err(e);
+    }
+
+    enqueue_builds(db, tx, new_package).await
+}
+
+/// Add an AUR package whose info was already fetched, e.g. by a multiinfo request
+pub async fn package_add_aur(
+    db: &DatabaseConnection,
+    tx: &Sender<Action>,
+    platforms: Option<Vec<Platform>>,
+    build_flags: Option<Vec<String>>,
+    aur_info: Package,
+) -> anyhow::Result<packages::Model> {
+    let source_data = SourceData::Aur {
+        name: aur_info.name.clone(),
+    };
+    add_package(
+        db,
+        tx,
+        platforms,
+        build_flags,
+        source_data,
+        Some(aur_info),
+        None,
+    )
+    .await
+}
+
+async fn add_package(
+    db: &DatabaseConnection,
+    tx: &Sender<Action>,
+    platforms: Option<Vec<Platform>>,
+    build_flags: Option<Vec<String>>,
+    source_data: SourceData,
+    aur_info: Option<Package>,
+    expected_name: Option<&str>,
+) -> anyhow::Result<packages::Model> {
+    let new_package = create_package(
+        db,
+        platforms,
+        build_flags,
+        source_data,
+        aur_info,
+        expected_name,
+    )
+    .await?;
+    enqueue_builds(db, tx, new_package).await
+}
+
+/// Stored package whose builds are not enqueued yet
+struct NewPackage {
+    model: packages::ActiveModel,
+    version: String,
+    platforms: Vec<Platform>,
+}
+
+async fn create_package(
+    db: &DatabaseConnection,
+    platforms: Option<Vec<Platform>>,
+    build_flags: Option<Vec<String>>,
+    source_data: SourceData,
+    aur_info: Option<Package>,
+    expected_name: Option<&str>,
+) -> anyhow::Result<NewPackage> {
     let platforms = match platforms {
         None => vec![Platform::X86_64],
         Some(platforms) => {
@@ -42,7 +142,7 @@ pub async fn package_add(
         .collect::<Vec<_>>()
         .join(";");
 
-    let (mut new_package, new_version) = match source_data {
+    let (model, version) = match source_data {
         SourceData::Aur { ref name } => {
             // remove leading and trailing whitespaces
             let pkg_name = name.trim();
@@ -66,9 +166,12 @@ pub async fn package_add(
                 ]
             });
 
-            let pkg = get_package_info(pkg_name)
-                .await?
-                .ok_or(anyhow!("Package not found"))?;
+            let pkg = match aur_info {
+                Some(pkg) => pkg,
+                None => get_package_info(pkg_name)
+                    .await?
+                    .ok_or(anyhow!("Package not found"))?,
+            };
 
             let new_package = packages::ActiveModel {
                 name: Set(pkg_name.to_string()),
@@ -100,6 +203,11 @@ pub async fn package_add(
             let pkgbasee_name = sourceinfo.base.name;
 
             let pkg_name = pkgbasee_name.to_string();
+            if let Some(expected) = expected_name
+                && expected != pkg_name
+            {
+                bail!("PKGBUILD has pkgbase {pkg_name}, expected {expected}");
+            }
 
             if Packages::find()
                 .filter(packages::Column::Name.eq(pkg_name))
@@ -133,10 +241,29 @@ pub async fn package_add(
             (new_package.save(db).await?, pkgbase_version)
         }
         SourceData::Upload { .. } => {
-            todo!("upload")
+            bail!("Upload packages are not supported yet")
         }
     };
 
+    Ok(NewPackage {
+        model,
+        version,
+        platforms,
+    })
+}
+
+/// Create and enqueue a build of `new_package` for each of its platforms
+async fn enqueue_builds(
+    db: &DatabaseConnection,
+    tx: &Sender<Action>,
+    new_package: NewPackage,
+) -> anyhow::Result<packages::Model> {
+    let NewPackage {
+        model: mut new_package,
+        version: new_version,
+        platforms,
+    } = new_package;
+
     // trigger new build for each platform
     for platform in platforms {
         let txn = db.begin().await?;
@@ -172,7 +299,7 @@ pub async fn package_add(
         ));
     }
 
-    Ok(new_package.name.get()?.clone())
+    Ok(new_package.try_into_model()?)
 }
 
 fn check_platforms(platforms: &Vec<Platform>) -> anyhow::Result<()> {
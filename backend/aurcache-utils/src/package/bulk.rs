@@ -0,0 +1,132 @@
+//! Operations on many packages at once, with one result per package.
+//! AUR info is fetched with batched multiinfo requests instead of one per package.
+
+use crate::aur::api::get_packages_info;
+use crate::package::add::package_add_aur;
+use crate::package::update::{package_update, package_update_to};
+use crate::settings::meta::SettingsMetaTrait;
+use anyhow::{anyhow, bail};
+use aurcache_db::packages::SourceType;
+use aurcache_db::prelude::{Packages, Settings};
+use aurcache_db::{packages, settings};
+use aurcache_types::builder::{Action, BuildStates};
+use aurcache_types::package::PackageFilter;
+use aurcache_types::settings::Setting;
+use pacman_mirrors::platforms::Platform;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::broadcast::Sender;
+
+/// Entries of a comma separated `tags` setting
+pub fn split_tags(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim).filter(|t| !t.is_empty())
+}
+
+/// All packages matching the filter, ordered by name
+pub async fn select_packages(
+    db: &DatabaseConnection,
+    filter: &PackageFilter,
+) -> anyhow::Result<Vec<packages::Model>> {
+    if filter.is_empty() {
+        bail!("Empty filter, select packages by ids, out_of_date, failed or tag");
+    }
+
+    let mut query = Packages::find();
+    if let Some(ids) = &filter.ids {
+        query = query.filter(packages::Column::Id.is_in(ids.clone()));
+    }
+    if filter.out_of_date {
+        query = query.filter(packages::Column::OutOfDate.eq(1));
+    }
+    if filter.failed {
+        query = query.filter(packages::Column::Status.eq(BuildStates::FAILED_BUILD));
+    }
+    if let Some(tag) = &filter.tag {
+        let tagged: Vec<i32> = Settings::find()
+            .filter(settings::Column::Key.eq(Setting::Tags.meta().key))
+            .all(db)
+            .await?
+            .into_iter()
+            .filter(|row| {
+                row.value
+                    .as_deref()
+                    .is_some_and(|v| split_tags(v).any(|t| t == tag.trim()))
+            })
+            .filter_map(|row| row.pkg_id)
+            .collect();
+        query = query.filter(packages::Column::Id.is_in(tagged));
+    }
+
+    Ok(query.order_by_asc(packages::Column::Name).all(db).await?)
+}
+
+/// Add many AUR packages, all names are validated with batched AUR requests.
+/// Duplicate names are only added once.
+pub async fn package_add_many(
+    db: &DatabaseConnection,
+    tx: &Sender<Action>,
+    names: &[String],
+    platforms: Option<Vec<Platform>>,
+    build_flags: Option<Vec<String>>,
+) -> anyhow::Result<Vec<(String, anyhow::Result<packages::Model>)>> {
+    let mut seen = HashSet::new();
+    let names: Vec<&str> = names
+        .iter()
+        .map(|n| n.trim())
+        .filter(|n| !n.is_empty() && seen.insert(*n))
+        .collect();
+
+    let mut infos: HashMap<String, _> = get_packages_info(&names)
+        .await?
+        .into_iter()
+        .map(|p| (p.name.clone(), p))
+        .collect();
+
+    let mut results = Vec::with_capacity(names.len());
+    for name in names {
+        let result = match infos.remove(name) {
+            Some(info) => {
+                package_add_aur(db, tx, platforms.clone(), build_flags.clone(), info).await
+            }
+            None => Err(anyhow!("Package not found")),
+        };
+        results.push((name.to_string(), result));
+    }
+    Ok(results)
+}
+
+/// Trigger an update of each package, upstream versions of AUR packages are fetched
+/// with batched AUR requests.
+pub async fn package_update_many(
+    db: &DatabaseConnection,
+    tx: &Sender<Action>,
+    pkgs: Vec<packages::Model>,
+    force: bool,
+) -> anyhow::Result<Vec<(packages::Model, anyhow::Result<Vec<i32>>)>> {
+    let aur_names: Vec<&str> = pkgs
+        .iter()
+        .filter(|p| p.source_type == SourceType::Aur)
+        .map(|p| p.name.as_str())
+        .collect();
+    let versions: HashMap<String, String> = get_packages_info(&aur_names)
+        .await?
+        .into_iter()
+        .map(|p| (p.name, p.version))
+        .collect();
+
+    let mut results = Vec::with_capacity(pkgs.len());
+    for pkg in pkgs {
+        let result = if pkg.source_type == SourceType::Aur {
+            match versions.get(&pkg.name) {
+                Some(version) => {
+                    package_update_to(db, pkg.clone(), version.clone(), force, tx).await
+                }
+                None => Err(anyhow!("Package not found")),
+            }
+        } else {
+            package_update(db, pkg.clone(), force, tx).await
+        };
+        results.push((pkg, result));
+    }
+    Ok(results)
+}
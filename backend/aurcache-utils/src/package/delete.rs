@@ -1,10 +1,11 @@
-use crate::utils::remove_archive_file::try_remove_archive_file;
+use crate::utils::remove_archive_file::{release_archive_file, remove_archive_files};
 use anyhow::anyhow;
 use aurcache_db::prelude::{Builds, Packages, PackagesFiles, Settings};
 use aurcache_db::{builds, files, packages_files, settings};
 use sea_orm::{ColumnTrait, QuerySelect, RelationTrait};
 use sea_orm::{DatabaseConnection, EntityTrait, ModelTrait, TransactionTrait};
 use sea_orm::{JoinType, QueryFilter};
+use std::collections::BTreeMap;
 
 pub async fn package_delete(db: &DatabaseConnection, pkg_id: i32) -> anyhow::Result<()> {
     let txn = db.begin().await?;
@@ -34,14 +35,16 @@ pub async fn package_delete(db: &DatabaseConnection, pkg_id: i32) -> anyhow::Res
         .all(&txn)
         .await?;
 
+    // archives no other package uses, per platform
+    let mut removed_files: BTreeMap<String, Vec<String>> = BTreeMap::new();
     for (pf, file) in package_files {
         pf.delete(&txn).await?;
 
-        try_remove_archive_file(
-            file.ok_or(anyhow!("package id has no attached file"))?,
-            &txn,
-        )
-        .await?;
+        let file = file.ok_or(anyhow!("package id has no attached file"))?;
+        let platform = file.platform.clone();
+        if let Some(filename) = release_archive_file(file, &txn).await? {
+            removed_files.entry(platform).or_default().push(filename);
+        }
     }
 
     // delete corresponding settings entries
@@ -52,5 +55,10 @@ pub async fn package_delete(db: &DatabaseConnection, pkg_id: i32) -> anyhow::Res
 
     txn.commit().await?;
 
+    // only touch the repo once the rows are gone, a failed commit keeps every file
+    for (platform, filenames) in removed_files {
+        remove_archive_files(&platform, filenames).await?;
+    }
+
     Ok(())
 }
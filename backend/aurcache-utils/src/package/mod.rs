@@ -1,3 +1,4 @@
 pub mod add;
+pub mod bulk;
 pub mod delete;
 pub mod update;
@@ -1,6 +1,6 @@
 use crate::aur::api::get_package_info;
 use anyhow::{anyhow, bail};
-use aurcache_activitylog::activity_utils::ActivityLog;
+use aurcache_activitylog::activity_utils::{ActivityLog, ActivityMeta};
 use aurcache_activitylog::package_update_activity::PackageUpdateActivity;
 use aurcache_db::activities::ActivityType;
 use aurcache_db::packages::SourceData;
@@ -53,7 +53,7 @@ pub async fn package_update_all_outdated(
                         forced: false,
                     },
                     ActivityType::UpdatePackage,
-                    Some("Server".to_string()),
+                    ActivityMeta::server(Some(pkg.id)),
                 )
                 .await?;
             ids_total.append(&mut ids);
@@ -88,8 +88,6 @@ pub async fn package_update(
     force: bool,
     tx: &Sender<Action>,
 ) -> anyhow::Result<Vec<i32>> {
-    let txn = db.begin().await?;
-
     let source_data = SourceData::from_str(pkg_model.source_data.as_str())?;
     let upstream_version = match source_data {
         SourceData::Aur { .. } => {
@@ -107,6 +105,20 @@ pub async fn package_update(
         }
     };
 
+    package_update_to(db, pkg_model, upstream_version, force, tx).await
+}
+
+/// Same as [`package_update`] with an already known upstream version,
+/// e.g. fetched for many packages by batched multiinfo requests.
+pub async fn package_update_to(
+    db: &DatabaseConnection,
+    pkg_model: packages::Model,
+    upstream_version: String,
+    force: bool,
+    tx: &Sender<Action>,
+) -> anyhow::Result<Vec<i32>> {
+    let txn = db.begin().await?;
+
     // get the latest build
     let latest_build = Builds::find()
         .filter(builds::Column::PkgId.eq(pkg_model.id))
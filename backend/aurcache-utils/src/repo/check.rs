@@ -0,0 +1,181 @@
+use crate::repo::lock::lock_repo;
+use anyhow::anyhow;
+use aurcache_db::prelude::{Files, PackagesFiles};
+use aurcache_db::{files, packages_files};
+use pacman_mirrors::platforms::Platforms;
+use pacman_repo_utils::repo_check::repo_check;
+use pacman_repo_utils::repo_database::lock::RepoLock;
+use pacman_repo_utils::repo_rebuild::{package_files, repo_rebuild_locked};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, TransactionTrait};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// Consistency report of the repo of one platform
+#[derive(Debug, Clone, Default)]
+pub struct PlatformCheck {
+    pub platform: String,
+    /// package archives on disk which are not tracked in the `files` table
+    pub orphan_archives: Vec<String>,
+    /// `files` rows whose archive doesn't exist on disk
+    pub missing_files: Vec<String>,
+    /// package archives on disk without an entry in repo.db
+    pub unindexed_files: Vec<String>,
+    /// repo.db entries whose archive doesn't exist on disk
+    pub stale_entries: Vec<String>,
+    /// repo.db entries with a checksum not matching the archive on disk
+    pub checksum_mismatches: Vec<String>,
+}
+
+impl PlatformCheck {
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.orphan_archives.is_empty()
+            && self.missing_files.is_empty()
+            && self.unindexed_files.is_empty()
+            && self.stale_entries.is_empty()
+            && self.checksum_mismatches.is_empty()
+    }
+}
+
+/// Which classes of problems [`repair_repo`] should fix
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RepairOptions {
+    /// delete package archives which are not tracked in the database
+    pub orphan_archives: bool,
+    /// delete `files` rows (and their package links) whose archive is gone
+    pub missing_files: bool,
+    /// rebuild repo.db and repo.files from the archives on disk
+    pub repo_database: bool,
+}
+
+pub(crate) fn repo_dir(platform: &str) -> PathBuf {
+    PathBuf::from(format!("./repo/{platform}"))
+}
+
+/// Check the repos of all platforms against the database
+pub async fn check_repo(db: &DatabaseConnection) -> anyhow::Result<Vec<PlatformCheck>> {
+    let mut checks = vec![];
+    for platform in Platforms {
+        let platform = platform.to_string();
+        let Some(_lock) = lock_existing_repo(&platform).await? else {
+            checks.push(PlatformCheck {
+                platform,
+                ..Default::default()
+            });
+            continue;
+        };
+        checks.push(check_platform(db, &platform).await?);
+    }
+    Ok(checks)
+}
+
+/// Lock of the repo of `platform`, `None` if the platform has no repo yet
+async fn lock_existing_repo(platform: &str) -> anyhow::Result<Option<RepoLock>> {
+    if !repo_dir(platform).exists() {
+        return Ok(None);
+    }
+    Ok(Some(lock_repo(platform).await?))
+}
+
+/// The caller has to hold the repo lock, a build being published would show up as orphan otherwise
+async fn check_platform(db: &DatabaseConnection, platform: &str) -> anyhow::Result<PlatformCheck> {
+    let dir = repo_dir(platform);
+    let tracked: BTreeSet<String> = Files::find()
+        .filter(files::Column::Platform.eq(platform))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|f| f.filename)
+        .collect();
+
+    let (on_disk, repo) = {
+        let dir = dir.clone();
+        tokio::task::spawn_blocking(move || {
+            let on_disk: BTreeSet<String> = package_files(&dir)?.into_iter().collect();
+            let db_archive = dir.join("repo.db");
+            let repo = repo_check(&dir, &db_archive.to_string_lossy())?;
+            anyhow::Ok((on_disk, repo))
+        })
+        .await
+        .map_err(|e| anyhow!(e))??
+    };
+
+    Ok(PlatformCheck {
+        platform: platform.to_string(),
+        orphan_archives: on_disk.difference(&tracked).cloned().collect(),
+        missing_files: tracked.difference(&on_disk).cloned().collect(),
+        unindexed_files: repo.unindexed_files,
+        stale_entries: repo.missing_files,
+        checksum_mismatches: repo.checksum_mismatches,
+    })
+}
+
+/// Fix the selected classes of problems and return the reports after the repair
+pub async fn repair_repo(
+    db: &DatabaseConnection,
+    options: RepairOptions,
+) -> anyhow::Result<Vec<PlatformCheck>> {
+    for platform in Platforms {
+        let platform = platform.to_string();
+        // held for check and repair, so no build publishes files in between
+        let Some(lock) = lock_existing_repo(&platform).await? else {
+            continue;
+        };
+        let check = check_platform(db, &platform).await?;
+        let dir = repo_dir(&check.platform);
+
+        if options.orphan_archives {
+            for filename in &check.orphan_archives {
+                match fs::remove_file(dir.join(filename)) {
+                    Ok(()) => info!("Removed orphan archive: {}/{filename}", check.platform),
+                    Err(e) => warn!("Failed to remove orphan archive {filename}: {e}"),
+                }
+            }
+        }
+
+        if options.missing_files && !check.missing_files.is_empty() {
+            let txn = db.begin().await?;
+            let missing = Files::find()
+                .filter(files::Column::Platform.eq(check.platform.clone()))
+                .filter(files::Column::Filename.is_in(check.missing_files.clone()))
+                .all(&txn)
+                .await?;
+            let ids: Vec<i32> = missing.iter().map(|f| f.id).collect();
+            PackagesFiles::delete_many()
+                .filter(packages_files::Column::FileId.is_in(ids.clone()))
+                .exec(&txn)
+                .await?;
+            Files::delete_many()
+                .filter(files::Column::Id.is_in(ids))
+                .exec(&txn)
+                .await?;
+            txn.commit().await?;
+            info!(
+                "Removed {} file rows without archive for {}",
+                missing.len(),
+                check.platform
+            );
+        }
+
+        // rebuild last so the database reflects the cleaned up directory
+        if options.repo_database {
+            let result = tokio::task::spawn_blocking(move || {
+                repo_rebuild_locked(
+                    &lock,
+                    &dir,
+                    dir.join("repo.db").to_string_lossy().to_string(),
+                    dir.join("repo.files").to_string_lossy().to_string(),
+                )
+            })
+            .await
+            .map_err(|e| anyhow!(e))??;
+            for (filename, e) in result.failed {
+                warn!("Package {filename} couldn't be added to the rebuilt repo: {e}");
+            }
+        }
+    }
+
+    check_repo(db).await
+}
@@ -0,0 +1,49 @@
+use crate::repo::check::repo_dir;
+use pacman_mirrors::platforms::{Platform, Platforms};
+use pacman_repo_utils::repo_files::FilesIndex;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+
+/// platform -> mtime of `repo.files` and its parsed index
+type FilesIndexCache = HashMap<String, (SystemTime, Arc<FilesIndex>)>;
+
+/// Parsed `repo.files` per platform, reloaded once the archive changes on disk
+static FILES_INDEX_CACHE: LazyLock<Mutex<FilesIndexCache>> = LazyLock::new(Mutex::default);
+
+/// Files index of the repo of `platform`, `None` if the platform has no repo yet
+pub async fn files_index(platform: Platform) -> anyhow::Result<Option<Arc<FilesIndex>>> {
+    let platform = platform.as_str();
+    let path = repo_dir(platform).join("repo.files");
+    // metadata follows the symlink, so this is the mtime of the current archive
+    let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) else {
+        return Ok(None);
+    };
+
+    if let Some((mtime, index)) = FILES_INDEX_CACHE.lock().unwrap().get(platform)
+        && *mtime == modified
+    {
+        return Ok(Some(index.clone()));
+    }
+
+    let index = Arc::new(
+        tokio::task::spawn_blocking(move || FilesIndex::load(&path.to_string_lossy())).await??,
+    );
+    FILES_INDEX_CACHE
+        .lock()
+        .unwrap()
+        .insert(platform.to_string(), (modified, index.clone()));
+    Ok(Some(index))
+}
+
+/// Files indexes of all platforms with a repo
+pub async fn files_indexes() -> anyhow::Result<Vec<(String, Arc<FilesIndex>)>> {
+    let mut indexes = vec![];
+    for platform in Platforms {
+        if let Some(index) = files_index(platform).await? {
+            indexes.push((platform.to_string(), index));
+        }
+    }
+    Ok(indexes)
+}
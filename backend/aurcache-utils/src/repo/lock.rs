@@ -0,0 +1,13 @@
+use anyhow::anyhow;
+use pacman_repo_utils::repo_database::lock::RepoLock;
+
+/// Acquire the [`RepoLock`] of `./repo/<platform>` without blocking an async worker.
+///
+/// Hold it while changing package files and the database together, e.g. from copying a
+/// built package until its `files` row is committed, so checks never see half published builds.
+pub async fn lock_repo(platform: &str) -> anyhow::Result<RepoLock> {
+    let db_archive = format!("./repo/{platform}/repo.db");
+    tokio::task::spawn_blocking(move || RepoLock::acquire(db_archive))
+        .await
+        .map_err(|e| anyhow!(e))?
+}
@@ -0,0 +1,3 @@
+pub mod check;
+pub mod files;
+pub mod lock;
@@ -3,6 +3,7 @@ use crate::settings::parser::ParseSetting;
 use aurcache_db::settings;
 use aurcache_types::settings::{ApplicationSettings, Setting, SettingSource, SettingsEntry};
 use sea_orm::*;
+use std::collections::BTreeMap;
 
 const GLOBAL_PKG_ID: i32 = -1;
 
@@ -159,6 +160,20 @@ where
     }
 }
 
+/// Values stored for `pkg_id` or the global ones, without env overrides and defaults
+async fn stored_settings(
+    pkg_id: Option<i32>,
+    db: &DatabaseConnection,
+) -> anyhow::Result<BTreeMap<String, String>> {
+    Ok(settings::Entity::find()
+        .filter(settings::Column::PkgId.eq(pkg_id.unwrap_or(GLOBAL_PKG_ID)))
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|row| Some((row.key, row.value?)))
+        .collect())
+}
+
 pub trait SettingsTraits {
     fn get_all(
         db: &DatabaseConnection,
@@ -169,6 +184,10 @@ pub trait SettingsTraits {
         pkgid: Option<i32>,
         db: &DatabaseConnection,
     ) -> impl Future<Output = SettingsEntry<T>> + Send;
+    fn get_stored(
+        db: &DatabaseConnection,
+        pkgid: Option<i32>,
+    ) -> impl Future<Output = anyhow::Result<BTreeMap<String, String>>> + Send;
     fn patch<I>(
         db: &DatabaseConnection,
         settings: I,
@@ -201,6 +220,13 @@ impl SettingsTraits for ApplicationSettings {
         get_setting(setting, pkgid, db).await
     }
 
+    async fn get_stored(
+        db: &DatabaseConnection,
+        pkgid: Option<i32>,
+    ) -> anyhow::Result<BTreeMap<String, String>> {
+        stored_settings(pkgid, db).await
+    }
+
     async fn patch<I>(db: &DatabaseConnection, settings: I) -> anyhow::Result<()>
     where
         I: IntoIterator<Item = (Setting, Option<i32>, Option<String>)> + Send,
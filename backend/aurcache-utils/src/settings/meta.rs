@@ -52,6 +52,86 @@ impl SettingsMetaTrait for Setting {
                 env_name: None,
                 default: "",
             },
+            Setting::RepoCompression => SettingsMeta {
+                key: "repo_compression",
+                env_name: Some("REPO_COMPRESSION"),
+                default: "gzip",
+            },
+            Setting::LintFailOnError => SettingsMeta {
+                key: "lint_fail_on_error",
+                env_name: Some("LINT_FAIL_ON_ERROR"),
+                default: "false",
+            },
+            Setting::MirrorCountries => SettingsMeta {
+                key: "mirror_countries",
+                env_name: Some("MIRROR_COUNTRIES"),
+                default: "", // all countries
+            },
+            Setting::MirrorHttpsOnly => SettingsMeta {
+                key: "mirror_https_only",
+                env_name: Some("MIRROR_HTTPS_ONLY"),
+                default: "false",
+            },
+            Setting::MirrorIpv4 => SettingsMeta {
+                key: "mirror_ipv4",
+                env_name: Some("MIRROR_IPV4"),
+                default: "false",
+            },
+            Setting::MirrorIpv6 => SettingsMeta {
+                key: "mirror_ipv6",
+                env_name: Some("MIRROR_IPV6"),
+                default: "false",
+            },
+            Setting::MirrorMaxSyncAge => SettingsMeta {
+                key: "mirror_max_sync_age",
+                env_name: Some("MIRROR_MAX_SYNC_AGE"),
+                default: "0", // hours, 0 disables the check
+            },
+            Setting::MirrorMinCompletion => SettingsMeta {
+                key: "mirror_min_completion",
+                env_name: Some("MIRROR_MIN_COMPLETION"),
+                default: "0", // percent
+            },
+            Setting::MirrorCount => SettingsMeta {
+                key: "mirror_count",
+                env_name: Some("MIRROR_COUNT"),
+                default: "10",
+            },
+            Setting::MirrorBenchConcurrency => SettingsMeta {
+                key: "mirror_bench_concurrency",
+                env_name: Some("MIRROR_BENCH_CONCURRENCY"),
+                default: "8",
+            },
+            Setting::MirrorBenchSamples => SettingsMeta {
+                key: "mirror_bench_samples",
+                env_name: Some("MIRROR_BENCH_SAMPLES"),
+                default: "1",
+            },
+            Setting::MirrorRankSchedule => SettingsMeta {
+                key: "mirror_rank_schedule",
+                env_name: Some("MIRROR_RANK_SCHEDULE"),
+                default: "0 0 2 * * 1", // empty disables the ranking
+            },
+            Setting::MirrorStatusSource => SettingsMeta {
+                key: "mirror_status_source",
+                env_name: Some("MIRROR_STATUS_SOURCE"),
+                default: "", // archlinux.org
+            },
+            Setting::MirrorHealthInterval => SettingsMeta {
+                key: "mirror_health_interval",
+                env_name: Some("MIRROR_HEALTH_INTERVAL"),
+                default: "3600", // 0 disables the probe
+            },
+            Setting::MirrorHealthMaxSyncAge => SettingsMeta {
+                key: "mirror_health_max_sync_age",
+                env_name: Some("MIRROR_HEALTH_MAX_SYNC_AGE"),
+                default: "24",
+            },
+            Setting::Tags => SettingsMeta {
+                key: "tags",
+                env_name: None,
+                default: "", // comma separated, used to select packages in bulk operations
+            },
         }
     }
 }
@@ -1,3 +1,3 @@
 pub mod general;
-mod meta;
+pub(crate) mod meta;
 mod parser;
@@ -1,3 +1,4 @@
+use pacman_repo_utils::repo_database::compression::DbCompression;
 use std::str::FromStr;
 
 pub trait ParseSetting: Sized + Clone {
@@ -15,7 +16,7 @@ macro_rules! impl_parse_setting {
     };
 }
 
-impl_parse_setting!(u32, i32, u64, i64, String);
+impl_parse_setting!(u32, i32, u64, i64, bool, String, DbCompression);
 
 impl<T> ParseSetting for Option<T>
 where
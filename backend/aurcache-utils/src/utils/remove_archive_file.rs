@@ -6,32 +6,56 @@ use sea_orm::{DatabaseTransaction, EntityTrait, ModelTrait};
 use std::fs;
 use tracing::{info, warn};
 
-pub async fn try_remove_archive_file(
+/// Remove released archives of `platform` from the repo database and disk.
+///
+/// Call it after the transaction releasing them is committed, the repo lock is only
+/// taken here so it is never held while waiting for the database.
+pub async fn remove_archive_files(platform: &str, filenames: Vec<String>) -> anyhow::Result<()> {
+    if filenames.is_empty() {
+        return Ok(());
+    }
+    let (names, db_archive, files_archive) = (
+        filenames.clone(),
+        format!("./repo/{platform}/repo.db"),
+        format!("./repo/{platform}/repo.files"),
+    );
+    // the repo lock and archive rewrite block, keep them off the async workers
+    tokio::task::spawn_blocking(move || {
+        pacman_repo_utils::repo_remove::repo_remove_many(&names, db_archive, files_archive)
+    })
+    .await??;
+    for filename in &filenames {
+        remove_archive_from_disk(platform, filename);
+    }
+    Ok(())
+}
+
+/// Delete the `files` row if no package uses the archive anymore.
+///
+/// Returns the file name the caller has to remove from the repo database and disk,
+/// so several removals can be written to the repo database in one batch.
+pub async fn release_archive_file(
     file: files::Model,
     db: &DatabaseTransaction,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Option<String>> {
     let package_files = PackagesFiles::find()
         .filter(packages_files::Column::FileId.eq(file.id))
         .all(db)
         .await?;
-    if package_files.is_empty() {
-        let platform = file.platform.clone();
-
-        pacman_repo_utils::repo_remove::repo_remove(
-            file.filename.clone(),
-            format!("./repo/{platform}/repo.db.tar.gz"),
-            format!("./repo/{platform}/repo.files.tar.gz"),
-        )?;
+    if !package_files.is_empty() {
+        return Ok(None);
+    }
 
-        let file_path = format!("./repo/{}/{}", platform, file.filename);
-        if let Ok(()) = fs::remove_file(file_path.clone()) {
-            info!("Removed old file: {file_path}")
-        } else {
-            warn!("Failed to remove package file: {file_path}")
-        }
+    let filename = file.filename.clone();
+    file.delete(db).await?;
+    Ok(Some(filename))
+}
 
-        file.delete(db).await?;
+pub fn remove_archive_from_disk(platform: &str, filename: &str) {
+    let file_path = format!("./repo/{platform}/{filename}");
+    if let Ok(()) = fs::remove_file(file_path.clone()) {
+        info!("Removed old file: {file_path}")
+    } else {
+        warn!("Failed to remove package file: {file_path}")
     }
-
-    Ok(())
 }
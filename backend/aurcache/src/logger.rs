@@ -3,7 +3,25 @@ use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{EnvFilter, fmt};
 
-pub fn init_logger() {
+/// Flushes spans still buffered by the OTLP exporter when dropped at the end of `main`
+#[must_use]
+pub struct LoggerGuard {
+    #[cfg(feature = "otlp")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for LoggerGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otlp")]
+        if let Some(provider) = self.provider.take()
+            && let Err(e) = provider.shutdown()
+        {
+            eprintln!("Failed to flush OTLP spans: {e}");
+        }
+    }
+}
+
+pub fn init_logger() -> LoggerGuard {
     let env_name = "LOG_LEVEL";
     let default_level = LevelFilter::INFO;
 
@@ -30,8 +48,67 @@ pub fn init_logger() {
         .with_ansi(use_color)
         .compact();
 
-    tracing_subscriber::registry()
+    let registry = tracing_subscriber::registry()
         .with(env_filter)
-        .with(formatter)
-        .init();
+        .with(formatter);
+
+    #[cfg(feature = "otlp")]
+    let (otlp_layer, provider, otlp_error) = match otlp::layer() {
+        Ok(Some((layer, provider))) => (Some(layer), Some(provider), None),
+        Ok(None) => (None, None, None),
+        Err(e) => (None, None, Some(e)),
+    };
+    #[cfg(feature = "otlp")]
+    let registry = registry.with(otlp_layer);
+
+    registry.init();
+
+    #[cfg(feature = "otlp")]
+    if let Some(e) = otlp_error {
+        tracing::warn!("Failed to initialize OTLP exporter: {e}");
+    }
+
+    LoggerGuard {
+        #[cfg(feature = "otlp")]
+        provider,
+    }
+}
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_sdk::Resource;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing::Subscriber;
+    use tracing_subscriber::Layer;
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// OTLP span exporter, only enabled if `OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+    /// All other `OTEL_*` variables of the exporter are respected as well.
+    /// The provider has to be shut down on exit to export the last batch of spans.
+    pub fn layer<S>() -> anyhow::Result<Option<(impl Layer<S>, SdkTracerProvider)>>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        if std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").is_err() {
+            return Ok(None);
+        }
+
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .build()?;
+        let service_name =
+            std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "aurcache".to_string());
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name(service_name).build())
+            .build();
+        let tracer = provider.tracer("aurcache");
+        opentelemetry::global::set_tracer_provider(provider.clone());
+
+        Ok(Some((
+            tracing_opentelemetry::layer().with_tracer(tracer),
+            provider,
+        )))
+    }
 }
@@ -4,21 +4,53 @@ use aurcache_api::init::{init_api, init_repo};
 use aurcache_builder::init::init_build_queue;
 use aurcache_db::init::init_db;
 use aurcache_scheduler::auto_update::start_auto_update_job;
+use aurcache_scheduler::mirror_health::start_mirror_health_job;
 use aurcache_scheduler::mirror_ranking::start_mirror_rank_job;
 use aurcache_scheduler::update_version_check::start_update_version_checking;
 use aurcache_types::builder::Action;
+use aurcache_utils::mirrors::{MirrorHealthReport, mirrorlist_override};
+use clap::Parser;
 use dotenvy::dotenv;
-use std::env;
+use std::process::ExitCode;
 use tokio::sync::broadcast;
-use tracing::warn;
+use tracing::{error, warn};
 
+mod commands;
 mod logger;
 mod startup;
 
+/// Build server and pacman repository for AUR and git packages
+#[derive(Parser)]
+#[command(name = "aurcache", version)]
+struct Cli {
+    /// Maintenance command, the server is started if omitted
+    #[command(subcommand)]
+    command: Option<commands::Command>,
+}
+
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
     _ = dotenv();
-    init_logger();
+    // dropped after the server or command finished
+    let _logger = init_logger();
+
+    match cli.command {
+        None => {
+            serve().await;
+            ExitCode::SUCCESS
+        }
+        Some(command) => match commands::run(command).await {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("{e:#}");
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
+
+async fn serve() {
     pre_startup_tasks().await;
 
     let (tx, _) = broadcast::channel::<Action>(32);
@@ -32,19 +64,23 @@ async fn main() {
         warn!("auto_update job not properly configured: {e}");
     }
 
-    let mirrorlist_override =
-        env::var("MIRRORLIST_SERVERS_X86_64").is_ok_and(|s| !s.trim().is_empty());
-
-    if !mirrorlist_override && let Err(e) = start_mirror_rank_job(db.clone(), tx.clone()) {
+    if !mirrorlist_override()
+        && let Err(e) = start_mirror_rank_job(db.clone(), tx.clone())
+    {
         warn!("mirror_rank job not properly configured: {e}");
     }
-    let api_handle = init_api(db, tx);
+    let mirror_health = MirrorHealthReport::default();
+    let mirror_health_handle = start_mirror_health_job(db.clone(), mirror_health.clone());
+    let api_handle = init_api(db, tx, mirror_health);
     let repo_handle = init_repo();
 
     tokio::select! {
         _ = version_check_handle => {
             warn!("Version check handle exited");
         }
+        _ = mirror_health_handle => {
+            warn!("Mirror health check handle exited");
+        }
         _ = build_queue_handle => {
             warn!("Build queue handle exited");
         }
@@ -6,8 +6,12 @@ use aurcache_builder::build_mode::{BuildMode, get_build_mode};
 use aurcache_db::prelude::{Builds, Packages};
 use aurcache_db::{builds, packages};
 use aurcache_types::builder::BuildStates;
+use aurcache_types::settings::{ApplicationSettings, Setting};
+use aurcache_utils::mirrors::{mirror_status, rank_options};
+use aurcache_utils::settings::general::SettingsTraits;
 use pacman_mirrors::benchmark::Bench;
 use pacman_mirrors::platforms::{Platform, Platforms};
+use pacman_repo_utils::repo_database::compression::DbCompression;
 use sea_orm::QueryFilter;
 use sea_orm::prelude::Expr;
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait};
@@ -48,21 +52,26 @@ pub async fn pre_startup_tasks() {
         }
     }
 
+    // disable on debug builds since annoying bc. of root permissions
+    #[cfg(not(debug_assertions))]
+    init_qemu_binfmt().await.unwrap();
+}
+
+pub async fn post_startup_tasks(db: &DatabaseConnection) -> anyhow::Result<()> {
+    // changing the compression converts the existing repo archives on next startup
+    let compression: DbCompression = ApplicationSettings::get(Setting::RepoCompression, None, db)
+        .await
+        .value;
     for platform in Platforms {
         if let Err(e) = pacman_repo_utils::repo_init::init_repo(
             &PathBuf::from(format!("./repo/{platform}")),
             "repo",
+            compression,
         ) {
             error!("Failed to initialize pacman repo: {e:?}");
         }
     }
 
-    // disable on debug builds since annoying bc. of root permissions
-    #[cfg(not(debug_assertions))]
-    init_qemu_binfmt().await.unwrap();
-}
-
-pub async fn post_startup_tasks(db: &DatabaseConnection) -> anyhow::Result<()> {
     // set all pending package status to failed
     Packages::update_many()
         .col_expr(
@@ -113,10 +122,11 @@ pub async fn post_startup_tasks(db: &DatabaseConnection) -> anyhow::Result<()> {
         info!("Wrote mirrorlist to {mirrorlist_path}");
     } else if std::fs::metadata(&mirrorlist_file).is_err() {
         info!("Perform initial load of pacman mirrorlist");
-        match pacman_mirrors::get_status(Platform::X86_64).await {
+        match mirror_status(db, Platform::X86_64).await {
             Ok(status) => {
                 let urls = status.urls;
-                let mirrorlist = urls.gen_mirrorlist(urls.0.clone())?;
+                let options = rank_options(db).await;
+                let mirrorlist = urls.gen_mirrorlist(urls.filtered(&options), &options)?;
                 fs::write(&mirrorlist_file, mirrorlist).await?;
                 info!("Wrote mirrorlist to {mirrorlist_path}");
             }
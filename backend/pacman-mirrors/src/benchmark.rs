@@ -1,10 +1,12 @@
-use crate::Mirror;
 use crate::mirror::Mirrors;
-use anyhow::anyhow;
+use crate::{Mirror, RankOptions};
+use anyhow::bail;
 use chrono::Utc;
+use futures::StreamExt;
+use futures::stream;
 use reqwest::Client;
 use std::time::{Duration, Instant};
-use tracing::info;
+use tracing::{debug, info};
 use url::Url;
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
@@ -13,58 +15,122 @@ pub enum TargetDb {
     Extra,
 }
 
+impl TargetDb {
+    /// Path of the repo database relative to the mirror url
+    #[must_use]
+    pub fn path(self) -> &'static str {
+        match self {
+            TargetDb::Core => "core/os/x86_64/core.db",
+            TargetDb::Extra => "extra/os/x86_64/extra.db",
+        }
+    }
+
+    /// Request timeout of a download. extra.db is roughly 60 times larger than core.db,
+    /// so slower mirrors and shared uplinks get more time for it.
+    #[must_use]
+    pub fn timeout(self) -> Duration {
+        match self {
+            TargetDb::Core => Duration::from_secs(10),
+            TargetDb::Extra => Duration::from_secs(60),
+        }
+    }
+}
+
+/// Weights of the normalized metrics in [`MirrorBenchmark::score`]
+const THROUGHPUT_WEIGHT: f64 = 0.6;
+const LATENCY_WEIGHT: f64 = 0.25;
+const UPSTREAM_WEIGHT: f64 = 0.15;
+
+/// A single download of a repo database from a mirror.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    /// Seconds until the response headers arrived.
+    latency: f64,
+    /// Transfer rate of the whole download in KiB/s.
+    throughput: f64,
+}
+
 trait Benchmark {
-    /// Measure time (in seconds) it took to connect (from user's geography)
-    /// and retrive the '[core,extra]/`os/x86_64`/[core,extra].db' file from the given URL.
-    async fn measure_duration(&mut self, target_db: TargetDb) -> anyhow::Result<f64>;
+    /// Download the '[core,extra]/`os/x86_64`/[core,extra].db' file from the given URL
+    /// and measure latency (from user's geography) and transfer rate.
+    async fn measure(&self, client: &Client, target_db: TargetDb) -> anyhow::Result<Sample>;
+}
+
+/// Benchmark result of a mirror.
+#[derive(Debug, Clone)]
+pub struct MirrorBenchmark {
+    pub mirror: Mirror,
+
+    /// Average transfer rate in KiB/s.
+    pub throughput: f64,
+
+    /// Average seconds until the response headers arrived.
+    pub latency: f64,
+
+    /// Combined score of throughput, latency and the upstream score in `0.0..=1.0`.
+    /// Higher is better.
+    pub score: f64,
 }
 
 pub trait Bench {
-    /// Rank the mirrors based on the score.
-    fn rank(&mut self) -> impl Future<Output = anyhow::Result<Vec<Mirror>>> + Send;
+    /// Benchmark the mirrors passing the filters of `options` and rank them by score, best first.
+    fn rank(
+        &self,
+        options: &RankOptions,
+    ) -> impl Future<Output = anyhow::Result<Vec<MirrorBenchmark>>> + Send;
 
-    fn gen_mirrorlist(&self, mirrors: Vec<Mirror>) -> anyhow::Result<String>;
+    /// Mirrorlist of the first [`RankOptions::count`] mirrors.
+    fn gen_mirrorlist(&self, mirrors: Vec<Mirror>, options: &RankOptions)
+    -> anyhow::Result<String>;
 }
 
 impl Bench for Mirrors {
-    async fn rank(&mut self) -> anyhow::Result<Vec<Mirror>> {
-        let mut durations = Vec::new();
-        for mirror in &mut self.0 {
-            // Skip mirrors that are not active
-            if !mirror.active {
-                continue;
-            }
+    async fn rank(&self, options: &RankOptions) -> anyhow::Result<Vec<MirrorBenchmark>> {
+        let client = Client::builder()
+            .connect_timeout(Duration::from_secs(5))
+            .build()?;
 
-            // only use http(s) mirrors
-            if mirror.protocol != crate::Protocol::Http && mirror.protocol != crate::Protocol::Https
-            {
-                continue;
-            }
+        // Skip inactive, non http(s) and filtered mirrors
+        let candidates = self.filtered(options);
+        info!(
+            "Benchmarking {} mirrors with {} concurrent downloads",
+            candidates.len(),
+            options.concurrency
+        );
 
-            info!("Benchmarking {}", mirror.url);
-            let duration = mirror.measure_duration(TargetDb::Core).await;
-            match duration {
-                Ok(duration) => {
-                    durations.push((mirror, duration));
-                }
-                Err(err) => {
-                    info!("Failed to measure duration for {}: {}", mirror.url, err);
-                    continue;
+        let samples = options.samples;
+        let mut benchmarks: Vec<MirrorBenchmark> = stream::iter(candidates)
+            .map(|mirror| {
+                let client = client.clone();
+                async move {
+                    let samples = benchmark_mirror(&mirror, &client, samples).await;
+                    if samples.is_empty() {
+                        return None;
+                    }
+                    let count = samples.len() as f64;
+                    Some(MirrorBenchmark {
+                        throughput: samples.iter().map(|s| s.throughput).sum::<f64>() / count,
+                        latency: samples.iter().map(|s| s.latency).sum::<f64>() / count,
+                        score: 0.0,
+                        mirror,
+                    })
                 }
-            }
-        }
-
-        // Sort by duration (ascending order)
-        durations.sort_by(|a, b| a.1.partial_cmp(&b.1).expect("Failed to compare durations"));
+            })
+            .buffer_unordered(options.concurrency.max(1))
+            .filter_map(|benchmark| async move { benchmark })
+            .collect()
+            .await;
 
-        // Extract only the sorted Mirror references
-        Ok(durations
-            .into_iter()
-            .map(|(mirror, _)| mirror.clone())
-            .collect())
+        score(&mut benchmarks);
+        benchmarks.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(benchmarks)
     }
 
-    fn gen_mirrorlist(&self, mirrors: Vec<Mirror>) -> anyhow::Result<String> {
+    fn gen_mirrorlist(
+        &self,
+        mirrors: Vec<Mirror>,
+        options: &RankOptions,
+    ) -> anyhow::Result<String> {
         let mut body = format!(
             r"##
 ## Arch Linux repository mirrorlist
@@ -75,7 +141,11 @@ impl Bench for Mirrors {
             Utc::now().date_naive()
         );
 
-        for mirror in &mirrors[..10] {
+        if mirrors.is_empty() {
+            bail!("No mirror matches the ranking options");
+        }
+
+        for mirror in mirrors.iter().take(options.count) {
             body.push_str(&format!("## {}\n", mirror.country.kind));
             body.push_str(&format!("Server = {}$repo/os/$arch\n", mirror.url));
             body.push('\n');
@@ -85,33 +155,186 @@ impl Bench for Mirrors {
     }
 }
 
-impl Benchmark for Mirror {
-    async fn measure_duration(&mut self, target_db: TargetDb) -> anyhow::Result<f64> {
-        let url = &self.url;
-        let url: Url = match target_db {
-            TargetDb::Core => url.join("core/os/x86_64/core.db")?,
-            TargetDb::Extra => url.join("extra/os/x86_64/extra.db")?,
+/// Take `samples` downloads of both core.db and extra.db, failed downloads are dropped
+async fn benchmark_mirror(mirror: &Mirror, client: &Client, samples: usize) -> Vec<Sample> {
+    let mut results = vec![];
+    for target_db in [TargetDb::Core, TargetDb::Extra] {
+        for _ in 0..samples.max(1) {
+            match mirror.measure(client, target_db).await {
+                Ok(sample) => results.push(sample),
+                Err(err) => debug!("Failed to sample {target_db:?} of {}: {err}", mirror.url),
+            }
+        }
+    }
+    if results.is_empty() {
+        info!("Failed to benchmark {}, no download succeeded", mirror.url);
+    }
+    results
+}
+
+/// Normalize throughput, latency and upstream score against the best mirror
+/// and combine them into [`MirrorBenchmark::score`].
+fn score(benchmarks: &mut [MirrorBenchmark]) {
+    let max_throughput = benchmarks.iter().map(|b| b.throughput).fold(0.0, f64::max);
+    let min_latency = benchmarks
+        .iter()
+        .map(|b| b.latency)
+        .fold(f64::INFINITY, f64::min);
+    // lower upstream scores are better
+    let min_upstream = benchmarks
+        .iter()
+        .filter_map(|b| b.mirror.score)
+        .filter(|s| *s > 0.0)
+        .fold(f64::INFINITY, f64::min);
+
+    for benchmark in benchmarks {
+        let throughput = if max_throughput > 0.0 {
+            benchmark.throughput / max_throughput
+        } else {
+            0.0
         };
+        let latency = if benchmark.latency > 0.0 {
+            min_latency / benchmark.latency
+        } else {
+            1.0
+        };
+        let upstream = benchmark
+            .mirror
+            .score
+            .filter(|s| *s > 0.0 && min_upstream.is_finite())
+            .map_or(0.0, |s| min_upstream / s);
 
-        let start = Instant::now();
+        benchmark.score =
+            THROUGHPUT_WEIGHT * throughput + LATENCY_WEIGHT * latency + UPSTREAM_WEIGHT * upstream;
+    }
+}
 
-        match Client::builder()
-            .connect_timeout(Duration::from_secs(5))
-            .timeout(Duration::from_secs(10))
-            .build()?
+impl Benchmark for Mirror {
+    async fn measure(&self, client: &Client, target_db: TargetDb) -> anyhow::Result<Sample> {
+        let url: Url = self.url.join(target_db.path())?;
+
+        let start = Instant::now();
+        let response = client
             .get(url.as_str())
+            .timeout(target_db.timeout())
             .send()
-            .await
-        {
-            Ok(response) => {
-                let transfer_time: f64 = start.elapsed().as_secs_f64();
-
-                let file_size = response.bytes().await?.len();
-                let transfer_rate = (file_size as f64) / (transfer_time * 1024.0);
-                info!("Transfer Rate: {url} => {transfer_rate:.2} kb/s");
-                Ok(transfer_rate)
-            }
-            Err(err) => Err(anyhow!(err)),
+            .await?
+            .error_for_status()?;
+        let latency = start.elapsed().as_secs_f64();
+
+        let file_size = response.bytes().await?.len();
+        let transfer_time = start.elapsed().as_secs_f64();
+        let throughput = (file_size as f64) / (transfer_time * 1024.0);
+        debug!("Transfer Rate: {url} => {throughput:.2} KiB/s, latency {latency:.3}s");
+        Ok(Sample {
+            latency,
+            throughput,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Country, Protocol};
+
+    fn benchmark(throughput: f64, latency: f64, upstream: Option<f64>) -> MirrorBenchmark {
+        MirrorBenchmark {
+            mirror: Mirror {
+                url: "https://mirror.example.org/archlinux/".parse().unwrap(),
+                protocol: Protocol::Https,
+                last_sync: None,
+                completion_pct: Some(1.0),
+                duration_avg: None,
+                duration_stddev: None,
+                score: upstream,
+                active: true,
+                country: Country::WORLDWIDE,
+                isos: false,
+                ipv4: true,
+                ipv6: false,
+                details: String::new(),
+            },
+            throughput,
+            latency,
+            score: 0.0,
         }
     }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn target_db_urls() {
+        let url: Url = "https://mirror.example.org/archlinux/".parse().unwrap();
+        assert_eq!(
+            url.join(TargetDb::Core.path()).unwrap().as_str(),
+            "https://mirror.example.org/archlinux/core/os/x86_64/core.db"
+        );
+        assert_eq!(
+            url.join(TargetDb::Extra.path()).unwrap().as_str(),
+            "https://mirror.example.org/archlinux/extra/os/x86_64/extra.db"
+        );
+        assert!(TargetDb::Extra.timeout() > TargetDb::Core.timeout());
+    }
+
+    #[test]
+    fn best_mirror_scores_one() {
+        let mut benchmarks = [
+            benchmark(2000.0, 0.1, Some(1.0)),
+            benchmark(1000.0, 0.2, Some(2.0)),
+        ];
+        score(&mut benchmarks);
+
+        assert_close(benchmarks[0].score, 1.0);
+        assert_close(
+            benchmarks[1].score,
+            THROUGHPUT_WEIGHT * 0.5 + LATENCY_WEIGHT * 0.5 + UPSTREAM_WEIGHT * 0.5,
+        );
+    }
+
+    #[test]
+    fn metrics_are_weighted() {
+        let mut benchmarks = [
+            benchmark(1000.0, 0.4, Some(4.0)),
+            benchmark(500.0, 0.1, Some(1.0)),
+        ];
+        score(&mut benchmarks);
+
+        assert_close(
+            benchmarks[0].score,
+            THROUGHPUT_WEIGHT + LATENCY_WEIGHT * 0.25 + UPSTREAM_WEIGHT * 0.25,
+        );
+        assert_close(
+            benchmarks[1].score,
+            THROUGHPUT_WEIGHT * 0.5 + LATENCY_WEIGHT + UPSTREAM_WEIGHT,
+        );
+    }
+
+    #[test]
+    fn missing_upstream_score_counts_as_zero() {
+        let mut benchmarks = [
+            benchmark(1000.0, 0.1, None),
+            benchmark(1000.0, 0.1, Some(0.0)),
+            benchmark(1000.0, 0.1, Some(3.0)),
+        ];
+        score(&mut benchmarks);
+
+        assert_close(benchmarks[0].score, THROUGHPUT_WEIGHT + LATENCY_WEIGHT);
+        assert_close(benchmarks[1].score, THROUGHPUT_WEIGHT + LATENCY_WEIGHT);
+        assert_close(benchmarks[2].score, 1.0);
+    }
+
+    #[test]
+    fn zero_throughput_and_latency_stay_finite() {
+        let mut benchmarks = [benchmark(0.0, 0.0, None), benchmark(0.0, 0.5, None)];
+        score(&mut benchmarks);
+
+        assert_close(benchmarks[0].score, LATENCY_WEIGHT);
+        assert_close(benchmarks[1].score, 0.0);
+    }
 }
@@ -0,0 +1,307 @@
+//! Lightweight probe of the servers of an already written mirrorlist, run between full rankings.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::StreamExt;
+use futures::stream;
+use reqwest::Client;
+use serde::Serialize;
+use std::time::Duration;
+use tracing::debug;
+
+/// Header line of mirrorlists written by [`Bench::gen_mirrorlist`](crate::benchmark::Bench::gen_mirrorlist)
+const GENERATED_MARKER: &str = "## Created by aurcache";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MirrorState {
+    Healthy,
+    /// `lastsync` is older than the allowed sync age
+    Stale,
+    /// `core.db` couldn't be downloaded
+    Dead,
+}
+
+/// Probe result of a single `Server` entry.
+#[derive(Debug, Clone, Serialize)]
+pub struct MirrorHealth {
+    /// `Server` entry as written in the mirrorlist, e.g. `https://host/archlinux/$repo/os/$arch`
+    pub server: String,
+    pub state: MirrorState,
+    /// HTTP status of `core.db`, none if the request failed
+    pub http_status: Option<u16>,
+    /// time of the last sync of the mirror, none if it doesn't serve `lastsync`
+    pub last_sync: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+/// `Server` entries of a mirrorlist in order
+#[must_use]
+pub fn parse_servers(mirrorlist: &str) -> Vec<String> {
+    mirrorlist
+        .lines()
+        .filter_map(server_entry)
+        .map(str::to_string)
+        .collect()
+}
+
+fn server_entry(line: &str) -> Option<&str> {
+    line.trim()
+        .strip_prefix("Server")?
+        .trim_start()
+        .strip_prefix('=')
+        .map(str::trim)
+}
+
+/// Check `core.db` and `lastsync` of all `servers`, `concurrency` at a time.
+pub async fn probe(
+    servers: Vec<String>,
+    max_sync_age: TimeDelta,
+    concurrency: usize,
+) -> anyhow::Result<Vec<MirrorHealth>> {
+    let client = Client::builder()
+        .connect_timeout(Duration::from_secs(5))
+        .timeout(Duration::from_secs(10))
+        .build()?;
+
+    let mut results: Vec<(usize, MirrorHealth)> = stream::iter(servers.into_iter().enumerate())
+        .map(|(i, server)| {
+            let client = client.clone();
+            async move { (i, probe_server(&client, &server, max_sync_age).await) }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+    results.sort_by_key(|(i, _)| *i);
+    Ok(results.into_iter().map(|(_, health)| health).collect())
+}
+
+async fn probe_server(client: &Client, server: &str, max_sync_age: TimeDelta) -> MirrorHealth {
+    let core_db = format!(
+        "{}/core.db",
+        server
+            .replace("$repo", "core")
+            .replace("$arch", "x86_64")
+            .trim_end_matches('/')
+    );
+    let (http_status, error) = match client.head(&core_db).send().await {
+        Ok(res) if res.status().is_success() => (Some(res.status().as_u16()), None),
+        Ok(res) => (
+            Some(res.status().as_u16()),
+            Some(format!("{core_db} returned {}", res.status())),
+        ),
+        Err(e) => (None, Some(e.to_string())),
+    };
+
+    let last_sync = if error.is_none() {
+        last_sync(client, server).await
+    } else {
+        None
+    };
+    let state = if error.is_some() {
+        MirrorState::Dead
+    } else if last_sync.is_some_and(|t| Utc::now() - t > max_sync_age) {
+        MirrorState::Stale
+    } else {
+        MirrorState::Healthy
+    };
+    debug!("Mirror health of {server}: {state:?}");
+
+    MirrorHealth {
+        server: server.to_string(),
+        state,
+        http_status,
+        last_sync,
+        error,
+    }
+}
+
+/// Unix timestamp served at `<mirror>/lastsync`, the mirror root is the server up to `$repo`
+async fn last_sync(client: &Client, server: &str) -> Option<DateTime<Utc>> {
+    let (root, _) = server.split_once("$repo")?;
+    let res = client
+        .get(format!("{root}lastsync"))
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?;
+    let timestamp = res.text().await.ok()?.trim().parse().ok()?;
+    DateTime::from_timestamp(timestamp, 0)
+}
+
+/// Mirrorlist with stale servers moved behind the healthy ones and dead servers to the end.
+///
+/// Only mirrorlists generated by aurcache are touched. None if nothing changes or all
+/// servers are dead.
+#[must_use]
+pub fn demote_unhealthy(mirrorlist: &str, health: &[MirrorHealth]) -> Option<String> {
+    if !mirrorlist.contains(GENERATED_MARKER) {
+        return None;
+    }
+    // header lines, then a `## Country` comment and a `Server` line per mirror
+    let mut header: Vec<&str> = vec![];
+    let mut entries: Vec<(Option<&str>, &str, MirrorState)> = vec![];
+    let mut pending: Vec<&str> = vec![];
+    for line in mirrorlist.lines() {
+        let Some(server) = server_entry(line) else {
+            pending.push(line);
+            continue;
+        };
+        let comment = pending.pop_if(|l| l.starts_with('#'));
+        if entries.is_empty() {
+            header.append(&mut pending);
+        }
+        pending.clear();
+        let state = health
+            .iter()
+            .find(|h| h.server == server)
+            .map_or(MirrorState::Healthy, |h| h.state);
+        entries.push((comment, line, state));
+    }
+
+    if entries.iter().all(|(_, _, s)| *s == MirrorState::Dead) {
+        return None;
+    }
+    let ordered: Vec<_> = [MirrorState::Healthy, MirrorState::Stale, MirrorState::Dead]
+        .into_iter()
+        .flat_map(|wanted| entries.iter().filter(move |(_, _, s)| *s == wanted))
+        .collect();
+    if ordered.iter().map(|e| e.1).eq(entries.iter().map(|e| e.1)) {
+        return None;
+    }
+
+    let mut body = String::new();
+    for line in header {
+        body.push_str(line);
+        body.push('\n');
+    }
+    for (comment, server, _) in ordered {
+        if let Some(comment) = comment {
+            body.push_str(comment);
+            body.push('\n');
+        }
+        body.push_str(server);
+        body.push_str("\n\n");
+    }
+    Some(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENERATED: &str = "##
+## Arch Linux repository mirrorlist
+## Created by aurcache
+## Generated on 2026-10-19
+##
+## Germany
+Server = https://dead.example.de/archlinux/$repo/os/$arch
+
+## France
+Server = https://stale.example.fr/archlinux/$repo/os/$arch
+
+## Sweden
+Server = https://healthy.example.se/archlinux/$repo/os/$arch
+
+";
+
+    fn health(server: &str, state: MirrorState) -> MirrorHealth {
+        MirrorHealth {
+            server: server.to_string(),
+            state,
+            http_status: None,
+            last_sync: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn parse_generated_servers() {
+        assert_eq!(
+            parse_servers(GENERATED),
+            [
+                "https://dead.example.de/archlinux/$repo/os/$arch",
+                "https://stale.example.fr/archlinux/$repo/os/$arch",
+                "https://healthy.example.se/archlinux/$repo/os/$arch",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_hand_written_servers() {
+        let mirrorlist = "# my mirrors
+#Server = https://disabled.example.org/$repo/os/$arch
+Server=https://a.example.org/$repo/os/$arch
+  Server =  https://b.example.org/$repo/os/$arch\t
+ServerName = not a server
+";
+        assert_eq!(
+            parse_servers(mirrorlist),
+            [
+                "https://a.example.org/$repo/os/$arch",
+                "https://b.example.org/$repo/os/$arch",
+            ]
+        );
+    }
+
+    #[test]
+    fn demote_dead_and_stale() {
+        let health = [
+            health(
+                "https://dead.example.de/archlinux/$repo/os/$arch",
+                MirrorState::Dead,
+            ),
+            health(
+                "https://stale.example.fr/archlinux/$repo/os/$arch",
+                MirrorState::Stale,
+            ),
+            health(
+                "https://healthy.example.se/archlinux/$repo/os/$arch",
+                MirrorState::Healthy,
+            ),
+        ];
+        let demoted = demote_unhealthy(GENERATED, &health).unwrap();
+        assert_eq!(
+            demoted,
+            "##
+## Arch Linux repository mirrorlist
+## Created by aurcache
+## Generated on 2026-10-19
+##
+## Sweden
+Server = https://healthy.example.se/archlinux/$repo/os/$arch
+
+## France
+Server = https://stale.example.fr/archlinux/$repo/os/$arch
+
+## Germany
+Server = https://dead.example.de/archlinux/$repo/os/$arch
+
+"
+        );
+        // already in order
+        assert_eq!(demote_unhealthy(&demoted, &health), None);
+    }
+
+    #[test]
+    fn hand_written_list_is_untouched() {
+        let mirrorlist = "Server = https://dead.example.org/$repo/os/$arch
+Server = https://healthy.example.org/$repo/os/$arch
+";
+        let health = [health(
+            "https://dead.example.org/$repo/os/$arch",
+            MirrorState::Dead,
+        )];
+        assert_eq!(demote_unhealthy(mirrorlist, &health), None);
+    }
+
+    #[test]
+    fn all_dead_is_untouched() {
+        let health: Vec<_> = parse_servers(GENERATED)
+            .iter()
+            .map(|server| health(server, MirrorState::Dead))
+            .collect();
+        assert_eq!(demote_unhealthy(GENERATED, &health), None);
+    }
+}
@@ -3,16 +3,19 @@
 #![warn(rustdoc::invalid_codeblock_attributes)]
 pub mod benchmark;
 pub mod country;
+pub mod health;
 pub mod mirror;
 pub mod platforms;
 pub mod protocol;
+pub mod rank_options;
 pub mod status;
 
 pub use crate::mirror::Mirror;
 use crate::platforms::Platform;
 pub use country::Country;
 pub use protocol::Protocol;
-pub use status::Status;
+pub use rank_options::RankOptions;
+pub use status::{Status, StatusSource};
 
 /// Shorthand for [`Status::get()`](Status::get). This gets the mirror status of all Arch Linux
 /// mirrors.
@@ -106,3 +106,15 @@ impl TryFrom<Raw> for Mirror {
         })
     }
 }
+
+impl Mirrors {
+    /// Mirrors passing the filters of `options`, in their current order.
+    #[must_use]
+    pub fn filtered(&self, options: &crate::RankOptions) -> Vec<Mirror> {
+        self.0
+            .iter()
+            .filter(|mirror| options.matches(mirror))
+            .cloned()
+            .collect()
+    }
+}
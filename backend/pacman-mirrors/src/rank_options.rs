@@ -0,0 +1,240 @@
+//! Filters deciding which mirrors are ranked and how many end up in the mirrorlist.
+
+use crate::country::Kind;
+use crate::{Mirror, Protocol};
+use chrono::{TimeDelta, Utc};
+use tracing::warn;
+
+/// Options for [`Bench::rank`](crate::benchmark::Bench::rank) and
+/// [`Bench::gen_mirrorlist`](crate::benchmark::Bench::gen_mirrorlist).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankOptions {
+    /// Only mirrors located in one of these countries, every country if empty.
+    pub countries: Vec<Kind>,
+
+    /// Only HTTPS mirrors instead of HTTP and HTTPS.
+    pub https_only: bool,
+
+    /// Only mirrors reachable via IPv4.
+    pub ipv4: bool,
+
+    /// Only mirrors reachable via IPv6.
+    pub ipv6: bool,
+
+    /// Maximum age of the last sync from the Arch Linux servers.
+    pub max_sync_age: Option<TimeDelta>,
+
+    /// Minimum completion percentage in `0.0..=1.0`.
+    pub min_completion: Option<f64>,
+
+    /// Number of mirrors written to the mirrorlist.
+    pub count: usize,
+
+    /// Number of mirrors benchmarked at the same time.
+    pub concurrency: usize,
+
+    /// Number of downloads of each repo database per mirror.
+    pub samples: usize,
+}
+
+impl Default for RankOptions {
+    fn default() -> Self {
+        Self {
+            countries: vec![],
+            https_only: false,
+            ipv4: false,
+            ipv6: false,
+            max_sync_age: None,
+            min_completion: None,
+            count: 10,
+            concurrency: 8,
+            samples: 1,
+        }
+    }
+}
+
+impl RankOptions {
+    /// Parse a comma separated list of country names like `Germany, France` into [`Kind`]s.
+    /// Unknown names are logged and ignored.
+    #[must_use]
+    pub fn parse_countries(countries: &str) -> Vec<Kind> {
+        let (known, unknown): (Vec<_>, Vec<_>) = countries
+            .split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(|c| Kind::from(c.to_string()))
+            .partition(|kind| !matches!(kind, Kind::Other(_)));
+        if !unknown.is_empty() {
+            let names: Vec<_> = unknown.iter().map(ToString::to_string).collect();
+            warn!("Ignoring unknown mirror countries: {}", names.join(", "));
+        }
+        known
+    }
+
+    /// Whether `mirror` passes all filters.
+    #[must_use]
+    pub fn matches(&self, mirror: &Mirror) -> bool {
+        let protocol_allowed = match mirror.protocol {
+            Protocol::Https => true,
+            Protocol::Http => !self.https_only,
+            Protocol::Rsync | Protocol::Ftp => false,
+        };
+        let synced_recently = self.max_sync_age.is_none_or(|max_age| {
+            mirror
+                .last_sync
+                .is_some_and(|last_sync| Utc::now() - last_sync <= max_age)
+        });
+        let complete = self
+            .min_completion
+            .is_none_or(|min| mirror.completion_pct.is_some_and(|pct| pct >= min));
+
+        mirror.active
+            && protocol_allowed
+            && (self.countries.is_empty() || self.countries.contains(&mirror.country.kind))
+            && (!self.ipv4 || mirror.ipv4)
+            && (!self.ipv6 || mirror.ipv6)
+            && synced_recently
+            && complete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Country;
+
+    fn mirror() -> Mirror {
+        Mirror {
+            url: "https://mirror.example.de/archlinux/".parse().unwrap(),
+            protocol: Protocol::Https,
+            last_sync: Some(Utc::now() - TimeDelta::hours(2)),
+            completion_pct: Some(0.9),
+            duration_avg: None,
+            duration_stddev: None,
+            score: None,
+            active: true,
+            country: Country::GERMANY,
+            isos: false,
+            ipv4: true,
+            ipv6: false,
+            details: String::new(),
+        }
+    }
+
+    #[test]
+    fn parse_known_countries() {
+        assert_eq!(
+            RankOptions::parse_countries(" Germany,France , ,United States"),
+            [Kind::Germany, Kind::France, Kind::UnitedStates]
+        );
+    }
+
+    #[test]
+    fn parse_ignores_unknown_countries() {
+        assert_eq!(
+            RankOptions::parse_countries("germany, DE, Germany, Atlantis"),
+            [Kind::Germany]
+        );
+        assert!(RankOptions::parse_countries("Atlantis").is_empty());
+    }
+
+    #[test]
+    fn defaults_match_active_mirrors() {
+        let options = RankOptions::default();
+        assert!(options.matches(&mirror()));
+        assert!(!options.matches(&Mirror {
+            active: false,
+            ..mirror()
+        }));
+        assert!(!options.matches(&Mirror {
+            protocol: Protocol::Rsync,
+            ..mirror()
+        }));
+    }
+
+    #[test]
+    fn https_only() {
+        let options = RankOptions {
+            https_only: true,
+            ..Default::default()
+        };
+        let http = Mirror {
+            protocol: Protocol::Http,
+            ..mirror()
+        };
+        assert!(options.matches(&mirror()));
+        assert!(!options.matches(&http));
+        assert!(RankOptions::default().matches(&http));
+    }
+
+    #[test]
+    fn ip_versions() {
+        let ipv6 = RankOptions {
+            ipv6: true,
+            ..Default::default()
+        };
+        assert!(!ipv6.matches(&mirror()));
+        assert!(ipv6.matches(&Mirror {
+            ipv6: true,
+            ..mirror()
+        }));
+
+        let ipv4 = RankOptions {
+            ipv4: true,
+            ..Default::default()
+        };
+        assert!(ipv4.matches(&mirror()));
+        assert!(!ipv4.matches(&Mirror {
+            ipv4: false,
+            ipv6: true,
+            ..mirror()
+        }));
+    }
+
+    #[test]
+    fn sync_age() {
+        let options = RankOptions {
+            max_sync_age: Some(TimeDelta::hours(3)),
+            ..Default::default()
+        };
+        assert!(options.matches(&mirror()));
+        assert!(!options.matches(&Mirror {
+            last_sync: Some(Utc::now() - TimeDelta::hours(4)),
+            ..mirror()
+        }));
+        assert!(!options.matches(&Mirror {
+            last_sync: None,
+            ..mirror()
+        }));
+    }
+
+    #[test]
+    fn completion() {
+        let options = RankOptions {
+            min_completion: Some(0.9),
+            ..Default::default()
+        };
+        assert!(options.matches(&mirror()));
+        assert!(!options.matches(&Mirror {
+            completion_pct: Some(0.5),
+            ..mirror()
+        }));
+        assert!(!options.matches(&Mirror {
+            completion_pct: None,
+            ..mirror()
+        }));
+    }
+
+    #[test]
+    fn countries() {
+        let options = RankOptions {
+            countries: vec![Kind::France, Kind::Germany],
+            ..Default::default()
+        };
+        assert!(options.matches(&mirror()));
+        assert!(!options.matches(&Mirror {
+            country: Country::UNITED_STATES,
+            ..mirror()
+        }));
+    }
+}
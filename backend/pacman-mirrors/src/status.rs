@@ -7,7 +7,10 @@ use backon::{FibonacciBuilder, Retryable};
 use reqwest::Client;
 use reqwest::header::ACCEPT;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::time::Duration;
+use tokio::fs;
 use tracing::warn;
 
 /// Raw, typed form of the JSON output given by performing a GET request on [`Status::URL`](Status::URL).
@@ -52,11 +55,66 @@ impl Status {
 
     /// Get the status from [`Status::URL`](Self::URL).
     pub async fn get_from_default_url(target_platform: Platform) -> anyhow::Result<Self> {
+        Self::from_json(&Self::fetch_default_json(target_platform).await?)
+    }
+
+    /// Get the status from a given url.
+    pub async fn get_from_url(url: &str, _platform: Platform) -> anyhow::Result<Self> {
+        // todo we need to fetch mirror list differently dependent on platform
+        Self::from_json(&Self::fetch_json(url).await?)
+    }
+
+    /// Get the status from `source`.
+    ///
+    /// Every successfully parsed status JSON of a remote source is written to `cache`,
+    /// which is used instead when the source can't be reached.
+    pub async fn get_from_source(
+        target_platform: Platform,
+        source: &StatusSource,
+        cache: Option<&Path>,
+    ) -> anyhow::Result<Self> {
+        let json = match source {
+            StatusSource::Default => Self::fetch_default_json(target_platform).await,
+            StatusSource::Url(url) => Self::fetch_json(url).await,
+            StatusSource::File(path) => fs::read_to_string(path)
+                .await
+                .with_context(|| format!("failed to read {}", path.display())),
+        };
+        let fetched = json.and_then(|json| Self::from_json(&json).map(|status| (status, json)));
+
+        match (fetched, cache) {
+            // a local file is already a copy, no need to cache it
+            (Ok((status, _)), Some(_)) if matches!(source, StatusSource::File(_)) => Ok(status),
+            (Ok((status, json)), Some(cache)) => {
+                if let Err(e) = write_cache(cache, &json).await {
+                    warn!("Failed to cache mirror status at {}: {e}", cache.display());
+                }
+                Ok(status)
+            }
+            (Ok((status, _)), None) => Ok(status),
+            (Err(e), Some(cache)) if fs::try_exists(cache).await.unwrap_or(false) => {
+                warn!(
+                    "Failed to get mirror status: {e} -- Using cached status from {}",
+                    cache.display()
+                );
+                Self::from_json(&fs::read_to_string(cache).await?)
+            }
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    /// Parse the JSON served at [`Status::URL_X86_64`](Self::URL_X86_64).
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let raw: Raw = serde_json::from_str(json).context("Failed to parse mirror status")?;
+        Self::try_from(raw)
+    }
+
+    async fn fetch_default_json(target_platform: Platform) -> anyhow::Result<String> {
         match target_platform {
             Platform::X86_64 => {
                 let result = (|| async {
                     // fetch original archlinux.org mirrorlist
-                    Self::get_from_url(Self::URL_X86_64, target_platform).await
+                    Self::fetch_json(Self::URL_X86_64).await
                 })
                 .retry(FibonacciBuilder::default().with_max_times(2))
                 .await;
@@ -70,7 +128,7 @@ impl Status {
                         );
                         (|| async {
                             // fetch alternative archlinux mirrorlist
-                            Self::get_from_url(Self::URL_X86_64_ALT, target_platform).await
+                            Self::fetch_json(Self::URL_X86_64_ALT).await
                         })
                         .retry(FibonacciBuilder::default().with_max_times(4))
                         .await
@@ -82,9 +140,7 @@ impl Status {
         }
     }
 
-    /// Get the status from a given url.
-    pub async fn get_from_url(url: &str, _platform: Platform) -> anyhow::Result<Self> {
-        // todo we need to fetch mirror list differently dependent on platform
+    async fn fetch_json(url: &str) -> anyhow::Result<String> {
         let client = Client::builder()
             .user_agent("Mozilla/5.0 (compatible; AURCache/1.0;)")
             .http1_only()
@@ -98,9 +154,50 @@ impl Status {
             .await?
             .error_for_status()?;
 
-        let raw: Raw = res.json().await?;
+        Ok(res.text().await?)
+    }
+}
 
-        Self::try_from(raw)
+/// Write the status JSON to a temp file first, so a crash never leaves a truncated cache
+async fn write_cache(cache: &Path, json: &str) -> anyhow::Result<()> {
+    if let Some(dir) = cache.parent() {
+        fs::create_dir_all(dir).await?;
+    }
+    let tmp = cache.with_extension("json.tmp");
+    fs::write(&tmp, json).await?;
+    fs::rename(&tmp, cache).await?;
+    Ok(())
+}
+
+/// Where the mirror status JSON is fetched from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum StatusSource {
+    /// archlinux.org with a fallback to [`Status::URL_X86_64_ALT`].
+    #[default]
+    Default,
+
+    /// Status JSON served at this url, e.g. a mirror inside an air-gapped network.
+    Url(String),
+
+    /// Local status JSON file.
+    File(PathBuf),
+}
+
+impl FromStr for StatusSource {
+    type Err = anyhow::Error;
+
+    /// Empty or `default`, an `http(s)://` url or a file path, optionally prefixed with `file://`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() || s.eq_ignore_ascii_case("default") {
+            Ok(Self::Default)
+        } else if s.starts_with("http://") || s.starts_with("https://") {
+            Ok(Self::Url(s.parse::<url::Url>()?.to_string()))
+        } else {
+            Ok(Self::File(PathBuf::from(
+                s.strip_prefix("file://").unwrap_or(s),
+            )))
+        }
     }
 }
 
@@ -128,3 +225,113 @@ impl TryFrom<Raw> for Status {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATUS_JSON: &str = r#"{
+  "cutoff": 86400,
+  "last_check": "2026-10-19T12:00:00.000Z",
+  "num_checks": 24,
+  "check_frequency": 3600,
+  "urls": [
+    {
+      "url": "https://mirror.example.se/archlinux/",
+      "protocol": "https",
+      "last_sync": "2026-10-19T11:00:00Z",
+      "completion_pct": 1.0,
+      "duration_avg": 0.25,
+      "duration_stddev": 0.05,
+      "score": 0.8,
+      "active": true,
+      "country": "Sweden",
+      "country_code": "SE",
+      "isos": true,
+      "ipv4": true,
+      "ipv6": false,
+      "details": "https://archlinux.org/mirrors/mirror.example.se/1/"
+    }
+  ],
+  "version": 3
+}"#;
+
+    /// Nothing listens on port 1, so the request fails right away
+    const UNREACHABLE: &str = "http://127.0.0.1:1/mirrors/status/json";
+
+    #[test]
+    fn parse_default_source() {
+        for s in ["", "  ", "default", "Default"] {
+            assert_eq!(StatusSource::from_str(s).unwrap(), StatusSource::Default);
+        }
+    }
+
+    #[test]
+    fn parse_url_source() {
+        assert_eq!(
+            StatusSource::from_str("https://mirror.example.org/status.json").unwrap(),
+            StatusSource::Url("https://mirror.example.org/status.json".to_string())
+        );
+        assert_eq!(
+            StatusSource::from_str(" http://10.0.0.1:8080/status ").unwrap(),
+            StatusSource::Url("http://10.0.0.1:8080/status".to_string())
+        );
+        assert!(StatusSource::from_str("https://").is_err());
+    }
+
+    #[test]
+    fn parse_file_source() {
+        assert_eq!(
+            StatusSource::from_str("file:///var/lib/aurcache/status.json").unwrap(),
+            StatusSource::File(PathBuf::from("/var/lib/aurcache/status.json"))
+        );
+        assert_eq!(
+            StatusSource::from_str("/var/lib/aurcache/status.json").unwrap(),
+            StatusSource::File(PathBuf::from("/var/lib/aurcache/status.json"))
+        );
+        assert_eq!(
+            StatusSource::from_str("config/status.json").unwrap(),
+            StatusSource::File(PathBuf::from("config/status.json"))
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_source_falls_back_to_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("cache/status.json");
+        let source = StatusSource::Url(UNREACHABLE.to_string());
+
+        assert!(
+            Status::get_from_source(Platform::X86_64, &source, Some(&cache))
+                .await
+                .is_err()
+        );
+
+        write_cache(&cache, STATUS_JSON).await.unwrap();
+        let status = Status::get_from_source(Platform::X86_64, &source, Some(&cache))
+            .await
+            .unwrap();
+        assert_eq!(status, Status::from_json(STATUS_JSON).unwrap());
+
+        let missing = StatusSource::File(dir.path().join("missing.json"));
+        let status = Status::get_from_source(Platform::X86_64, &missing, Some(&cache))
+            .await
+            .unwrap();
+        assert_eq!(status.urls.0.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn file_source_is_not_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("status.json");
+        let cache = dir.path().join("cache/status.json");
+        std::fs::write(&file, STATUS_JSON).unwrap();
+
+        let status =
+            Status::get_from_source(Platform::X86_64, &StatusSource::File(file), Some(&cache))
+                .await
+                .unwrap();
+        assert_eq!(status, Status::from_json(STATUS_JSON).unwrap());
+        assert!(!cache.exists());
+    }
+}
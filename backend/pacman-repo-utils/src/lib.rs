@@ -1,5 +1,12 @@
 mod pkginfo;
 pub mod repo_add;
-mod repo_database;
+pub mod repo_check;
+pub mod repo_database;
+pub mod repo_files;
 pub mod repo_init;
+pub mod repo_packages;
+pub mod repo_rebuild;
 pub mod repo_remove;
+pub mod repo_validate;
+#[cfg(test)]
+mod test_utils;
@@ -15,6 +15,10 @@ pub struct Pkginfo {
     pub optdepends: Vec<String>,
     pub makedepends: Vec<String>,
     pub checkdepends: Vec<String>,
+    /// files marked as config files, only used by pacman's local database
+    pub backup: Vec<String>,
+    /// extended data like `pkgtype=split`
+    pub xdata: Vec<String>,
     pub pkgname: String,
     pub pkgbase: String,
     pub pkgver: String,
@@ -39,6 +43,8 @@ impl Pkginfo {
             optdepends: vec![],
             makedepends: vec![],
             checkdepends: vec![],
+            backup: vec![],
+            xdata: vec![],
             pkgname: String::new(),
             pkgbase: String::new(),
             pkgver: String::new(),
@@ -79,6 +85,8 @@ impl Pkginfo {
             "optdepend" => self.optdepends.push(value.to_string()),
             "makedepend" => self.makedepends.push(value.to_string()),
             "checkdepend" => self.checkdepends.push(value.to_string()),
+            "backup" => self.backup.push(value.to_string()),
+            "xdata" => self.xdata.push(value.to_string()),
             "pkgname" => self.pkgname = value.to_string(),
             "pkgbase" => self.pkgbase = value.to_string(),
             "pkgver" => self.pkgver = value.to_string(),
@@ -181,6 +189,8 @@ mod tests {
             optdepend = secopdep
             makedepend = test
             checkdepend = test
+            backup = etc/test.conf
+            xdata = pkgtype=pkg
             "#;
         pkginfo.parse(data.as_bytes()).unwrap();
         assert_eq!(pkginfo.groups, vec!["test", "secgroup"]);
@@ -192,5 +202,7 @@ mod tests {
         assert_eq!(pkginfo.optdepends, vec!["myoptdep", "secopdep"]);
         assert_eq!(pkginfo.makedepends, vec!["test"]);
         assert_eq!(pkginfo.checkdepends, vec!["test"]);
+        assert_eq!(pkginfo.backup, vec!["etc/test.conf"]);
+        assert_eq!(pkginfo.xdata, vec!["pkgtype=pkg"]);
     }
 }
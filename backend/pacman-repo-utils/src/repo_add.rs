@@ -1,8 +1,12 @@
 use anyhow::{anyhow, bail};
 
 use crate::pkginfo::parser::Pkginfo;
-use crate::repo_database::db::add_to_db_file;
 use crate::repo_database::desc::Desc;
+use crate::repo_database::index::RepoIndex;
+use crate::repo_database::lock::RepoLock;
+use bzip2::read::BzDecoder;
+use flate2::read::GzDecoder;
+use lz4_flex::frame::FrameDecoder as Lz4Decoder;
 use sha2::{Digest, Sha256};
 use std::fs::{self, File};
 use std::io::{BufReader, Read};
@@ -12,37 +16,100 @@ use tracing::{debug, error, warn};
 use xz2::read::XzDecoder;
 use zstd::stream::read::Decoder as ZstdDecoder;
 
+/// Database entry of a single package file
+pub(crate) struct RepoEntry {
+    pub(crate) dir_name: String,
+    pub(crate) desc: String,
+    pub(crate) files: String,
+}
+
 pub fn repo_add(pkgfile: &str, db_archive: String, files_archive: String) -> anyhow::Result<()> {
+    repo_add_many(&[pkgfile], db_archive, files_archive)
+}
+
+/// Add several package files to the repo, writing each database archive only once
+pub fn repo_add_many<S: AsRef<str>>(
+    pkgfiles: &[S],
+    db_archive: String,
+    files_archive: String,
+) -> anyhow::Result<()> {
+    repo_update(pkgfiles, &[] as &[&str], db_archive, files_archive)
+}
+
+/// Remove the package files `remove` and add `pkgfiles` to the repo in one batch,
+/// writing each database archive only once
+pub fn repo_update<A: AsRef<str>, R: AsRef<str>>(
+    pkgfiles: &[A],
+    remove: &[R],
+    db_archive: String,
+    files_archive: String,
+) -> anyhow::Result<()> {
+    let lock = RepoLock::acquire(&db_archive)?;
+    repo_update_locked(&lock, pkgfiles, remove, db_archive, files_archive)
+}
+
+/// [`repo_update`] for callers already holding the [`RepoLock`] of `db_archive`,
+/// e.g. to keep the package files and the database consistent while publishing a build
+pub fn repo_update_locked<A: AsRef<str>, R: AsRef<str>>(
+    _lock: &RepoLock,
+    pkgfiles: &[A],
+    remove: &[R],
+    db_archive: String,
+    files_archive: String,
+) -> anyhow::Result<()> {
+    // parse all packages first, so a broken package doesn't leave a half updated repo
+    let entries = pkgfiles
+        .iter()
+        .map(|pkgfile| read_entry(pkgfile.as_ref()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut db_index = RepoIndex::load(&db_archive)?;
+    let mut files_index = RepoIndex::load(&files_archive)?;
+
+    // remove first, an old file may share its `pkgname-pkgver` entry with a new one
+    for filename in remove {
+        let dir_name = entry_dir_name(filename.as_ref());
+        db_index.remove(dir_name);
+        files_index.remove(dir_name);
+    }
+
+    debug!("Adding DESC and FILES entries to db archive");
+    for entry in entries {
+        db_index.insert(&entry.dir_name, "desc", entry.desc.clone());
+        files_index.insert(&entry.dir_name, "desc", entry.desc);
+        files_index.insert(&entry.dir_name, "files", entry.files);
+    }
+
+    db_index.save()?;
+    files_index.save()?;
+    Ok(())
+}
+
+/// Database entry (`pkgname-pkgver-pkgrel`) of a package file name
+pub(crate) fn entry_dir_name(filename: &str) -> &str {
+    filename
+        .rsplit_once('-')
+        .map_or(filename, |(dir_name, _)| dir_name)
+}
+
+pub(crate) fn read_entry(pkgfile: &str) -> anyhow::Result<RepoEntry> {
     let mut files = vec![];
     let mut pkginfo = Pkginfo::new();
 
-    // Path to the .tar.zst file
-    let file = File::open(Path::new(pkgfile))?;
-    let ext = Path::new(pkgfile).extension().and_then(|e| e.to_str());
-
-    // Select the appropriate decompression method
-    let decompressor: Box<dyn Read> = match ext {
-        Some("zst") => {
-            let decoder = ZstdDecoder::new(BufReader::new(file))?;
-            Box::new(decoder)
-        }
-        Some("xz") => {
-            let decoder = XzDecoder::new(BufReader::new(file));
-            Box::new(decoder)
-        }
-        _ => {
-            bail!("Unsupported file type");
-        }
-    };
-    let mut archive = Archive::new(decompressor);
+    let mut archive = Archive::new(open_package(pkgfile)?);
 
     // Iterate over the entries in the tar archive
     for entry in archive.entries()? {
         match entry {
             Ok(entry) => {
                 if let Ok(path) = entry.path() {
-                    if !path.display().to_string().starts_with('.') {
-                        files.push(format!("{}", path.display()));
+                    let mut file = path.display().to_string();
+                    if !file.starts_with('.') {
+                        // `bsdtar -t` lists directories with a trailing slash
+                        if entry.header().entry_type().is_dir() && !file.ends_with('/') {
+                            file.push('/');
+                        }
+                        files.push(file);
                     }
 
                     if path == Path::new(".PKGINFO") {
@@ -85,30 +152,36 @@ pub fn repo_add(pkgfile: &str, db_archive: String, files_archive: String) -> any
     desc.md5sum = md5sum.clone();
     desc.csize = csize.to_string();
     desc.sha256sum = sha256sum.clone();
-    let desc_str = desc.to_string();
-
-    debug!("Adding DESC and FILES entries to db archive");
-    add_to_db_file(
-        desc_str.clone(),
-        dir_name.clone(),
-        "desc".to_string(),
-        db_archive,
-    )?;
 
+    // same as `bsdtar -tf | LC_ALL=C sort -u` in repo-add
     files.sort();
-    let files_comb = format!("%FILES%\n{}", files.join("\n"));
-    add_to_db_file(
-        desc_str,
-        dir_name.clone(),
-        "desc".to_string(),
-        files_archive.clone(),
-    )?;
-    add_to_db_file(files_comb, dir_name, "files".to_string(), files_archive)?;
+    files.dedup();
+    Ok(RepoEntry {
+        dir_name,
+        desc: desc.to_string(),
+        files: files
+            .iter()
+            .fold("%FILES%\n".to_string(), |acc, f| acc + f + "\n"),
+    })
+}
 
-    Ok(())
+/// Open a package file with the decompression matching its `PKGEXT`
+pub(crate) fn open_package(pkgfile: &str) -> anyhow::Result<Box<dyn Read>> {
+    let file = BufReader::new(File::open(Path::new(pkgfile))?);
+    let ext = Path::new(pkgfile).extension().and_then(|e| e.to_str());
+
+    Ok(match ext {
+        Some("zst") => Box::new(ZstdDecoder::with_buffer(file)?),
+        Some("xz") => Box::new(XzDecoder::new(file)),
+        Some("gz") => Box::new(GzDecoder::new(file)),
+        Some("bz2") => Box::new(BzDecoder::new(file)),
+        Some("lz4") => Box::new(Lz4Decoder::new(file)),
+        Some("tar") => Box::new(file),
+        _ => bail!("Unsupported file type"),
+    })
 }
 
-fn calc_checksums(path: &str) -> anyhow::Result<(String, String)> {
+pub fn calc_checksums(path: &str) -> anyhow::Result<(String, String)> {
     let mut file = File::open(path)?;
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer)?;
@@ -123,3 +196,113 @@ fn calc_checksums(path: &str) -> anyhow::Result<(String, String)> {
 
     Ok((md5sum, sha256sum))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo_database::desc::parse_desc_fields;
+    use crate::test_utils::{compress, package_tar, simple_package};
+
+    #[test]
+    fn read_all_package_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        let tar = simple_package();
+
+        for pkgext in [".zst", ".xz", ".gz", ".bz2", ".lz4", ""] {
+            let name = format!("foo-1.0-1-any.pkg.tar{pkgext}");
+            let path = dir.path().join(&name);
+            fs::write(&path, compress(pkgext.trim_start_matches('.'), &tar)).unwrap();
+
+            let entry = read_entry(&path.to_string_lossy()).unwrap();
+            assert_eq!(entry.dir_name, "foo-1.0-1");
+            assert!(entry.desc.contains(&format!("%FILENAME%\n{name}\n")));
+            assert_eq!(entry.files, "%FILES%\nusr/bin/foo\n");
+        }
+    }
+
+    #[test]
+    fn update_removes_before_adding() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("repo.db.tar.gz");
+        let files = dir.path().join("repo.files.tar.gz");
+        let old = dir.path().join("foo-1.0-1-any.pkg.tar.gz");
+        let new = dir.path().join("foo-1.0-1-any.pkg.tar.zst");
+        let bar = dir.path().join("bar-2.0-1-any.pkg.tar.gz");
+        fs::write(&old, compress("gz", &simple_package())).unwrap();
+        fs::write(&new, compress("zst", &simple_package())).unwrap();
+        fs::write(
+            &bar,
+            compress(
+                "gz",
+                &package_tar(
+                    "pkgname = bar\npkgver = 2.0-1\narch = any\nsize = 3\n",
+                    &["usr/bin/bar"],
+                ),
+            ),
+        )
+        .unwrap();
+        let db_str = db.to_string_lossy().to_string();
+        let files_str = files.to_string_lossy().to_string();
+
+        repo_add_many(
+            &[old.to_string_lossy(), bar.to_string_lossy()],
+            db_str.clone(),
+            files_str.clone(),
+        )
+        .unwrap();
+        // the rebuilt file has the same entry, it must survive the removal of the old one
+        repo_update(
+            &[new.to_string_lossy()],
+            &["foo-1.0-1-any.pkg.tar.gz", "bar-2.0-1-any.pkg.tar.gz"],
+            db_str,
+            files_str,
+        )
+        .unwrap();
+
+        for archive in [&db, &files] {
+            let index = RepoIndex::load(archive).unwrap();
+            assert_eq!(index.dirs().collect::<Vec<_>>(), vec!["foo-1.0-1"]);
+            let desc = String::from_utf8(index.get("foo-1.0-1", "desc").unwrap().to_vec()).unwrap();
+            assert!(desc.contains("%FILENAME%\nfoo-1.0-1-any.pkg.tar.zst\n"));
+        }
+    }
+
+    #[test]
+    fn unsupported_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("foo-1.0-1-any.pkg.tar.rar");
+        fs::write(&path, simple_package()).unwrap();
+        assert!(read_entry(&path.to_string_lossy()).is_err());
+    }
+
+    /// Compare against the entries pacman's repo-add writes for the same packages,
+    /// see `testdata/repo-add/README.md`
+    #[test]
+    fn fixture_desc_and_files() {
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/repo-add");
+
+        let mut samples = fs::read_dir(&corpus)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.is_dir())
+            .collect::<Vec<_>>();
+        samples.sort();
+        assert!(!samples.is_empty());
+
+        for sample in samples {
+            let read = |name: &str| fs::read_to_string(sample.join(name)).unwrap();
+            let expected_desc = read("desc");
+            let fields = parse_desc_fields(&expected_desc);
+            let pkgfile = sample.join(&fields["FILENAME"][0]);
+            assert!(pkgfile.exists(), "{} is missing", pkgfile.display());
+
+            let entry = read_entry(&pkgfile.to_string_lossy()).unwrap();
+            assert_eq!(
+                entry.dir_name,
+                format!("{}-{}", fields["NAME"][0], fields["VERSION"][0])
+            );
+            assert_eq!(entry.desc, expected_desc, "desc of {}", sample.display());
+            assert_eq!(entry.files, read("files"), "files of {}", sample.display());
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::repo_add::calc_checksums;
+use crate::repo_database::desc::parse_desc_fields;
+use crate::repo_database::index::RepoIndex;
+use crate::repo_rebuild::package_files;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Inconsistencies between a repo database and the package files next to it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoCheck {
+    /// package files without a database entry
+    pub unindexed_files: Vec<String>,
+    /// database entries whose package file doesn't exist
+    pub missing_files: Vec<String>,
+    /// database entries whose sha256sum doesn't match the package file
+    pub checksum_mismatches: Vec<String>,
+}
+
+impl RepoCheck {
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.unindexed_files.is_empty()
+            && self.missing_files.is_empty()
+            && self.checksum_mismatches.is_empty()
+    }
+}
+
+/// Compare the entries of `db_archive` with the package files in `repo_dir`
+pub fn repo_check(repo_dir: &Path, db_archive: &str) -> anyhow::Result<RepoCheck> {
+    let index = RepoIndex::load(db_archive)?;
+    let on_disk: BTreeSet<String> = package_files(repo_dir)?.into_iter().collect();
+    let mut check = RepoCheck::default();
+    let mut indexed = BTreeSet::new();
+
+    for dir in index.dirs() {
+        let Some(desc) = index.get(dir, "desc") else {
+            continue;
+        };
+        let fields = parse_desc_fields(&String::from_utf8_lossy(desc));
+        let Some(filename) = fields.get("FILENAME").and_then(|f| f.first()) else {
+            continue;
+        };
+        indexed.insert(filename.clone());
+
+        if !on_disk.contains(filename) {
+            check.missing_files.push(filename.clone());
+            continue;
+        }
+
+        let expected = fields.get("SHA256SUM").and_then(|f| f.first());
+        let (_, sha256sum) = calc_checksums(&repo_dir.join(filename).to_string_lossy())?;
+        if expected != Some(&sha256sum) {
+            check.checksum_mismatches.push(filename.clone());
+        }
+    }
+
+    check.unindexed_files = on_disk.difference(&indexed).cloned().collect();
+    Ok(check)
+}
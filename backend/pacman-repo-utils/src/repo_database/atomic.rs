@@ -0,0 +1,53 @@
+use anyhow::anyhow;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+/// Replace the file at `path` with `data` without ever exposing a partially written file.
+///
+/// The data is written to a temporary file in the same directory, synced to disk and
+/// renamed over the target, so readers either see the old or the new archive.
+pub fn write_atomic(path: &Path, data: &[u8]) -> anyhow::Result<()> {
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let file_name = path
+        .file_name()
+        .ok_or(anyhow!("invalid path: {}", path.display()))?
+        .to_string_lossy();
+    let tmp_path = dir.join(format!(".{file_name}.tmp.{}", std::process::id()));
+
+    let result = (|| {
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(data)?;
+        tmp.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        // persist the rename itself
+        File::open(dir)?.sync_all()?;
+        anyhow::Ok(())
+    })();
+
+    if result.is_err() {
+        _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("repo.db.tar.gz");
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, b"new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        // no temp files are left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::anyhow;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::fmt;
+use std::io::{BufRead, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+use xz2::read::XzDecoder;
+use xz2::write::XzEncoder;
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Compression of a repo database archive, same choices as `repo-add`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DbCompression {
+    #[default]
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl DbCompression {
+    /// File extension following `.tar`, e.g. `repo.db.tar.zst`
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            DbCompression::Gzip => "gz",
+            DbCompression::Zstd => "zst",
+            DbCompression::Xz => "xz",
+        }
+    }
+
+    /// Compression matching the extension of `path`
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "gz" => Some(DbCompression::Gzip),
+            "zst" => Some(DbCompression::Zstd),
+            "xz" => Some(DbCompression::Xz),
+            _ => None,
+        }
+    }
+
+    /// Compression identified by the magic bytes at the start of an archive
+    #[must_use]
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(GZIP_MAGIC) {
+            Some(DbCompression::Gzip)
+        } else if header.starts_with(ZSTD_MAGIC) {
+            Some(DbCompression::Zstd)
+        } else if header.starts_with(XZ_MAGIC) {
+            Some(DbCompression::Xz)
+        } else {
+            None
+        }
+    }
+
+    pub fn decoder<'a>(self, reader: impl BufRead + 'a) -> anyhow::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            DbCompression::Gzip => Box::new(GzDecoder::new(reader)),
+            DbCompression::Zstd => Box::new(ZstdDecoder::with_buffer(reader)?),
+            DbCompression::Xz => Box::new(XzDecoder::new(reader)),
+        })
+    }
+
+    /// Compress a complete tar archive
+    pub fn compress(self, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        Ok(match self {
+            DbCompression::Gzip => {
+                let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+                enc.write_all(data)?;
+                enc.finish()?
+            }
+            DbCompression::Zstd => zstd::encode_all(data, 0)?,
+            DbCompression::Xz => {
+                let mut enc = XzEncoder::new(Vec::new(), 6);
+                enc.write_all(data)?;
+                enc.finish()?
+            }
+        })
+    }
+}
+
+impl FromStr for DbCompression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "gzip" | "gz" => Ok(DbCompression::Gzip),
+            "zstd" | "zst" => Ok(DbCompression::Zstd),
+            "xz" => Ok(DbCompression::Xz),
+            other => Err(anyhow!(
+                "unknown repo compression '{other}', expected gzip, zstd or xz"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DbCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DbCompression::Gzip => "gzip",
+            DbCompression::Zstd => "zstd",
+            DbCompression::Xz => "xz",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_and_detect() {
+        let data = b"some tar content".repeat(64);
+        for compression in [DbCompression::Gzip, DbCompression::Zstd, DbCompression::Xz] {
+            let compressed = compression.compress(&data).unwrap();
+            assert_eq!(DbCompression::detect(&compressed), Some(compression));
+
+            let mut decoded = Vec::new();
+            compression
+                .decoder(compressed.as_slice())
+                .unwrap()
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, data);
+        }
+        assert_eq!(DbCompression::detect(b"ustar"), None);
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "zstd".parse::<DbCompression>().unwrap(),
+            DbCompression::Zstd
+        );
+        assert_eq!("GZ".parse::<DbCompression>().unwrap(), DbCompression::Gzip);
+        assert!("bzip2".parse::<DbCompression>().is_err());
+        assert_eq!(
+            DbCompression::from_path("repo.db.tar.xz"),
+            Some(DbCompression::Xz)
+        );
+        assert_eq!(DbCompression::from_path("repo.db"), None);
+    }
+}
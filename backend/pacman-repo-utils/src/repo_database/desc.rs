@@ -1,4 +1,5 @@
 use crate::pkginfo::parser::Pkginfo;
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 
 pub struct Desc {
@@ -25,6 +26,7 @@ pub struct Desc {
     pub optdepends: Vec<String>,
     pub makedepends: Vec<String>,
     pub checkdepends: Vec<String>,
+    pub xdata: Vec<String>,
 }
 
 impl Display for Desc {
@@ -53,6 +55,7 @@ impl Display for Desc {
             self.add_desc_entries("optdepends", &self.optdepends),
             self.add_desc_entries("makedepends", &self.makedepends),
             self.add_desc_entries("checkdepends", &self.checkdepends),
+            self.add_desc_entries("xdata", &self.xdata),
         ];
         write!(f, "{}", desc_lines.join(""))
     }
@@ -106,15 +109,55 @@ impl From<Pkginfo> for Desc {
             optdepends: value.optdepends,
             makedepends: value.makedepends,
             checkdepends: value.checkdepends,
+            xdata: value.xdata,
             sha256sum: String::new(),
         }
     }
 }
 
+/// Parse the `%KEY%` sections of a desc (or files) entry into a map of key to values
+#[must_use]
+pub fn parse_desc_fields(content: &str) -> BTreeMap<String, Vec<String>> {
+    let mut fields: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut current: Option<String> = None;
+
+    for line in content.lines() {
+        if line.len() > 2 && line.starts_with('%') && line.ends_with('%') {
+            let key = line.trim_matches('%').to_string();
+            fields.entry(key.clone()).or_default();
+            current = Some(key);
+        } else if line.is_empty() {
+            current = None;
+        } else if let Some(key) = &current {
+            fields
+                .entry(key.clone())
+                .or_default()
+                .push(line.to_string());
+        }
+    }
+    fields
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_fields() {
+        let fields = parse_desc_fields(
+            "%FILENAME%\nfoo-1.0-1-x86_64.pkg.tar.zst\n\n%DEPENDS%\nglibc\nbash\n\n",
+        );
+        assert_eq!(
+            fields.get("FILENAME"),
+            Some(&vec!["foo-1.0-1-x86_64.pkg.tar.zst".to_string()])
+        );
+        assert_eq!(
+            fields.get("DEPENDS"),
+            Some(&vec!["glibc".to_string(), "bash".to_string()])
+        );
+        assert_eq!(fields.get("NAME"), None);
+    }
+
     #[test]
     fn test_desc_to_string() {
         let desc = Desc {
@@ -141,6 +184,7 @@ mod tests {
             optdepends: vec!["test".to_string()],
             makedepends: vec!["test".to_string()],
             checkdepends: vec!["test".to_string()],
+            xdata: vec!["pkgtype=pkg".to_string()],
         };
 
         let expected = "\
@@ -214,6 +258,9 @@ test
 %CHECKDEPENDS%
 test
 
+%XDATA%
+pkgtype=pkg
+
 ";
         assert_eq!(desc.to_string(), expected);
     }
@@ -240,6 +287,8 @@ test
             optdepends: vec!["test".to_string()],
             makedepends: vec!["test".to_string()],
             checkdepends: vec!["test".to_string()],
+            backup: vec!["etc/test.conf".to_string()],
+            xdata: vec!["pkgtype=pkg".to_string()],
         };
 
         let desc = Desc::from(pkginfo);
@@ -268,5 +317,6 @@ test
         assert_eq!(desc.provides, vec!["test".to_string()]);
         assert_eq!(desc.depends, vec!["test".to_string()]);
         assert_eq!(desc.optdepends, vec!["test".to_string()]);
+        assert_eq!(desc.xdata, vec!["pkgtype=pkg".to_string()]);
     }
 }
@@ -0,0 +1,250 @@
+use crate::repo_database::atomic::write_atomic;
+use crate::repo_database::compression::DbCompression;
+use anyhow::anyhow;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use tar::{Archive, Builder, EntryType, Header};
+
+/// In-memory index of a repo database archive (`*.db.tar.*` or `*.files.tar.*`).
+///
+/// The archive is decoded once on [`RepoIndex::load`], modified per package and
+/// encoded once on [`RepoIndex::save`], instead of rewriting it for every single change.
+/// `path` may also be the `repo.db` symlink, in which case the archive it points to is used.
+#[derive(Debug, Clone)]
+pub struct RepoIndex {
+    path: PathBuf,
+    compression: DbCompression,
+    /// entry directory (`pkgname-pkgver`) -> file name (`desc`, `files`) -> content
+    entries: BTreeMap<String, BTreeMap<String, Vec<u8>>>,
+    dirty: bool,
+}
+
+impl RepoIndex {
+    /// Decode the archive at `path`. A missing archive results in an empty index.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = resolve_archive(path.as_ref())?;
+        let mut compression = DbCompression::from_path(&path).unwrap_or_default();
+        let mut entries: BTreeMap<String, BTreeMap<String, Vec<u8>>> = BTreeMap::new();
+
+        if path.exists() {
+            let mut reader = BufReader::new(File::open(&path)?);
+            // trust the content over the file name, `repo-add` doesn't check either
+            compression = DbCompression::detect(reader.fill_buf()?).ok_or_else(|| {
+                anyhow!("Unknown compression of repo archive '{}'", path.display())
+            })?;
+            let mut archive = Archive::new(compression.decoder(reader)?);
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                let entry_path = entry.path()?.to_string_lossy().to_string();
+                let entry_path = entry_path.trim_end_matches('/');
+
+                match entry_path.split_once('/') {
+                    None => {
+                        entries.entry(entry_path.to_string()).or_default();
+                    }
+                    Some((dir, file)) => {
+                        let mut content = Vec::new();
+                        entry.read_to_end(&mut content)?;
+                        entries
+                            .entry(dir.to_string())
+                            .or_default()
+                            .insert(file.to_string(), content);
+                    }
+                }
+            }
+        }
+
+        Ok(RepoIndex {
+            path,
+            compression,
+            entries,
+            dirty: false,
+        })
+    }
+
+    /// Empty index which replaces the archive at `path` on save
+    pub fn empty(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = resolve_archive(path.as_ref())?;
+        Ok(RepoIndex {
+            compression: DbCompression::from_path(&path).unwrap_or_default(),
+            path,
+            entries: BTreeMap::new(),
+            dirty: true,
+        })
+    }
+
+    /// Archive the index is written to
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    #[must_use]
+    pub fn compression(&self) -> DbCompression {
+        self.compression
+    }
+
+    /// Add or replace a file of a repo entry
+    pub fn insert(&mut self, dir_name: &str, file_name: &str, content: impl Into<Vec<u8>>) {
+        self.entries
+            .entry(dir_name.to_string())
+            .or_default()
+            .insert(file_name.to_string(), content.into());
+        self.dirty = true;
+    }
+
+    /// Remove a repo entry with all its files, returns whether it existed
+    pub fn remove(&mut self, dir_name: &str) -> bool {
+        let removed = self.entries.remove(dir_name).is_some();
+        self.dirty |= removed;
+        removed
+    }
+
+    #[must_use]
+    pub fn contains(&self, dir_name: &str) -> bool {
+        self.entries.contains_key(dir_name)
+    }
+
+    #[must_use]
+    pub fn get(&self, dir_name: &str, file_name: &str) -> Option<&[u8]> {
+        self.entries
+            .get(dir_name)
+            .and_then(|files| files.get(file_name))
+            .map(Vec::as_slice)
+    }
+
+    /// Names of all entry directories
+    pub fn dirs(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Encode the index back to its archive, if anything changed since loading
+    pub fn save(&mut self) -> anyhow::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let mut builder = Builder::new(Vec::new());
+        for (dir, files) in &self.entries {
+            let mut header = Header::new_gnu();
+            header.set_path(dir)?;
+            header.set_entry_type(EntryType::Directory);
+            header.set_mode(0o755);
+            header.set_size(0);
+            header.set_cksum();
+            builder.append(&header, io::empty())?;
+
+            for (file, content) in files {
+                let mut header = Header::new_gnu();
+                header.set_path(format!("{dir}/{file}"))?;
+                header.set_size(content.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder.append(&header, content.as_slice())?;
+            }
+        }
+
+        let data = self.compression.compress(&builder.into_inner()?)?;
+        write_atomic(&self.path, &data)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Write the index to another archive, compressed according to its extension
+    pub fn save_as(&mut self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref().to_path_buf();
+        self.compression = DbCompression::from_path(&path).unwrap_or_default();
+        self.path = path;
+        self.dirty = true;
+        self.save()
+    }
+}
+
+/// Follow a `repo.db` style symlink to the archive it points to
+fn resolve_archive(path: &Path) -> anyhow::Result<PathBuf> {
+    match fs::read_link(path) {
+        Ok(target) => Ok(match path.parent() {
+            Some(parent) if target.is_relative() => parent.join(target),
+            _ => target,
+        }),
+        Err(e)
+            if e.kind() == io::ErrorKind::InvalidInput || e.kind() == io::ErrorKind::NotFound =>
+        {
+            Ok(path.to_path_buf())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_archive_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = RepoIndex::load(dir.path().join("repo.db.tar.gz")).unwrap();
+        assert_eq!(index.dirs().count(), 0);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("repo.files.tar.gz");
+
+        let mut index = RepoIndex::load(&path).unwrap();
+        index.insert("foo-1.0-1", "desc", "%NAME%\nfoo\n");
+        index.insert("foo-1.0-1", "files", "%FILES%\nusr/bin/foo");
+        index.insert("bar-2.0-1", "desc", "%NAME%\nbar\n");
+        index.save().unwrap();
+
+        let mut index = RepoIndex::load(&path).unwrap();
+        assert_eq!(
+            index.dirs().collect::<Vec<_>>(),
+            vec!["bar-2.0-1", "foo-1.0-1"]
+        );
+        assert_eq!(
+            index.get("foo-1.0-1", "files"),
+            Some("%FILES%\nusr/bin/foo".as_bytes())
+        );
+
+        assert!(index.remove("foo-1.0-1"));
+        assert!(!index.remove("foo-1.0-1"));
+        index.save().unwrap();
+
+        let index = RepoIndex::load(&path).unwrap();
+        assert!(!index.contains("foo-1.0-1"));
+        assert!(index.contains("bar-2.0-1"));
+    }
+
+    #[test]
+    fn follows_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink("repo.db.tar.xz", dir.path().join("repo.db")).unwrap();
+
+        let mut index = RepoIndex::load(dir.path().join("repo.db")).unwrap();
+        assert_eq!(index.path(), dir.path().join("repo.db.tar.xz"));
+        assert_eq!(index.compression(), DbCompression::Xz);
+        index.insert("foo-1.0-1", "desc", "%NAME%\nfoo\n");
+        index.save().unwrap();
+
+        assert!(
+            fs::symlink_metadata(dir.path().join("repo.db"))
+                .unwrap()
+                .is_symlink()
+        );
+        let index = RepoIndex::load(dir.path().join("repo.db")).unwrap();
+        assert!(index.contains("foo-1.0-1"));
+    }
+
+    #[test]
+    fn insert_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut index = RepoIndex::load(dir.path().join("repo.db.tar.gz")).unwrap();
+        index.insert("foo-1.0-1", "desc", "old");
+        index.insert("foo-1.0-1", "desc", "new");
+        assert_eq!(index.get("foo-1.0-1", "desc"), Some("new".as_bytes()));
+    }
+}
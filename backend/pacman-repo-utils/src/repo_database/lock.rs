@@ -0,0 +1,49 @@
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Advisory lock serializing all writers of a repo database.
+///
+/// The lock is held on a `<db_archive>.lck` file next to the database and released on drop.
+pub struct RepoLock {
+    _file: File,
+}
+
+impl RepoLock {
+    /// Block until the lock of the repo database `db_archive` is acquired
+    pub fn acquire(db_archive: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = Self::lock_path(db_archive.as_ref());
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+        debug!("Waiting for repo lock '{}'", path.display());
+        file.lock()?;
+        Ok(RepoLock { _file: file })
+    }
+
+    fn lock_path(db_archive: &Path) -> PathBuf {
+        let mut path = db_archive.as_os_str().to_owned();
+        path.push(".lck");
+        PathBuf::from(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("repo.db.tar.gz");
+
+        let lock = RepoLock::acquire(&db).unwrap();
+        let other = File::open(dir.path().join("repo.db.tar.gz.lck")).unwrap();
+        assert!(other.try_lock().is_err());
+
+        drop(lock);
+        assert!(other.try_lock().is_ok());
+    }
+}
@@ -1,2 +1,5 @@
-pub mod db;
+pub mod atomic;
+pub mod compression;
 pub mod desc;
+pub mod index;
+pub mod lock;
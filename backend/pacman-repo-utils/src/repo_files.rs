@@ -0,0 +1,143 @@
+use crate::repo_database::index::RepoIndex;
+use crate::repo_packages::RepoPackage;
+
+/// File list of a single package in the repo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageFiles {
+    pub name: String,
+    pub version: String,
+    /// paths without leading slash, directories end with `/`
+    pub files: Vec<String>,
+}
+
+/// Package match of [`FilesIndex::search`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMatch<'a> {
+    pub package: &'a PackageFiles,
+    pub path: &'a str,
+}
+
+/// Lookup table of the `files` entries of a `repo.files` database, like `pacman -F`
+#[derive(Debug, Clone, Default)]
+pub struct FilesIndex {
+    packages: Vec<PackageFiles>,
+}
+
+impl FilesIndex {
+    pub fn load(files_archive: &str) -> anyhow::Result<Self> {
+        let index = RepoIndex::load(files_archive)?;
+        let mut packages: Vec<PackageFiles> = index
+            .dirs()
+            .filter_map(|dir| {
+                let desc =
+                    RepoPackage::from_desc(&String::from_utf8_lossy(index.get(dir, "desc")?));
+                let files = String::from_utf8_lossy(index.get(dir, "files")?)
+                    .lines()
+                    .skip_while(|line| *line != "%FILES%")
+                    .skip(1)
+                    .take_while(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                Some(PackageFiles {
+                    name: desc.name,
+                    version: desc.version,
+                    files,
+                })
+            })
+            .collect();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(FilesIndex { packages })
+    }
+
+    #[must_use]
+    pub fn packages(&self) -> &[PackageFiles] {
+        &self.packages
+    }
+
+    /// Files of the package `name`
+    #[must_use]
+    pub fn package(&self, name: &str) -> Option<&PackageFiles> {
+        self.packages.iter().find(|p| p.name == name)
+    }
+
+    /// Packages owning `query`.
+    ///
+    /// A query containing `/` is matched against the full path, e.g. `/usr/bin/foo`,
+    /// anything else against the file name only, e.g. `foo`.
+    #[must_use]
+    pub fn search(&self, query: &str) -> Vec<FileMatch<'_>> {
+        let query = query.trim();
+        let full_path = query.contains('/');
+        let query = query.trim_start_matches('/');
+        if query.is_empty() {
+            return vec![];
+        }
+
+        self.packages
+            .iter()
+            .flat_map(|package| {
+                package
+                    .files
+                    .iter()
+                    .filter(move |file| {
+                        if full_path {
+                            file.as_str() == query
+                                || file.strip_suffix('/').is_some_and(|dir| dir == query)
+                        } else {
+                            file_name(file) == query
+                        }
+                    })
+                    .map(move |path| FileMatch { package, path })
+            })
+            .collect()
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    let path = path.strip_suffix('/').unwrap_or(path);
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("repo.files.tar.gz");
+        let mut index = RepoIndex::load(&path).unwrap();
+        for (name, files) in [
+            ("foo", "usr/\nusr/bin/\nusr/bin/foo\nusr/share/foo/README\n"),
+            ("bar", "usr/\nusr/bin/\nusr/bin/bar\nusr/lib/foo\n"),
+        ] {
+            let dir_name = format!("{name}-1.0-1");
+            index.insert(
+                &dir_name,
+                "desc",
+                format!("%NAME%\n{name}\n\n%VERSION%\n1.0-1\n\n"),
+            );
+            index.insert(&dir_name, "files", format!("%FILES%\n{files}"));
+        }
+        index.save().unwrap();
+
+        let files = FilesIndex::load(&path.to_string_lossy()).unwrap();
+        assert_eq!(
+            files.package("bar").unwrap().files,
+            vec!["usr/", "usr/bin/", "usr/bin/bar", "usr/lib/foo"]
+        );
+
+        let owners = |query| {
+            files
+                .search(query)
+                .into_iter()
+                .map(|m| format!("{} {}", m.package.name, m.path))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(owners("/usr/bin/foo"), vec!["foo usr/bin/foo"]);
+        assert_eq!(owners("foo"), vec!["bar usr/lib/foo", "foo usr/bin/foo"]);
+        assert_eq!(owners("/usr/bin"), vec!["bar usr/bin/", "foo usr/bin/"]);
+        assert!(owners("README.md").is_empty());
+        assert!(owners("/").is_empty());
+    }
+}
@@ -1,14 +1,15 @@
+use crate::repo_database::compression::DbCompression;
+use crate::repo_database::index::RepoIndex;
+use crate::repo_database::lock::RepoLock;
 use anyhow::{anyhow, bail};
-use flate2::Compression;
-use flate2::read::GzEncoder;
 use std::fs;
-use std::fs::File;
 use std::os::unix::fs::symlink;
 use std::path::{Path, PathBuf};
 use tracing::info;
 
-pub fn init_repo(path: &PathBuf, name: &str) -> anyhow::Result<()> {
-    if repo_exists(path, name).is_ok() {
+/// Create the repo archives and their symlinks, or convert existing ones to `compression`
+pub fn init_repo(path: &PathBuf, name: &str, compression: DbCompression) -> anyhow::Result<()> {
+    if repo_exists(path, name, compression).is_ok() {
         info!(
             "Pacman repo '{}' archive already exists at path '{}'",
             name,
@@ -18,46 +19,111 @@ pub fn init_repo(path: &PathBuf, name: &str) -> anyhow::Result<()> {
     }
 
     // create repo folder
-    info!("Initializing empty pacman Repo archive");
+    info!("Initializing pacman Repo archive with {compression} compression");
     _ = fs::create_dir_all(path);
 
-    create_empty_archive(path, name, "db")?;
-    create_empty_archive(path, name, "files")?;
+    let _lock = RepoLock::acquire(path.join(format!("{name}.db")))?;
+    init_archive(path, name, "db", compression)?;
+    init_archive(path, name, "files", compression)?;
     Ok(())
 }
 
 /// check if repo archives and symlink exist
-fn repo_exists(path: &Path, name: &str) -> anyhow::Result<()> {
+fn repo_exists(path: &Path, name: &str, compression: DbCompression) -> anyhow::Result<()> {
     for suffix in ["db", "files"] {
-        let files = get_archive_names(name, suffix);
-        for file in [files.0, files.1] {
-            if fs::metadata(path.join(&file)).is_err() {
-                bail!("{file} doesn't exist");
-            }
+        let (file_name, symlink_name) = get_archive_names(name, suffix, compression);
+        if fs::metadata(path.join(&file_name)).is_err() {
+            bail!("{file_name} doesn't exist");
+        }
+        if fs::read_link(path.join(&symlink_name))? != Path::new(&file_name) {
+            bail!("{symlink_name} doesn't point to {file_name}");
         }
     }
     Ok(())
 }
 
 /// assembles filneame of archive and symlink
-fn get_archive_names(name: &str, suffix: &str) -> (String, String) {
-    let file_name = format!("{name}.{suffix}.tar.gz");
+fn get_archive_names(name: &str, suffix: &str, compression: DbCompression) -> (String, String) {
+    let file_name = format!("{name}.{suffix}.tar.{}", compression.extension());
     let symlink_name = format!("{name}.{suffix}");
     (file_name, symlink_name)
 }
 
-/// create empty archive and corresponding symlink
-fn create_empty_archive(path: &Path, name: &str, suffix: &str) -> anyhow::Result<()> {
-    let (archive_file_name, symlink_name) = get_archive_names(name, suffix);
+/// create the archive, recompressing the one the symlink currently points to,
+/// and (re)point the symlink to it
+fn init_archive(
+    path: &Path,
+    name: &str,
+    suffix: &str,
+    compression: DbCompression,
+) -> anyhow::Result<()> {
+    let (archive_file_name, symlink_name) = get_archive_names(name, suffix, compression);
     let archive_path = path.join(&archive_file_name);
     let symlink_path = path.join(&symlink_name);
 
-    let tar_gz = File::create(archive_path)?;
-    let enc = GzEncoder::new(tar_gz, Compression::default());
-    let mut tar = tar::Builder::new(enc);
-    tar.finish()
-        .map_err(|_| anyhow!("failed to create repo archive"))?;
-    symlink(archive_file_name, symlink_path)
-        .map_err(|_| anyhow!("failed to create repo symlink"))?;
+    // archive the symlink currently points to, e.g. `repo.db.tar.gz` before switching to zstd
+    let previous = fs::read_link(&symlink_path)
+        .map(|target| path.join(target))
+        .ok()
+        .filter(|p| p.exists());
+
+    match &previous {
+        Some(previous) if *previous != archive_path => {
+            info!(
+                "Converting '{}' to '{}'",
+                previous.display(),
+                archive_path.display()
+            );
+            RepoIndex::load(previous)?.save_as(&archive_path)?;
+        }
+        Some(_) => {}
+        None if archive_path.exists() => {}
+        None => RepoIndex::empty(&archive_path)?.save()?,
+    }
+
+    // swap the symlink atomically, pacman might be downloading it right now
+    let tmp_link = path.join(format!(".{symlink_name}.tmp.{}", std::process::id()));
+    _ = fs::remove_file(&tmp_link);
+    symlink(&archive_file_name, &tmp_link).map_err(|_| anyhow!("failed to create repo symlink"))?;
+    fs::rename(&tmp_link, &symlink_path).map_err(|_| anyhow!("failed to create repo symlink"))?;
+
+    if let Some(previous) = previous
+        && previous != archive_path
+    {
+        fs::remove_file(&previous)?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_and_convert() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_path_buf();
+
+        init_repo(&path, "repo", DbCompression::Gzip).unwrap();
+        assert!(path.join("repo.db.tar.gz").exists());
+        assert_eq!(
+            fs::read_link(path.join("repo.files")).unwrap(),
+            Path::new("repo.files.tar.gz")
+        );
+
+        let mut index = RepoIndex::load(path.join("repo.db")).unwrap();
+        index.insert("foo-1.0-1", "desc", "%NAME%\nfoo\n");
+        index.save().unwrap();
+
+        init_repo(&path, "repo", DbCompression::Zstd).unwrap();
+        assert!(!path.join("repo.db.tar.gz").exists());
+        assert_eq!(
+            fs::read_link(path.join("repo.db")).unwrap(),
+            Path::new("repo.db.tar.zst")
+        );
+
+        let index = RepoIndex::load(path.join("repo.db")).unwrap();
+        assert_eq!(index.compression(), DbCompression::Zstd);
+        assert!(index.contains("foo-1.0-1"));
+    }
+}
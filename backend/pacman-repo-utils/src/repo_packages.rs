@@ -0,0 +1,123 @@
+use crate::repo_database::desc::parse_desc_fields;
+use crate::repo_database::index::RepoIndex;
+use std::collections::BTreeMap;
+
+/// Package entry of a repo database, parsed from its `desc` file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoPackage {
+    pub filename: String,
+    pub name: String,
+    pub base: String,
+    pub version: String,
+    pub desc: String,
+    pub url: String,
+    pub arch: String,
+    pub packager: String,
+    /// compressed size of the package file in bytes
+    pub csize: u64,
+    /// installed size in bytes
+    pub isize: u64,
+    /// unix timestamp
+    pub builddate: i64,
+    pub licenses: Vec<String>,
+    pub groups: Vec<String>,
+    pub provides: Vec<String>,
+    pub depends: Vec<String>,
+    pub optdepends: Vec<String>,
+}
+
+impl RepoPackage {
+    #[must_use]
+    pub fn from_desc(desc: &str) -> Self {
+        let mut fields = parse_desc_fields(desc);
+        let mut list = |key: &str| fields.remove(key).unwrap_or_default();
+        let licenses = list("LICENSE");
+        let groups = list("GROUPS");
+        let provides = list("PROVIDES");
+        let depends = list("DEPENDS");
+        let optdepends = list("OPTDEPENDS");
+
+        let single = |key: &str| {
+            fields
+                .get(key)
+                .and_then(|values| values.first())
+                .cloned()
+                .unwrap_or_default()
+        };
+        RepoPackage {
+            filename: single("FILENAME"),
+            name: single("NAME"),
+            base: single("BASE"),
+            version: single("VERSION"),
+            desc: single("DESC"),
+            url: single("URL"),
+            arch: single("ARCH"),
+            packager: single("PACKAGER"),
+            csize: single("CSIZE").parse().unwrap_or_default(),
+            isize: single("ISIZE").parse().unwrap_or_default(),
+            builddate: single("BUILDDATE").parse().unwrap_or_default(),
+            licenses,
+            groups,
+            provides,
+            depends,
+            optdepends,
+        }
+    }
+
+    /// Case insensitive match of `query` on name, description or provides
+    #[must_use]
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.name.to_lowercase().contains(&query)
+            || self.desc.to_lowercase().contains(&query)
+            || self
+                .provides
+                .iter()
+                .any(|p| p.to_lowercase().contains(&query))
+    }
+}
+
+/// All packages of the repo database `db_archive`, sorted by name
+pub fn repo_packages(db_archive: &str) -> anyhow::Result<Vec<RepoPackage>> {
+    let index = RepoIndex::load(db_archive)?;
+    let packages: BTreeMap<String, RepoPackage> = index
+        .dirs()
+        .filter_map(|dir| index.get(dir, "desc"))
+        .map(|desc| RepoPackage::from_desc(&String::from_utf8_lossy(desc)))
+        .map(|pkg| (pkg.name.clone(), pkg))
+        .collect();
+    Ok(packages.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("repo.db.tar.gz");
+        let mut index = RepoIndex::load(&path).unwrap();
+        index.insert(
+            "zlib-ng-2.2.2-1",
+            "desc",
+            "%FILENAME%\nzlib-ng-2.2.2-1-x86_64.pkg.tar.zst\n\n%NAME%\nzlib-ng\n\n%VERSION%\n2.2.2-1\n\n%DESC%\nzlib replacement\n\n%CSIZE%\n1024\n\n%BUILDDATE%\n1718000000\n\n%PROVIDES%\nlibz-ng.so=2-64\n\n%DEPENDS%\nglibc\n\n",
+        );
+        index.insert("bar-1.0-1", "desc", "%NAME%\nbar\n\n%VERSION%\n1.0-1\n\n");
+        index.save().unwrap();
+
+        let packages = repo_packages(&path.to_string_lossy()).unwrap();
+        assert_eq!(
+            packages.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+            vec!["bar", "zlib-ng"]
+        );
+
+        let zlib = &packages[1];
+        assert_eq!(zlib.csize, 1024);
+        assert_eq!(zlib.builddate, 1_718_000_000);
+        assert_eq!(zlib.depends, vec!["glibc"]);
+        assert!(zlib.matches("LIBZ-NG"));
+        assert!(zlib.matches("replacement"));
+        assert!(!packages[0].matches("zlib"));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::repo_add::read_entry;
+use crate::repo_database::index::RepoIndex;
+use crate::repo_database::lock::RepoLock;
+use std::fs;
+use std::path::Path;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Default)]
+pub struct RebuildResult {
+    /// package files added to the new database
+    pub added: Vec<String>,
+    /// package files which couldn't be read, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+/// Regenerate the db and files archives from scratch from the package files in `repo_dir`
+pub fn repo_rebuild(
+    repo_dir: &Path,
+    db_archive: String,
+    files_archive: String,
+) -> anyhow::Result<RebuildResult> {
+    let lock = RepoLock::acquire(&db_archive)?;
+    repo_rebuild_locked(&lock, repo_dir, db_archive, files_archive)
+}
+
+/// [`repo_rebuild`] for callers already holding the [`RepoLock`] of `db_archive`
+pub fn repo_rebuild_locked(
+    _lock: &RepoLock,
+    repo_dir: &Path,
+    db_archive: String,
+    files_archive: String,
+) -> anyhow::Result<RebuildResult> {
+    let mut db_index = RepoIndex::empty(&db_archive)?;
+    let mut files_index = RepoIndex::empty(&files_archive)?;
+    let mut result = RebuildResult::default();
+
+    for filename in package_files(repo_dir)? {
+        let path = repo_dir.join(&filename);
+        match read_entry(&path.to_string_lossy()) {
+            Ok(entry) => {
+                db_index.insert(&entry.dir_name, "desc", entry.desc.clone());
+                files_index.insert(&entry.dir_name, "desc", entry.desc);
+                files_index.insert(&entry.dir_name, "files", entry.files);
+                result.added.push(filename);
+            }
+            Err(e) => {
+                warn!("Skipping unreadable package file '{filename}': {e}");
+                result.failed.push((filename, e.to_string()));
+            }
+        }
+    }
+
+    db_index.save()?;
+    files_index.save()?;
+    info!(
+        "Rebuilt repo database of '{}' with {} packages",
+        repo_dir.display(),
+        result.added.len()
+    );
+    Ok(result)
+}
+
+/// File names of all package archives in `repo_dir`, sorted
+pub fn package_files(repo_dir: &Path) -> anyhow::Result<Vec<String>> {
+    let mut files = vec![];
+    for entry in fs::read_dir(repo_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if is_package_file(&name) {
+            files.push(name);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn is_package_file(name: &str) -> bool {
+    !name.starts_with('.') && name.contains(".pkg.tar") && !name.ends_with(".sig")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn package_file_names() {
+        assert!(is_package_file("foo-1.0-1-x86_64.pkg.tar.zst"));
+        assert!(is_package_file("foo-1.0-1-any.pkg.tar.xz"));
+        assert!(!is_package_file("foo-1.0-1-any.pkg.tar.xz.sig"));
+        assert!(!is_package_file("repo.db.tar.gz"));
+        assert!(!is_package_file(".repo.db.tar.gz.tmp.1"));
+    }
+}
@@ -1,19 +1,18 @@
-use crate::repo_database::db::remove_from_db_file;
+use crate::repo_add::repo_update;
 
 pub fn repo_remove(
     filename: String,
     db_archive: String,
     files_archive: String,
 ) -> anyhow::Result<()> {
-    let (dir_name, _) = split_last_occurrence(filename.as_str(), '-');
-    remove_from_db_file(db_archive, dir_name.to_string())?;
-    remove_from_db_file(files_archive, dir_name.to_string())?;
-    Ok(())
+    repo_remove_many(&[filename], db_archive, files_archive)
 }
 
-fn split_last_occurrence(s: &str, delimiter: char) -> (&str, &str) {
-    match s.rfind(delimiter) {
-        Some(pos) => (&s[..pos], &s[pos + delimiter.len_utf8()..]),
-        None => (s, ""),
-    }
+/// Remove several package files from the repo, writing each database archive only once
+pub fn repo_remove_many<S: AsRef<str>>(
+    filenames: &[S],
+    db_archive: String,
+    files_archive: String,
+) -> anyhow::Result<()> {
+    repo_update(&[] as &[&str], filenames, db_archive, files_archive)
 }
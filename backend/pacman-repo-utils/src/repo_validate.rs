@@ -0,0 +1,152 @@
+use crate::pkginfo::parser::Pkginfo;
+use crate::repo_add::{calc_checksums, open_package};
+use anyhow::{anyhow, bail};
+use std::io;
+use std::path::Path;
+use tar::Archive;
+
+/// Compression extensions makepkg may append to `.pkg.tar`
+const PKG_EXTENSIONS: [&str; 6] = ["", ".zst", ".xz", ".gz", ".bz2", ".lz4"];
+
+/// Metadata of a package file which passed [`validate_package`]
+#[derive(Debug, Clone)]
+pub struct ValidatedPackage {
+    pub filename: String,
+    pub pkgname: String,
+    /// `[epoch:]pkgver-pkgrel`
+    pub pkgver: String,
+    pub arch: String,
+    pub sha256sum: String,
+}
+
+/// Check a built package before it is published to the repo of `platform`.
+///
+/// The archive has to decompress completely, contain a valid `.PKGINFO` whose arch is
+/// `platform` or `any`, and be named `pkgname-pkgver-pkgrel-arch.pkg.tar[.ext]`.
+pub fn validate_package(pkgfile: &Path, platform: &str) -> anyhow::Result<ValidatedPackage> {
+    let filename = pkgfile
+        .file_name()
+        .and_then(|f| f.to_str())
+        .ok_or(anyhow!("invalid path"))?
+        .to_string();
+    let path = pkgfile.to_str().ok_or(anyhow!("invalid path"))?;
+
+    let mut pkginfo = Pkginfo::new();
+    let mut archive = Archive::new(open_package(path)?);
+    for entry in archive
+        .entries()
+        .map_err(|e| anyhow!("{filename}: failed to read archive: {e}"))?
+    {
+        let mut entry = entry.map_err(|e| anyhow!("{filename}: corrupt archive: {e}"))?;
+        if entry.path()?.as_ref() == Path::new(".PKGINFO") {
+            pkginfo.parse(&mut entry)?;
+        }
+        // read every entry to the end, so a truncated archive is detected
+        io::copy(&mut entry, &mut io::sink())
+            .map_err(|e| anyhow!("{filename}: corrupt archive: {e}"))?;
+    }
+
+    if !pkginfo.valid() {
+        bail!("{filename}: missing or invalid .PKGINFO");
+    }
+    if pkginfo.arch != platform && pkginfo.arch != "any" {
+        bail!(
+            "{filename}: package arch '{}' doesn't match build platform '{platform}'",
+            pkginfo.arch
+        );
+    }
+
+    let expected = format!(
+        "{}-{}-{}.pkg.tar",
+        pkginfo.pkgname, pkginfo.pkgver, pkginfo.arch
+    );
+    let extension_valid = filename
+        .strip_prefix(&expected)
+        .is_some_and(|ext| PKG_EXTENSIONS.contains(&ext));
+    if !extension_valid {
+        bail!("{filename}: file name doesn't match .PKGINFO, expected '{expected}[.ext]'");
+    }
+
+    let (_, sha256sum) = calc_checksums(path)?;
+    Ok(ValidatedPackage {
+        filename,
+        pkgname: pkginfo.pkgname,
+        pkgver: pkginfo.pkgver,
+        arch: pkginfo.arch,
+        sha256sum,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{compress, package_tar, simple_package};
+    use std::fs;
+
+    fn write(dir: &Path, name: &str, data: &[u8]) -> std::path::PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn valid_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let tar = package_tar(
+            "pkgname = foo-bar\npkgver = 1:2.0-3\narch = x86_64\n",
+            &["usr/bin/foo"],
+        );
+        let path = write(
+            dir.path(),
+            "foo-bar-1:2.0-3-x86_64.pkg.tar.zst",
+            &compress("zst", &tar),
+        );
+
+        let pkg = validate_package(&path, "x86_64").unwrap();
+        assert_eq!(pkg.pkgname, "foo-bar");
+        assert_eq!(pkg.pkgver, "1:2.0-3");
+        assert!(validate_package(&path, "aarch64").is_err());
+    }
+
+    #[test]
+    fn any_arch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            "foo-1.0-1-any.pkg.tar.xz",
+            &compress("xz", &simple_package()),
+        );
+        assert!(validate_package(&path, "aarch64").is_ok());
+    }
+
+    #[test]
+    fn filename_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = compress("zst", &simple_package());
+        for name in [
+            "foo-1.0-2-any.pkg.tar.zst",
+            "bar-1.0-1-any.pkg.tar.zst",
+            "foo-1.0-1-x86_64.pkg.tar.zst",
+            "foo-1.0-1-any.pkg.tar.zst.part.zst",
+        ] {
+            let path = write(dir.path(), name, &data);
+            assert!(validate_package(&path, "x86_64").is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn truncated_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let tar = package_tar(
+            "pkgname = foo\npkgver = 1.0-1\narch = any\n",
+            &["usr/bin/foo", "usr/share/foo/data"],
+        );
+        let data = compress("gz", &tar);
+        let path = write(
+            dir.path(),
+            "foo-1.0-1-any.pkg.tar.gz",
+            &data[..data.len() / 2],
+        );
+        assert!(validate_package(&path, "x86_64").is_err());
+    }
+}
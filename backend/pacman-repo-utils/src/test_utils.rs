@@ -0,0 +1,60 @@
+//! Package archives for tests, built in memory instead of committing binary packages
+
+use std::io::Write;
+use tar::{Builder, Header};
+
+pub(crate) fn package_tar(pkginfo: &str, contents: &[&str]) -> Vec<u8> {
+    let mut builder = Builder::new(Vec::new());
+    for path in std::iter::once(".PKGINFO").chain(contents.iter().copied()) {
+        let content = if path == ".PKGINFO" { pkginfo } else { path };
+        let mut header = Header::new_gnu();
+        header.set_path(path).unwrap();
+        if path.ends_with('/') {
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_size(0);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append(&header, std::io::empty()).unwrap();
+        } else {
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, content.as_bytes()).unwrap();
+        }
+    }
+    builder.into_inner().unwrap()
+}
+
+pub(crate) fn simple_package() -> Vec<u8> {
+    package_tar(
+        "pkgname = foo\npkgver = 1.0-1\narch = any\nsize = 3\n",
+        &["usr/bin/foo"],
+    )
+}
+
+pub(crate) fn compress(ext: &str, data: &[u8]) -> Vec<u8> {
+    match ext {
+        "zst" => zstd::encode_all(data, 0).unwrap(),
+        "xz" => {
+            let mut enc = xz2::write::XzEncoder::new(Vec::new(), 6);
+            enc.write_all(data).unwrap();
+            enc.finish().unwrap()
+        }
+        "gz" => {
+            let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            enc.write_all(data).unwrap();
+            enc.finish().unwrap()
+        }
+        "bz2" => {
+            let mut enc = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+            enc.write_all(data).unwrap();
+            enc.finish().unwrap()
+        }
+        "lz4" => {
+            let mut enc = lz4_flex::frame::FrameEncoder::new(Vec::new());
+            enc.write_all(data).unwrap();
+            enc.finish().unwrap()
+        }
+        _ => data.to_vec(),
+    }
+}
@@ -0,0 +1,65 @@
+#!/usr/bin/env bash
+# Rebuild the sample packages from PKGINFO/contents, sign the `signed` sample and
+# store the `desc`/`files` entries written by pacman's repo-add as expected output.
+#
+# Needs bsdtar, gzip, xz, zstd, gpg and repo-add (pacman >= 6.1 for --include-sigs).
+set -euo pipefail
+shopt -s nullglob
+
+cd "$(dirname "$0")"
+corpus=$PWD
+command -v repo-add >/dev/null || { echo "repo-add not found, run this on an Arch Linux host" >&2; exit 1; }
+
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+
+# throwaway signing key, only the signature is committed
+export GNUPGHOME=$tmp/gnupg
+mkdir -m 700 "$GNUPGHOME"
+gpg --batch --quiet --passphrase '' --quick-gen-key 'AURCache test <test@aurcache.invalid>' ed25519 sign never
+
+pkgfiles=()
+for sample in */; do
+    sample=${sample%/}
+    filename=$(sed -n 's/^pkgname = //p' "$sample/PKGINFO")-$(sed -n 's/^pkgver = //p' "$sample/PKGINFO")-$(sed -n 's/^arch = //p' "$sample/PKGINFO")
+    ext=$(cat "$sample/pkgext")
+
+    stage=$tmp/stage/$sample
+    mkdir -p "$stage"
+    cp "$sample/PKGINFO" "$stage/.PKGINFO"
+    while read -r path; do
+        case $path in
+            */) mkdir -p "$stage/$path" ;;
+            *) printf '%s\n' "$path" > "$stage/$path" ;;
+        esac
+    done < "$sample/contents"
+
+    rm -f "$sample"/*.pkg.tar.*
+    pkgfile=$corpus/$sample/$filename.pkg.tar.$ext
+    # same member order as makepkg, metadata first
+    (cd "$stage" && bsdtar --uid 0 --gid 0 --uname root --gname root -cnf - .PKGINFO $(cat "$corpus/$sample/contents")) |
+        case $ext in
+            gz) gzip -c -n ;;
+            xz) xz -c -z - ;;
+            zst) zstd -c -q -T0 --ultra -20 - ;;
+        esac > "$pkgfile"
+
+    if [[ $sample == signed ]]; then
+        gpg --batch --quiet --detach-sign --no-armor "$pkgfile"
+    fi
+    pkgfiles+=("$pkgfile")
+done
+
+repo-add --quiet --include-sigs "$tmp/repo/test.db.tar.gz" "${pkgfiles[@]}"
+
+mkdir -p "$tmp/db" "$tmp/files"
+bsdtar -xf "$tmp/repo/test.db.tar.gz" -C "$tmp/db"
+bsdtar -xf "$tmp/repo/test.files.tar.gz" -C "$tmp/files"
+for sample in */; do
+    sample=${sample%/}
+    name=$(sed -n 's/^pkgname = //p' "$sample/PKGINFO")-$(sed -n 's/^pkgver = //p' "$sample/PKGINFO")
+    cp "$tmp/db/$name/desc" "$sample/desc"
+    cp "$tmp/files/$name/files" "$sample/files"
+done
+
+repo-add --version | head -n 1 > repo-add-version